@@ -0,0 +1,43 @@
+#! /bin/bash
+# Pins the uv release Staten installs and records the SHA-256 of each
+# platform's archive, as published next to it on GitHub, in
+# src-tauri/uv-manifest.json.
+#
+#   scripts/update-uv-manifest.sh 0.5.11
+
+set -euo pipefail
+
+version="${1:?usage: $0 <uv version>}"
+base="https://github.com/astral-sh/uv/releases/download/${version}"
+manifest="$(dirname "$0")/../src-tauri/uv-manifest.json"
+
+targets=(
+  "aarch64-apple-darwin tar.gz"
+  "x86_64-apple-darwin tar.gz"
+  "aarch64-unknown-linux-gnu tar.gz"
+  "x86_64-unknown-linux-gnu tar.gz"
+  "aarch64-pc-windows-msvc zip"
+  "x86_64-pc-windows-msvc zip"
+)
+
+entries=()
+for entry in "${targets[@]}"; do
+  read -r target extension <<<"$entry"
+  checksum="$(curl -fsSL "${base}/uv-${target}.${extension}.sha256" | awk '{print tolower($1)}')"
+  if [[ ! "$checksum" =~ ^[0-9a-f]{64}$ ]]; then
+    echo "No checksum for uv-${target}.${extension}" >&2
+    exit 1
+  fi
+  entries+=("    \"${target}\": \"${checksum}\"")
+done
+
+{
+  echo "{"
+  echo "  \"version\": \"${version}\","
+  echo "  \"sha256\": {"
+  (IFS=$'\n'; echo "${entries[*]}" | sed '$!s/$/,/')
+  echo "  }"
+  echo "}"
+} >"$manifest"
+
+echo "Pinned uv ${version} in ${manifest}"
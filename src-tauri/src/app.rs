@@ -1,7 +1,11 @@
-use crate::clients::{self, ClientPathConfig, ClientType};
-use crate::environment::{ensure_environment_sync, ensure_npx_shim, get_uvx_path};
-use crate::file_utils::{ensure_config_file, ensure_mcp_servers};
-use dirs;
+use crate::clients::{self, ClientPathConfig, ClientType, ConfigScope};
+use crate::container::ContainerImage;
+use crate::download::BinaryApp;
+use crate::environment::{
+    ensure_environment_sync, ensure_npx_shim, ensure_uvx_shim, get_npx_shim_path, get_uvx_path,
+};
+use crate::file_utils::{ensure_config_file_in, ensure_mcp_servers};
+use crate::remote::{RemoteServer, RemoteTransport};
 use lazy_static::lazy_static;
 use log::{debug, error, info, warn};
 use regex;
@@ -19,7 +23,8 @@ use std::os::windows::process::CommandExt;
 use crate::environment::CREATE_NO_WINDOW;
 
 lazy_static! {
-    static ref CONFIG_CACHE: Mutex<std::collections::HashMap<ClientType, Value>> =
+    // Keyed by config file path, since a client can have several
+    static ref CONFIG_CACHE: Mutex<std::collections::HashMap<PathBuf, Value>> =
         Mutex::new(std::collections::HashMap::new());
     static ref TEST_CONFIG_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
     pub static ref APP_REGISTRY_CACHE: Mutex<Option<Value>> = Mutex::new(None);
@@ -50,7 +55,7 @@ pub fn validate_client(client: &ClientType) -> Result<(), String> {
     clients::validate_client(client)
 }
 
-fn get_config_path(client: &ClientType) -> Result<PathBuf, String> {
+pub(crate) fn get_config_path(client: &ClientType, scope: &ConfigScope) -> Result<PathBuf, String> {
     debug!(
         "Getting config path for client {}, test_mode: {}",
         client.as_str(),
@@ -60,6 +65,10 @@ fn get_config_path(client: &ClientType) -> Result<PathBuf, String> {
     // Validate client
     clients::validate_client(client)?;
 
+    if let ConfigScope::Project(project_dir) = scope {
+        return clients::project_config_path(client, project_dir);
+    }
+
     // Check if we have a test config path set
     let test_path = TEST_CONFIG_PATH.lock().unwrap();
     if let Some(path) = test_path.clone() {
@@ -84,8 +93,21 @@ fn get_config_path(client: &ClientType) -> Result<PathBuf, String> {
 #[derive(Clone, Debug)]
 pub struct AppConfig {
     pub mcp_key: String,
+    pub runtime: String,
     pub command: String,
     pub args: Vec<String>,
+    /// Set for servers that are reached over the network instead of started
+    /// locally. Header values may hold ${VAR} placeholders.
+    pub remote: Option<RemoteServer>,
+    /// Set for apps that run from a container image
+    pub container: Option<ContainerImage>,
+    /// Set for apps that run a downloaded executable
+    pub binary: Option<BinaryApp>,
+    /// Node.js version of an npx app whose `engines.node` range the default
+    /// version doesn't satisfy
+    pub node_version: Option<String>,
+    /// `requires-python` of a uvx app
+    pub python: Option<String>,
 }
 
 fn fetch_app_registry() -> Result<Value, String> {
@@ -96,24 +118,40 @@ fn fetch_app_registry() -> Result<Value, String> {
         return Ok(registry.clone());
     }
 
-    // Fetch the registry from GitHub
-    let registry_url =
-        "https://raw.githubusercontent.com/aerugo/staten.ai-app-registry/refs/heads/main/apps.json";
-    info!("Fetching app registry from {}", registry_url);
-    let response = get(registry_url).map_err(|e| {
-        error!("Failed to fetch app registry: {}", e);
-        format!("Failed to fetch app registry: {}", e)
-    })?;
+    // Try each configured registry source in order
+    let sources = crate::settings::load_settings()
+        .map(|settings| settings.registry_sources)
+        .unwrap_or_else(|e| {
+            warn!("Using the default registry: {}", e);
+            vec![crate::settings::DEFAULT_REGISTRY_URL.to_string()]
+        });
 
-    let registry_json: Value = response.json().map_err(|e| {
-        error!("Failed to parse app registry JSON: {}", e);
-        format!("Failed to parse app registry JSON: {}", e)
-    })?;
+    let mut last_error = "No registry sources configured".to_string();
+    for registry_url in &sources {
+        info!("Fetching app registry from {}", registry_url);
+        let registry_json = get(registry_url.as_str())
+            .map_err(|e| format!("Failed to fetch app registry: {}", e))
+            .and_then(|response| {
+                response
+                    .json::<Value>()
+                    .map_err(|e| format!("Failed to parse app registry JSON: {}", e))
+            });
+
+        match registry_json {
+            Ok(registry_json) => {
+                // Cache the registry
+                *cache = Some(registry_json.clone());
+                info!("Successfully fetched and cached app registry");
+                return Ok(registry_json);
+            }
+            Err(e) => {
+                error!("{} ({})", e, registry_url);
+                last_error = e;
+            }
+        }
+    }
 
-    // Cache the registry
-    *cache = Some(registry_json.clone());
-    info!("Successfully fetched and cached app registry");
-    Ok(registry_json)
+    Err(last_error)
 }
 
 fn ensure_env_setup() -> Result<(), String> {
@@ -175,6 +213,117 @@ fn replace_env_vars(input: &str, env: &serde_json::Value) -> String {
     result
 }
 
+pub(crate) fn merge_env(existing_env: Value, env_vars: Option<Value>) -> Value {
+    if let Some(new_env) = env_vars {
+        let mut merged = existing_env
+            .as_object()
+            .unwrap_or(&serde_json::Map::new())
+            .clone();
+        for (k, v) in new_env.as_object().unwrap_or(&serde_json::Map::new()) {
+            merged.insert(k.clone(), v.clone());
+        }
+        serde_json::Value::Object(merged)
+    } else {
+        existing_env
+    }
+}
+
+// Builds the `mcpServers` entry for an app, with ${VAR} placeholders in the
+// registry args replaced by values from `env`.
+pub(crate) fn build_server_entry(config: &AppConfig, env: Value) -> Value {
+    if let Some(remote) = &config.remote {
+        let mut remote = remote.clone();
+        remote.url = replace_env_vars(&remote.url, &env);
+        for value in remote.headers.values_mut() {
+            *value = replace_env_vars(value, &env);
+        }
+        return remote.to_entry();
+    }
+
+    let mut processed_args = config
+        .args
+        .iter()
+        .map(|arg| replace_env_vars(arg, &env))
+        .collect::<Vec<String>>();
+    if let Some(container) = &config.container {
+        processed_args = container.run_args(&env, &processed_args);
+    }
+    if let Some(python) = &config.python {
+        processed_args.splice(0..0, crate::python::python_args(python));
+    }
+
+    json!({
+        "command": config.command,
+        "args": processed_args,
+        "env": env
+    })
+}
+
+// Project configs are used on other machines, so they run the package
+// runners from PATH instead of Staten's shims. Apps that need a particular
+// Node.js or Python, an image or a download have no such command.
+fn portable_command(config: &AppConfig) -> Option<&'static str> {
+    match config.runtime.as_str() {
+        "npx" if config.node_version.is_none() => Some("npx"),
+        "uvx" if config.python.is_none() => Some("uvx"),
+        _ => None,
+    }
+}
+
+// Recovers the values that were filled into the ${VAR} placeholders of
+// `template`, given the filled in `value`.
+fn match_env_vars(template: &str, value: &str, env: &mut serde_json::Map<String, Value>) {
+    let re = regex::Regex::new(r"\$\{([^}]+)\}").unwrap();
+    let mut pattern = String::from("^");
+    let mut names = Vec::new();
+    let mut last = 0;
+    for captures in re.captures_iter(template) {
+        let full_match = captures.get(0).unwrap();
+        pattern.push_str(&regex::escape(&template[last..full_match.start()]));
+        pattern.push_str("(.*)");
+        names.push(captures[1].to_string());
+        last = full_match.end();
+    }
+    pattern.push_str(&regex::escape(&template[last..]));
+    pattern.push('$');
+
+    let Some(captures) = regex::Regex::new(&pattern)
+        .ok()
+        .and_then(|matcher| matcher.captures(value))
+    else {
+        return;
+    };
+    for (i, name) in names.into_iter().enumerate() {
+        let found = &captures[i + 1];
+        // Still a placeholder, so it was never set
+        if !found.contains("${") {
+            env.insert(name, json!(found));
+        }
+    }
+}
+
+// The env values of an installed server. Remote servers have no environment,
+// their values are read back out of the url and headers.
+pub(crate) fn installed_env(config: &AppConfig, entry: Option<&Value>) -> Value {
+    let Some(entry) = entry else {
+        return json!({});
+    };
+    if let Some(env) = entry.get("env") {
+        return env.clone();
+    }
+
+    let mut env = serde_json::Map::new();
+    if let (Some(template), Some(installed)) = (&config.remote, RemoteServer::from_entry(entry)) {
+        match_env_vars(&template.url, &installed.url, &mut env);
+        for (name, value) in &template.headers {
+            if let Some(installed_value) = installed.headers.get(name) {
+                match_env_vars(value, installed_value, &mut env);
+            }
+        }
+    }
+    Value::Object(env)
+}
+
 pub fn get_app_configs() -> Result<Vec<(String, AppConfig)>, String> {
     debug!(
         "Getting app configurations, test_mode: {}",
@@ -185,21 +334,22 @@ pub fn get_app_configs() -> Result<Vec<(String, AppConfig)>, String> {
     ensure_env_setup()?;
 
     // In test mode, use test paths directly
-    let (npx_shim, uvx_path) = if crate::environment::is_test_mode() {
-        debug!("Using test paths for npx_shim and uvx_path");
+    let (npx_shim, uvx_shim) = if crate::environment::is_test_mode() {
+        debug!("Using test paths for npx_shim and uvx_shim");
         (
             "/test/.local/share/staten/bin/npx-staten".to_string(),
-            "/test/.local/bin/uvx".to_string(),
+            "/test/.local/share/staten/bin/uvx-staten".to_string(),
         )
     } else {
-        // Get absolute paths, and fail if they can't be obtained
-        let npx_shim = ensure_npx_shim()?;
-        let uvx_path = get_uvx_path()?;
-        (npx_shim, uvx_path)
+        // Writing the npx shim may download Node.js, which is left to
+        // environment setup and install
+        let npx_shim = get_npx_shim_path().to_string_lossy().to_string();
+        let uvx_shim = ensure_uvx_shim()?;
+        (npx_shim, uvx_shim)
     };
 
     info!("Using npx_shim: {}", npx_shim);
-    info!("Using uvx_path: {}", uvx_path);
+    info!("Using uvx_shim: {}", uvx_shim);
 
     let registry = fetch_app_registry()?;
     let apps = registry.as_array().ok_or_else(|| {
@@ -209,6 +359,8 @@ pub fn get_app_configs() -> Result<Vec<(String, AppConfig)>, String> {
     })?;
 
     let mut configs = Vec::new();
+    let mut container_engine = None;
+    let default_node = crate::node::default_node_version();
 
     for app in apps {
         let name = app["name"]
@@ -221,19 +373,142 @@ pub fn get_app_configs() -> Result<Vec<(String, AppConfig)>, String> {
             .as_str()
             .ok_or("mcpKey is missing")?
             .to_string();
+
+        match config.get("transport").and_then(|t| t.as_str()) {
+            None | Some("stdio") => {}
+            Some(transport) => {
+                let transport = RemoteTransport::parse(transport)
+                    .ok_or_else(|| format!("Unknown transport for {}: {}", name, transport))?;
+                let url = config
+                    .get("url")
+                    .and_then(|u| u.as_str())
+                    .ok_or("url is missing")?
+                    .to_string();
+                let headers = config
+                    .get("headers")
+                    .and_then(|h| h.as_object())
+                    .map(|headers| {
+                        headers
+                            .iter()
+                            .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                debug!(
+                    "Configured remote app '{}' at {} over {}",
+                    name,
+                    url,
+                    transport.as_str()
+                );
+                configs.push((
+                    name,
+                    AppConfig {
+                        mcp_key,
+                        runtime: "remote".to_string(),
+                        command: String::new(),
+                        args: Vec::new(),
+                        container: None,
+                        binary: None,
+                        node_version: None,
+                        python: None,
+                        remote: Some(RemoteServer {
+                            transport,
+                            url,
+                            headers,
+                            oauth: config.get("auth").and_then(|a| a.as_str()) == Some("oauth"),
+                        }),
+                    },
+                ));
+                continue;
+            }
+        }
+
         let runtime = config["runtime"].as_str().ok_or("runtime is missing")?;
 
+        let container = match runtime {
+            "docker" => Some(ContainerImage::from_registry(app, config)?),
+            _ => None,
+        };
+        let binary = match runtime {
+            "binary" => Some(BinaryApp::from_registry(&mcp_key, config)?),
+            _ => None,
+        };
+
+        // npx apps whose `engines.node` range the default version doesn't
+        // satisfy run on a version of their own
+        let mut node_version = None;
+        let mut node_missing = false;
+        if let Some(range) = config
+            .get("engines")
+            .and_then(|engines| engines.get("node"))
+            .and_then(|range| range.as_str())
+            .filter(|_| runtime == "npx")
+        {
+            match crate::node::resolve_node_version(range) {
+                Ok(version) if version != default_node => node_version = Some(version),
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("No Node.js version for {}: {}", name, e);
+                    node_missing = true;
+                }
+            }
+        }
+
+        let mut python_invalid = false;
+        let python = match config
+            .get("requires-python")
+            .and_then(|requirement| requirement.as_str())
+            .filter(|_| runtime == "uvx")
+            .map(crate::python::parse_requirement)
+        {
+            Some(Ok(requirement)) => Some(requirement),
+            Some(Err(e)) => {
+                warn!("{}: {}", name, e);
+                python_invalid = true;
+                None
+            }
+            None => None,
+        };
+
         let command = match runtime {
-            "npx" => npx_shim.clone(),
-            "uvx" => uvx_path.clone(),
+            // Left empty, so the app shows as not configured
+            "npx" if node_missing => String::new(),
+            "uvx" if python_invalid => String::new(),
+            "npx" => match &node_version {
+                Some(version) => crate::environment::get_versioned_npx_shim_path(version)
+                    .to_string_lossy()
+                    .to_string(),
+                None => npx_shim.clone(),
+            },
+            "uvx" if python.is_some() => crate::environment::get_uvx_python_shim_path()
+                .to_string_lossy()
+                .to_string(),
+            "uvx" => uvx_shim.clone(),
+            // Left empty without an engine, so the app shows as not configured
+            "docker" => container_engine
+                .get_or_insert_with(crate::environment::find_container_engine)
+                .as_ref()
+                .map(|engine| engine.path.clone())
+                .unwrap_or_default(),
+            // Left empty when there is no build for this platform
+            "binary" => binary
+                .as_ref()
+                .and_then(|binary| binary.executable())
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default(),
             _ => runtime.to_string(),
         };
 
-        let args_value = config["args"].as_array().ok_or("args is missing")?;
-        let args: Vec<String> = args_value
-            .iter()
-            .map(|arg| arg.as_str().unwrap_or("").to_string())
-            .collect();
+        // Images and executables can run without args
+        let args: Vec<String> = match config["args"].as_array() {
+            Some(args_value) => args_value
+                .iter()
+                .map(|arg| arg.as_str().unwrap_or("").to_string())
+                .collect(),
+            None if container.is_some() || binary.is_some() => Vec::new(),
+            None => return Err("args is missing".to_string()),
+        };
 
         debug!(
             "Configured app '{}' with command: '{}', args: {:?}",
@@ -243,8 +518,14 @@ pub fn get_app_configs() -> Result<Vec<(String, AppConfig)>, String> {
             name,
             AppConfig {
                 mcp_key,
+                runtime: runtime.to_string(),
                 command,
                 args,
+                remote: None,
+                container,
+                binary,
+                node_version,
+                python,
             },
         ));
     }
@@ -253,7 +534,20 @@ pub fn get_app_configs() -> Result<Vec<(String, AppConfig)>, String> {
     Ok(configs)
 }
 
+/// Whether the client's user config file exists.
+pub fn has_config(client: &ClientType) -> bool {
+    has_scoped_config(client, &ConfigScope::User)
+}
+
+pub fn has_scoped_config(client: &ClientType, scope: &ConfigScope) -> bool {
+    get_config_path(client, scope).is_ok_and(|path| path.exists())
+}
+
 pub fn get_config(client: &ClientType) -> Result<Value, String> {
+    get_scoped_config(client, &ConfigScope::User)
+}
+
+pub fn get_scoped_config(client: &ClientType, scope: &ConfigScope) -> Result<Value, String> {
     debug!(
         "Getting config for client {}, test_mode: {}",
         client.as_str(),
@@ -263,18 +557,24 @@ pub fn get_config(client: &ClientType) -> Result<Value, String> {
     // Validate client
     validate_client(client)?;
 
+    let config_path = get_config_path(client, scope)?;
+    let cacheable = crate::config_format::is_cacheable(client);
     let mut cache = CONFIG_CACHE.lock().unwrap();
-    if let Some(config) = cache.get(client) {
+    if let Some(config) = cache.get(&config_path).filter(|_| cacheable) {
         debug!("Using cached config for client {}", client.as_str());
         return Ok(config.clone());
     }
 
-    let config_path = get_config_path(client)?;
     debug!("Using config path: {}", config_path.display());
 
+    let config_format = crate::config_format::for_client(client);
     if !config_path.exists() {
         info!("Config file does not exist, creating it");
-        ensure_config_file(&config_path)?;
+        ensure_config_file_in(
+            &config_path,
+            config_format.as_ref(),
+            &crate::client_format::initial_config(client),
+        )?;
     }
 
     let config_str = fs::read_to_string(&config_path).map_err(|e| {
@@ -282,35 +582,47 @@ pub fn get_config(client: &ClientType) -> Result<Value, String> {
         format!("Failed to read config file: {}", e)
     })?;
 
-    let mut config_json: Value = serde_json::from_str(&config_str).map_err(|e| {
-        error!("Failed to parse config JSON: {}", e);
-        format!("Failed to parse config JSON: {}", e)
+    let config_json = config_format.parse(&config_str).map_err(|e| {
+        error!("Failed to parse config {}: {}", config_format.name(), e);
+        format!("Failed to parse config {}: {}", config_format.name(), e)
     })?;
 
+    let mut config_json = crate::client_format::from_client_format(client, config_json);
     ensure_mcp_servers(&mut config_json)?;
 
-    cache.insert(client.clone(), config_json.clone());
-    debug!(
-        "Config for client {} loaded and cached successfully",
-        client.as_str()
-    );
+    if cacheable {
+        cache.insert(config_path, config_json.clone());
+    }
+    debug!("Config for client {} loaded successfully", client.as_str());
     Ok(config_json)
 }
 
 pub fn save_config(config: &Value, client: &ClientType) -> Result<(), String> {
+    save_scoped_config(config, client, &ConfigScope::User)
+}
+
+pub fn save_scoped_config(
+    config: &Value,
+    client: &ClientType,
+    scope: &ConfigScope,
+) -> Result<(), String> {
     validate_client(client)?;
 
-    let config_path = get_config_path(client)?;
+    let config_path = get_config_path(client, scope)?;
     debug!(
         "Saving config for client {} to {}",
         client.as_str(),
         config_path.display()
     );
 
-    let updated_config = serde_json::to_string_pretty(config).map_err(|e| {
-        error!("Failed to serialize config: {}", e);
-        format!("Failed to serialize config: {}", e)
-    })?;
+    let native_config = crate::client_format::to_client_format(client, config);
+    let previous = fs::read_to_string(&config_path).ok();
+    let updated_config = crate::config_format::for_client(client)
+        .render(&native_config, previous.as_deref())
+        .map_err(|e| {
+            error!("Failed to serialize config: {}", e);
+            format!("Failed to serialize config: {}", e)
+        })?;
 
     fs::write(&config_path, updated_config).map_err(|e| {
         error!("Failed to write config file: {}", e);
@@ -318,21 +630,30 @@ pub fn save_config(config: &Value, client: &ClientType) -> Result<(), String> {
     })?;
 
     // Update cache
-    let mut cache = CONFIG_CACHE.lock().unwrap();
-    cache.insert(client.clone(), config.clone());
+    if crate::config_format::is_cacheable(client) {
+        let mut cache = CONFIG_CACHE.lock().unwrap();
+        cache.insert(config_path, config.clone());
+    }
     info!("Config for client {} saved successfully", client.as_str());
 
     Ok(())
 }
 
-#[tauri::command]
-pub fn restart_client_app(client: &str) -> Result<String, String> {
+pub fn restart_client(client: &str) -> Result<String, String> {
     let client_type =
         ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
     clients::restart_client_app(&client_type)
         .map_err(|e| format!("Failed to restart client app: {}", e))
 }
 
+#[tauri::command]
+pub async fn restart_client_app(client: String) -> Result<String, String> {
+    // Waiting for the client to quit and come back must not hold up the UI
+    tauri::async_runtime::spawn_blocking(move || restart_client(&client))
+        .await
+        .map_err(|e| format!("Failed to restart client app: {}", e))?
+}
+
 #[tauri::command]
 pub fn preload_dependencies() -> Result<(), String> {
     info!("Preloading dependencies");
@@ -350,8 +671,10 @@ pub fn preload_dependencies() -> Result<(), String> {
 
         #[cfg(target_os = "windows")]
         {
-            // Use the npm executable from the NVM installation if available
-            if let Ok((_, npx_path)) = crate::environment::get_nvm_node_paths() {
+            // Use the npm executable from the managed Node.js installation if available
+            if let Ok((_, npx_path)) =
+                crate::environment::get_node_paths(&crate::node::default_node_version())
+            {
                 let npm_path = std::path::Path::new(&npx_path)
                     .parent()
                     .map(|p| p.join("npm.cmd"))
@@ -397,10 +720,23 @@ pub fn preload_dependencies() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn install(
+pub async fn install(
+    app_name: String,
+    env_vars: Option<serde_json::Value>,
+    client: String,
+    scope: Option<String>,
+) -> Result<String, String> {
+    // Binaries, Node.js and Python interpreters may be downloaded first
+    tauri::async_runtime::spawn_blocking(move || install_app(&app_name, env_vars, &client, scope))
+        .await
+        .map_err(|e| format!("Failed to install app: {}", e))?
+}
+
+pub fn install_app(
     app_name: &str,
     env_vars: Option<serde_json::Value>,
     client: &str,
+    scope: Option<String>,
 ) -> Result<String, String> {
     info!("Installing app: {} for client: {}", app_name, client);
     debug!(
@@ -412,9 +748,10 @@ pub fn install(
 
     let client_type =
         ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
+    let scope = ConfigScope::parse(scope.as_deref())?;
     let configs = get_app_configs()?;
     if let Some((_, config)) = configs.iter().find(|(name, _)| name == app_name) {
-        let mut config_json = get_config(&client_type)?;
+        let mut config_json = get_scoped_config(&client_type, &scope)?;
         let mcp_key = config.mcp_key.clone();
         let command = config.command.clone();
         let args = config.args.clone();
@@ -424,8 +761,35 @@ pub fn install(
             app_name, command, args
         );
 
-        // Skip path validation entirely in test mode
-        if !crate::environment::is_test_mode() {
+        // Test mode's placeholder paths aren't validated, remote apps have no path
+        if config.container.is_some() && command.is_empty() {
+            return Err(format!(
+                "{} runs in a container, but neither Docker nor Podman was found",
+                app_name
+            ));
+        } else if config.remote.is_some() {
+            debug!("{} is a remote app, nothing to validate", app_name);
+        } else if let Some(binary) = &config.binary {
+            // Downloaded before the entry is written, so clients never start
+            // a missing executable
+            crate::download::ensure_binary(binary)?;
+        } else if config.runtime == "npx" && command.is_empty() {
+            return Err(format!(
+                "No Node.js version matches the engines.node range of {}",
+                app_name
+            ));
+        } else if config.runtime == "uvx" && command.is_empty() {
+            return Err(format!("{} has an invalid requires-python", app_name));
+        } else if let Some(python) = &config.python {
+            // Installed up front, so the first start doesn't sit through the
+            // download. The command is the shim written here.
+            crate::python::install_python(python)?;
+            crate::environment::ensure_uvx_python_shim()?;
+        } else if let Some(version) = &config.node_version {
+            crate::environment::ensure_versioned_npx_shim(version)?;
+        } else if config.runtime == "npx" {
+            ensure_npx_shim()?;
+        } else if !crate::environment::is_test_placeholder(std::path::Path::new(&command)) {
             if !std::path::Path::new(&command).exists() {
                 error!(
                     "Command path '{}' for app '{}' does not exist",
@@ -445,50 +809,56 @@ pub fn install(
             .and_then(|v| v.as_object_mut())
         {
             // Get existing environment variables for this app if any
-            let existing_env = if let Some(server_config) = mcp_servers.get(&mcp_key) {
-                if let Some(env) = server_config.get("env") {
-                    env.clone()
-                } else {
-                    json!({})
-                }
-            } else {
-                json!({})
-            };
+            let existing_env = installed_env(config, mcp_servers.get(&mcp_key));
 
             // Merge with provided env_vars if any
-            let env = if let Some(new_env) = env_vars {
-                let mut merged = existing_env
-                    .as_object()
-                    .unwrap_or(&serde_json::Map::new())
-                    .clone();
-                for (k, v) in new_env.as_object().unwrap_or(&serde_json::Map::new()) {
-                    merged.insert(k.clone(), v.clone());
+            let mut env = merge_env(existing_env, env_vars);
+            let mut notes = Vec::new();
+            if crate::client_format::uses_inputs(&client_type) {
+                env = crate::client_format::input_env(&env, &mcp_key);
+            } else if let ConfigScope::Project(_) = &scope {
+                let (project_env, secrets) = crate::client_format::project_env(&client_type, &env);
+                env = project_env;
+                if !secrets.is_empty() {
+                    notes.push(format!(
+                        "Set {} in the environment, the project config only refers to it",
+                        secrets.join(", ")
+                    ));
                 }
-                serde_json::Value::Object(merged)
-            } else {
-                existing_env
-            };
-
-            // Process args to replace environment variables
-            let processed_args = args
-                .iter()
-                .map(|arg| replace_env_vars(arg, &env))
-                .collect::<Vec<String>>();
-
-            let app_config = json!({
-                "command": command,
-                "args": processed_args,
-                "env": env
-            });
+            }
+            let mut app_config = build_server_entry(config, env);
+            if let ConfigScope::Project(_) = &scope {
+                match portable_command(config) {
+                    Some(runner) => app_config["command"] = json!(runner),
+                    None if config.remote.is_none() => notes.push(format!(
+                        "The project config runs {}, which only exists on this machine",
+                        command
+                    )),
+                    None => {}
+                }
+            }
 
             debug!("Adding config for {}: {:?}", mcp_key, app_config);
             mcp_servers.insert(mcp_key.clone(), app_config);
-            save_config(&config_json, &client_type)?;
+            if crate::client_format::uses_inputs(&client_type) {
+                crate::client_format::prompt_for_secrets(&mut config_json, &mcp_key);
+            }
+            save_scoped_config(&config_json, &client_type, &scope)?;
+            if let Some(binary) = &config.binary {
+                remove_unused_binary_versions(binary);
+            }
 
-            // Only attempt to pre-cache npm packages if not in test mode
+            // Only attempt to pre-cache npm packages and images if not in test mode
             if !crate::environment::is_test_mode() {
+                let image = config.container.as_ref().map(|c| c.reference());
                 std::thread::spawn(move || {
-                    if command.contains("npx") && args.len() > 1 {
+                    if let Some(image) = image {
+                        // The first start would otherwise sit through the pull
+                        info!("Pulling container image: {}", image);
+                        let _ = crate::environment::create_windowless_command(&command)
+                            .args(["pull", &image])
+                            .output();
+                    } else if command.contains("npx") && args.len() > 1 {
                         let package = &args[1];
                         info!("Pre-caching npm package: {}", package);
 
@@ -508,7 +878,16 @@ pub fn install(
                 "Successfully installed app: {} for client: {}",
                 app_name, client
             );
-            Ok(format!("Added {} configuration for {}", mcp_key, app_name))
+            if let ConfigScope::Project(project_dir) = &scope {
+                crate::projects::remember_project(project_dir);
+            }
+            crate::onboarding::record_step(crate::onboarding::OnboardingStep::FirstAppInstalled);
+            let mut message = format!("Added {} configuration for {}", mcp_key, app_name);
+            for note in notes {
+                warn!("{}: {}", app_name, note);
+                message = format!("{}. {}", message, note);
+            }
+            Ok(message)
         } else {
             let err = "Failed to find mcpServers in config".to_string();
             error!("{}", err);
@@ -522,21 +901,30 @@ pub fn install(
 }
 
 #[tauri::command]
-pub fn uninstall(app_name: &str, client: &str) -> Result<String, String> {
+pub fn uninstall(app_name: &str, client: &str, scope: Option<String>) -> Result<String, String> {
     info!("Uninstalling app: {} for client: {:?}", app_name, client);
 
     let client_type =
         ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
+    let scope = ConfigScope::parse(scope.as_deref())?;
 
     if let Some((_, config)) = get_app_configs()?.iter().find(|(name, _)| name == app_name) {
-        let mut config_json = get_config(&client_type)?;
+        let mut config_json = get_scoped_config(&client_type, &scope)?;
+        crate::client_format::remove_secret_inputs(&mut config_json, &config.mcp_key);
 
         if let Some(mcp_servers) = config_json
             .get_mut("mcpServers")
             .and_then(|v| v.as_object_mut())
         {
             if mcp_servers.remove(&config.mcp_key).is_some() {
-                save_config(&config_json, &client_type)?;
+                save_scoped_config(&config_json, &client_type, &scope)?;
+                if let Some(binary) = &config.binary {
+                    if is_installed_anywhere(&config.mcp_key) {
+                        remove_unused_binary_versions(binary);
+                    } else {
+                        crate::download::remove_binary(&config.mcp_key)?;
+                    }
+                }
                 info!(
                     "Successfully uninstalled app: {} for client: {}",
                     app_name, client
@@ -560,6 +948,64 @@ pub fn uninstall(app_name: &str, client: &str) -> Result<String, String> {
     }
 }
 
+// Whether any client config, user or remembered project, or the gateway still
+// has the app. Downloaded files are only removed once nothing uses them anymore.
+fn is_installed_anywhere(mcp_key: &str) -> bool {
+    !installed_entries(mcp_key).is_empty()
+        || crate::gateway::load_gateway_config()
+            .is_ok_and(|gateway| gateway.apps.contains_key(mcp_key))
+}
+
+// The app's entries in every client config, user or remembered project
+fn installed_entries(mcp_key: &str) -> Vec<Value> {
+    let mut scopes = vec![ConfigScope::User];
+    if let Ok(settings) = crate::settings::load_settings() {
+        scopes.extend(
+            settings
+                .projects
+                .iter()
+                .map(|dir| ConfigScope::Project(PathBuf::from(dir))),
+        );
+    }
+
+    let mut entries = Vec::new();
+    for client in ClientType::all() {
+        for scope in &scopes {
+            if !has_scoped_config(&client, scope) {
+                continue;
+            }
+            if let Some(entry) = get_scoped_config(&client, scope)
+                .ok()
+                .and_then(|config| config["mcpServers"].get(mcp_key).cloned())
+            {
+                entries.push(entry);
+            }
+        }
+    }
+    entries
+}
+
+/// Removes downloaded versions of `binary` that neither a client config nor
+/// the gateway starts anymore.
+pub(crate) fn remove_unused_binary_versions(binary: &BinaryApp) {
+    let mut commands: Vec<PathBuf> = installed_entries(&binary.name)
+        .iter()
+        .filter_map(|entry| entry["command"].as_str())
+        .map(PathBuf::from)
+        .collect();
+    if let Ok(gateway) = crate::gateway::load_gateway_config() {
+        commands.extend(
+            gateway
+                .apps
+                .get(&binary.name)
+                .map(|app| PathBuf::from(&app.command)),
+        );
+    }
+    crate::download::remove_other_versions(binary, |dir| {
+        commands.iter().any(|command| command.starts_with(dir))
+    });
+}
+
 #[tauri::command]
 pub fn is_installed(app_name: &str, client: &str) -> Result<bool, String> {
     debug!(
@@ -610,7 +1056,28 @@ pub fn save_app_env(
             .get_mut("mcpServers")
             .and_then(|v| v.as_object_mut())
         {
-            if let Some(server_config) = mcp_servers
+            if config.remote.is_some() || config.container.is_some() {
+                // Remote apps take their values in the url and headers, and
+                // containers list the variables they get, so the entry is
+                // built again
+                if let Some(server_config) = mcp_servers.get_mut(&mcp_key) {
+                    let mut env =
+                        merge_env(installed_env(config, Some(server_config)), Some(env_values));
+                    if crate::client_format::uses_inputs(&client_type) {
+                        env = crate::client_format::input_env(&env, &mcp_key);
+                    }
+                    *server_config = build_server_entry(config, env);
+                    if crate::client_format::uses_inputs(&client_type) {
+                        crate::client_format::prompt_for_secrets(&mut config_json, &mcp_key);
+                    }
+                    save_config(&config_json, &client_type)?;
+                    info!(
+                        "Successfully saved ENV values for app: {} for client: {}",
+                        app_name, client
+                    );
+                    return Ok(format!("Saved ENV values for app '{}'", app_name));
+                }
+            } else if let Some(server_config) = mcp_servers
                 .get_mut(&mcp_key)
                 .and_then(|v| v.as_object_mut())
             {
@@ -626,6 +1093,9 @@ pub fn save_app_env(
                             env.insert(key.clone(), value.clone());
                         }
 
+                        if crate::client_format::uses_inputs(&client_type) {
+                            crate::client_format::prompt_for_secrets(&mut config_json, &mcp_key);
+                        }
                         save_config(&config_json, &client_type)?;
                         info!(
                             "Successfully saved ENV values for app: {} for client: {}",
@@ -660,11 +1130,8 @@ pub fn get_app_env(app_name: &str, client: &str) -> Result<Value, String> {
         let mcp_key = config.mcp_key.clone();
 
         if let Some(mcp_servers) = config_json.get("mcpServers").and_then(|v| v.as_object()) {
-            if let Some(server_config) = mcp_servers.get(&mcp_key).and_then(|v| v.as_object()) {
-                if let Some(env) = server_config.get("env") {
-                    return Ok(env.clone());
-                }
-                return Ok(json!({}));
+            if let Some(server_config) = mcp_servers.get(&mcp_key) {
+                return Ok(installed_env(config, Some(server_config)));
             }
             return Err(format!("App '{}' is not installed", app_name));
         } else {
@@ -676,7 +1143,68 @@ pub fn get_app_env(app_name: &str, client: &str) -> Result<Value, String> {
 }
 
 #[tauri::command]
-pub fn get_app_statuses(client: &str) -> Result<Value, String> {
+pub fn set_app_logging(app_name: &str, client: &str, enabled: bool) -> Result<String, String> {
+    info!(
+        "Setting log capture for app: {} for client: {} to {}",
+        app_name, client, enabled
+    );
+
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+
+    let configs = get_app_configs()?;
+    let (_, config) = configs
+        .iter()
+        .find(|(name, _)| name == app_name)
+        .ok_or_else(|| format!("No configuration available for '{}'", app_name))?;
+
+    let mut config_json = get_config(&client_type)?;
+    let server_config = config_json
+        .get_mut("mcpServers")
+        .and_then(|v| v.as_object_mut())
+        .ok_or("Failed to find mcpServers in config")?
+        .get_mut(&config.mcp_key)
+        .and_then(|v| v.as_object_mut())
+        .ok_or_else(|| format!("App '{}' is not installed", app_name))?;
+
+    if !server_config.contains_key("env") {
+        server_config.insert("env".to_string(), json!({}));
+    }
+    let env = server_config
+        .get_mut("env")
+        .and_then(|v| v.as_object_mut())
+        .ok_or("Invalid env format in config")?;
+
+    // The shims name the log folder after the value of this variable
+    if enabled {
+        env.insert(crate::logs::LOG_APP_ENV.to_string(), json!(config.mcp_key));
+    } else {
+        env.remove(crate::logs::LOG_APP_ENV);
+    }
+
+    save_config(&config_json, &client_type)?;
+    Ok(format!(
+        "Log capture {} for '{}'",
+        if enabled { "enabled" } else { "disabled" },
+        app_name
+    ))
+}
+
+#[tauri::command]
+pub fn get_app_logging(app_name: &str, client: &str) -> Result<bool, String> {
+    let env = get_app_env(app_name, client)?;
+    Ok(env.get(crate::logs::LOG_APP_ENV).is_some())
+}
+
+#[tauri::command]
+pub async fn get_app_statuses(client: String, scope: Option<String>) -> Result<Value, String> {
+    // The first call sets up the environment, which may download Node.js
+    tauri::async_runtime::spawn_blocking(move || app_statuses(&client, scope))
+        .await
+        .map_err(|e| format!("Failed to get app statuses: {}", e))?
+}
+
+pub fn app_statuses(client: &str, scope: Option<String>) -> Result<Value, String> {
     debug!(
         "Getting app statuses for client: {:?}, test_mode: {}",
         client,
@@ -686,9 +1214,11 @@ pub fn get_app_statuses(client: &str) -> Result<Value, String> {
     let client_type =
         ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
 
+    let scope = ConfigScope::parse(scope.as_deref())?;
+
     ensure_env_setup()?;
 
-    let config_json = get_config(&client_type)?;
+    let config_json = get_scoped_config(&client_type, &scope)?;
     let mut installed_apps = json!({});
     let mut configured_apps = json!({});
 
@@ -707,7 +1237,8 @@ pub fn get_app_statuses(client: &str) -> Result<Value, String> {
     if let Some(mcp_servers) = config_json.get("mcpServers").and_then(|v| v.as_object()) {
         for (app_name, config) in app_configs {
             installed_apps[&app_name] = json!(mcp_servers.contains_key(&config.mcp_key));
-            configured_apps[&app_name] = json!(!config.command.is_empty());
+            configured_apps[&app_name] =
+                json!(!config.command.is_empty() || config.remote.is_some());
         }
     }
 
@@ -782,6 +1313,7 @@ pub fn install_staten_mcp(client: &str) -> Result<String, String> {
             "Successfully installed staten-mcp for client: {}",
             client_type.as_str()
         );
+        crate::onboarding::record_step(crate::onboarding::OnboardingStep::StatenMcpInstalled);
         Ok("Added staten-mcp configuration".to_string())
     } else {
         let err = "Failed to find mcpServers in config".to_string();
@@ -819,44 +1351,19 @@ pub fn uninstall_staten_mcp(client: &str) -> Result<String, String> {
     }
 }
 
+pub fn mark_onboarding_completed() -> Result<(), String> {
+    crate::onboarding::complete_all()
+}
+
 #[tauri::command]
 pub fn check_onboarding_completed() -> Result<bool, String> {
-    let home = match dirs::home_dir() {
-        Some(path) => path,
-        None => return Err("Could not determine home directory".to_string()),
-    };
-
-    #[cfg(target_os = "macos")]
-    let onboarding_file = home.join(".staten/onboarding_completed");
-
-    #[cfg(target_os = "windows")]
-    let onboarding_file = home.join(".staten").join("onboarding_completed");
-
-    debug!("Checking onboarding file at: {}", onboarding_file.display());
-    Ok(onboarding_file.exists())
+    crate::onboarding::is_completed()
 }
 
 #[tauri::command]
 pub fn reset_onboarding_completed() -> Result<bool, String> {
-    let home = match dirs::home_dir() {
-        Some(path) => path,
-        None => return Err("Could not determine home directory".to_string()),
-    };
-
-    #[cfg(target_os = "macos")]
-    let onboarding_file = home.join(".staten/onboarding_completed");
-
-    #[cfg(target_os = "windows")]
-    let onboarding_file = home.join(".staten").join("onboarding_completed");
-
-    debug!(
-        "Resetting onboarding file at: {}",
-        onboarding_file.display()
-    );
-    if onboarding_file.exists() {
-        std::fs::remove_file(&onboarding_file)
-            .map_err(|e| format!("Failed to remove onboarding file: {}", e))?;
-    }
+    debug!("Resetting onboarding");
+    crate::onboarding::reset()?;
 
     Ok(true)
 }
@@ -870,6 +1377,13 @@ pub fn check_client_installed(client: &str) -> Result<bool, String> {
     clients::check_client_installed(&client_type)
 }
 
+#[tauri::command]
+pub fn client_needs_restart(client: &str) -> Result<bool, String> {
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+    Ok(client_type.needs_restart())
+}
+
 #[tauri::command]
 pub fn get_supported_clients() -> Vec<String> {
     ClientType::all_as_str()
@@ -883,6 +1397,50 @@ pub fn get_default_client_command() -> String {
     get_default_client().as_str().to_string()
 }
 
+// Rejects a path override that does not point at a usable client config
+fn validate_client_config_path(
+    client: &ClientType,
+    base_dir: &std::path::Path,
+    config_filename: &str,
+) -> Result<(), String> {
+    if config_filename.is_empty() || config_filename.contains(['/', '\\']) {
+        return Err(format!("Invalid config file name: {}", config_filename));
+    }
+    if !base_dir.is_dir() {
+        return Err(format!("Directory does not exist: {}", base_dir.display()));
+    }
+
+    let config_path = base_dir.join(config_filename);
+    if !config_path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read {}: {}", config_path.display(), e))?;
+    if content.trim().is_empty() {
+        return Ok(());
+    }
+    let config_format = crate::config_format::for_client(client);
+    let config = config_format
+        .parse(&content)
+        .map(|config| crate::client_format::from_client_format(client, config))
+        .map_err(|e| {
+            format!(
+                "{} is not valid {}: {}",
+                config_path.display(),
+                config_format.name(),
+                e
+            )
+        })?;
+    if !config.is_object() || config.get("mcpServers").is_some_and(|s| !s.is_object()) {
+        return Err(format!(
+            "{} is not a client config file",
+            config_path.display()
+        ));
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn set_client_config_path(
     client: String,
@@ -898,20 +1456,32 @@ pub fn set_client_config_path(
 
     // Create path from string
     let base_path = std::path::PathBuf::from(base_dir);
+    validate_client_config_path(&client_type, &base_path, config_filename)?;
 
     // Create the configuration
     let config = ClientPathConfig {
         base_dir: base_path,
         config_filename: config_filename.to_string(),
-        os: crate::os::OSType::default(),
+        os: crate::os::OSType::current(),
     };
 
+    // Persist the override so it survives a restart
+    crate::settings::modify_settings(|settings| {
+        settings.client_path_overrides.insert(
+            client_type.as_str().to_string(),
+            crate::settings::ClientPathOverride {
+                base_dir: base_dir.to_string(),
+                config_filename: config_filename.to_string(),
+            },
+        );
+        Ok(())
+    })?;
+
     // Set the configuration
     clients::set_client_path_config(&client_type, config)?;
 
-    // Clear the cache for this client
-    let mut cache = CONFIG_CACHE.lock().unwrap();
-    cache.remove(&client_type);
+    // The client now reads a different file, drop what was cached for it
+    CONFIG_CACHE.lock().unwrap().clear();
 
     info!(
         "Updated path configuration for client {}: base_dir={}, config_filename={}",
@@ -926,6 +1496,31 @@ pub fn set_client_config_path(
     ))
 }
 
+#[tauri::command]
+pub fn reset_client_config_path(client: String) -> Result<String, String> {
+    let client_type =
+        ClientType::from_str(&client).ok_or_else(|| format!("Invalid client: {}", client))?;
+
+    crate::settings::modify_settings(|settings| {
+        settings.client_path_overrides.remove(client_type.as_str());
+        Ok(())
+    })?;
+
+    let config = clients::get_default_client_path_config(&client_type)?;
+    clients::set_client_path_config(&client_type, config)?;
+
+    CONFIG_CACHE.lock().unwrap().clear();
+
+    info!(
+        "Reset path configuration for client {}",
+        client_type.as_str()
+    );
+    Ok(format!(
+        "Restored the default path configuration for {}",
+        client_type.as_str()
+    ))
+}
+
 #[tauri::command]
 pub fn get_client_config_path(client: &str) -> Result<Value, String> {
     let client_type =
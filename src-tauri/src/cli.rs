@@ -0,0 +1,484 @@
+use crate::app;
+use crate::clients::{self, ClientType, ConfigScope};
+use crate::doctor::Severity;
+use serde_json::{json, Map, Value};
+
+const USAGE: &str = "Usage: staten <command> [options]
+
+Commands:
+  list [--client <client>] [--json]              List registry apps
+  install <app> [--client <client>] [--project <dir>] [--env KEY=VALUE]...
+                                                 Install an app into a client
+  uninstall <app> [--client <client>] [--project <dir>]
+                                                 Remove an app from a client
+  status [--client <client>] [--project <dir>] [--json]
+                                                 Show installed apps per client
+  projects [--json]                              List known projects and their MCP configs
+  env get <app> [--client <client>]              Print an app's env values as JSON
+  env set <app> KEY=VALUE... [--client <client>] Update an app's env values
+  registry refresh                               Re-fetch the app registry
+  auth login|logout|status <app> [--json]        Sign in to a remote app that uses OAuth
+  apply <manifest> [--dry-run] [--json]          Reconcile clients with a staten.toml/json
+  doctor [--json]                                Check the local environment
+  gateway                                        Run the stdio MCP gateway
+  proxy [--transport http|sse] [--header \"Name: value\"]... <url>
+                                                 Bridge stdio to a remote MCP server
+
+Clients: Claude, Cursor, Windsurf, ClaudeCode, VSCode, Zed, Codex, Goose,
+         Continue (default: Claude)
+--project <dir> uses the project's own config: .cursor/mcp.json for Cursor,
+.vscode/mcp.json for VSCode and .mcp.json for ClaudeCode.";
+
+const COMMANDS: &[&str] = &[
+    "list",
+    "install",
+    "uninstall",
+    "status",
+    "projects",
+    "env",
+    "registry",
+    "auth",
+    "apply",
+    "doctor",
+    "gateway",
+    "proxy",
+    "help",
+    "--help",
+    "-h",
+    "--version",
+    "-V",
+];
+
+#[derive(Debug, Default)]
+struct ParsedArgs {
+    positional: Vec<String>,
+    clients: Vec<String>,
+    env: Vec<String>,
+    project: Option<String>,
+    json: bool,
+    dry_run: bool,
+}
+
+fn parse_args(args: &[String]) -> Result<ParsedArgs, String> {
+    let mut parsed = ParsedArgs::default();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--client" | "-c" => parsed
+                .clients
+                .push(iter.next().ok_or("--client requires a value")?.to_string()),
+            "--env" | "-e" => parsed
+                .env
+                .push(iter.next().ok_or("--env requires a value")?.to_string()),
+            "--project" | "-p" => {
+                parsed.project = Some(iter.next().ok_or("--project requires a value")?.to_string())
+            }
+            "--json" => parsed.json = true,
+            "--dry-run" => parsed.dry_run = true,
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ => parsed.positional.push(arg.clone()),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_env_pairs(pairs: &[String]) -> Result<Value, String> {
+    let mut env = Map::new();
+    for pair in pairs {
+        let (key, value) = pair
+            .split_once('=')
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(|| format!("Expected KEY=VALUE, got: {}", pair))?;
+        env.insert(key.to_string(), json!(value));
+    }
+    Ok(Value::Object(env))
+}
+
+fn resolve_client(parsed: &ParsedArgs) -> Result<String, String> {
+    match parsed.clients.as_slice() {
+        [] => Ok(clients::get_default_client().as_str().to_string()),
+        [client] => ClientType::from_str(client)
+            .map(|c| c.as_str().to_string())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid client: {} (expected one of {})",
+                    client,
+                    ClientType::all_as_str().join(", ")
+                )
+            }),
+        _ => Err("Only one --client may be given for this command".to_string()),
+    }
+}
+
+fn resolve_clients(parsed: &ParsedArgs) -> Result<Vec<String>, String> {
+    if parsed.clients.is_empty() {
+        return Ok(ClientType::all_as_str()
+            .iter()
+            .map(|c| c.to_string())
+            .collect());
+    }
+
+    parsed
+        .clients
+        .iter()
+        .map(|client| {
+            ClientType::from_str(client)
+                .map(|c| c.as_str().to_string())
+                .ok_or_else(|| format!("Invalid client: {}", client))
+        })
+        .collect()
+}
+
+fn app_argument(parsed: &ParsedArgs, index: usize) -> Result<String, String> {
+    parsed
+        .positional
+        .get(index)
+        .cloned()
+        .ok_or_else(|| "Missing app name".to_string())
+}
+
+fn print_json(value: &Value) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+    );
+}
+
+fn cmd_list(parsed: &ParsedArgs) -> Result<(), String> {
+    let registry = app::get_app_registry()?;
+    let apps = registry.as_array().ok_or("App registry is not an array")?;
+
+    let statuses = if parsed.clients.is_empty() {
+        None
+    } else {
+        Some(app::app_statuses(
+            &resolve_client(parsed)?,
+            parsed.project.clone(),
+        )?)
+    };
+
+    if parsed.json {
+        let listed: Vec<Value> = apps
+            .iter()
+            .map(|entry| {
+                let name = entry["name"].as_str().unwrap_or_default();
+                json!({
+                    "name": name,
+                    "description": entry["description"],
+                    "category": entry["category"],
+                    "mcpKey": entry["config"]["mcpKey"],
+                    "installed": statuses.as_ref().map(|s| s["installed"][name].clone())
+                })
+            })
+            .collect();
+        print_json(&Value::Array(listed));
+        return Ok(());
+    }
+
+    for entry in apps {
+        let name = entry["name"].as_str().unwrap_or_default();
+        let marker = match &statuses {
+            Some(s) if s["installed"][name].as_bool() == Some(true) => "* ",
+            Some(_) => "  ",
+            None => "",
+        };
+        println!(
+            "{}{:<24} {}",
+            marker,
+            name,
+            entry["description"].as_str().unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+fn cmd_status(parsed: &ParsedArgs) -> Result<(), String> {
+    let scope = ConfigScope::parse(parsed.project.as_deref())?;
+    let mut report = Map::new();
+    for client in resolve_clients(parsed)? {
+        // Without --client, skip clients that have no config to report on
+        let client_type = ClientType::from_str(&client).ok_or("Invalid client")?;
+        if parsed.clients.is_empty() && !app::has_scoped_config(&client_type, &scope) {
+            continue;
+        }
+        let statuses = app::app_statuses(&client, parsed.project.clone())?;
+        report.insert(client, statuses);
+    }
+
+    if parsed.json {
+        print_json(&Value::Object(report));
+        return Ok(());
+    }
+
+    for (client, statuses) in &report {
+        println!("{}:", client);
+        let installed: Vec<&String> = statuses["installed"]
+            .as_object()
+            .map(|apps| {
+                apps.iter()
+                    .filter(|(_, installed)| installed.as_bool() == Some(true))
+                    .map(|(name, _)| name)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if installed.is_empty() {
+            println!("  (no apps installed)");
+        }
+        for name in installed {
+            println!("  {}", name);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_projects(parsed: &ParsedArgs) -> Result<(), String> {
+    let projects = crate::projects::list_projects()?;
+    if parsed.json {
+        print_json(&projects);
+        return Ok(());
+    }
+
+    let projects = projects.as_array().cloned().unwrap_or_default();
+    if projects.is_empty() {
+        println!("No known projects");
+    }
+    for project in &projects {
+        let path = project["path"].as_str().unwrap_or_default();
+        if project["exists"].as_bool() != Some(true) {
+            println!("{} (missing)", path);
+            continue;
+        }
+        println!("{}:", path);
+        for config in project["configs"].as_array().into_iter().flatten() {
+            let servers: Vec<&str> = config["servers"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            println!(
+                "  {:<12} {}",
+                config["client"].as_str().unwrap_or_default(),
+                servers.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn cmd_env(parsed: &ParsedArgs) -> Result<(), String> {
+    let client = resolve_client(parsed)?;
+    let app_name = app_argument(parsed, 2)?;
+
+    match parsed.positional.get(1).map(String::as_str) {
+        Some("get") => {
+            print_json(&app::get_app_env(&app_name, &client)?);
+            Ok(())
+        }
+        Some("set") => {
+            let mut pairs: Vec<String> = parsed.positional[3..].to_vec();
+            pairs.extend(parsed.env.iter().cloned());
+            if pairs.is_empty() {
+                return Err("env set needs at least one KEY=VALUE".to_string());
+            }
+            println!(
+                "{}",
+                app::save_app_env(&app_name, parse_env_pairs(&pairs)?, &client)?
+            );
+            Ok(())
+        }
+        _ => Err("Expected 'env get' or 'env set'".to_string()),
+    }
+}
+
+fn cmd_auth(parsed: &ParsedArgs) -> Result<(), String> {
+    let app_name = app_argument(parsed, 2)?;
+
+    match parsed.positional.get(1).map(String::as_str) {
+        Some("login") => {
+            println!("Opening the browser to sign in to {}...", app_name);
+            println!("{}", crate::oauth::sign_in(&app_name)?);
+            Ok(())
+        }
+        Some("logout") => {
+            println!("{}", crate::oauth::oauth_sign_out(&app_name)?);
+            Ok(())
+        }
+        Some("status") => {
+            let status = crate::oauth::oauth_status(&app_name)?;
+            if parsed.json {
+                print_json(&status);
+            } else if status["signedIn"] == json!(true) {
+                println!("{}: signed in", app_name);
+            } else {
+                println!("{}: not signed in", app_name);
+            }
+            Ok(())
+        }
+        _ => Err("Expected 'auth login', 'auth logout' or 'auth status'".to_string()),
+    }
+}
+
+fn cmd_apply(parsed: &ParsedArgs) -> Result<(), String> {
+    let path = parsed.positional.get(1).ok_or("Missing manifest path")?;
+    let manifest = crate::manifest::load_manifest(std::path::Path::new(path))?;
+    let plan = crate::manifest::reconcile(&manifest, parsed.dry_run)?;
+
+    if parsed.json {
+        print_json(&serde_json::to_value(&plan).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+
+    for change in &plan.changes {
+        let symbol = match change.action {
+            crate::manifest::ChangeAction::Add => "+",
+            crate::manifest::ChangeAction::Update => "~",
+            crate::manifest::ChangeAction::Remove => "-",
+            crate::manifest::ChangeAction::Unchanged => "=",
+        };
+        println!(
+            "{} {} ({}) in {}",
+            symbol, change.app, change.mcp_key, change.client
+        );
+        for line in &change.diff {
+            println!("    {}", line);
+        }
+    }
+    if !plan.applied {
+        println!("Dry run: no client configs were changed");
+    }
+    Ok(())
+}
+
+fn cmd_doctor(parsed: &ParsedArgs) -> Result<(), String> {
+    let report = crate::doctor::run_checks();
+
+    if parsed.json {
+        print_json(&json!(report));
+    } else {
+        for check in &report.checks {
+            let mark = match check.severity {
+                Severity::Ok => "ok ",
+                Severity::Info => "-- ",
+                Severity::Warning => "WRN",
+                Severity::Error => "ERR",
+            };
+            println!("[{}] {}: {}", mark, check.title, check.message);
+            if let Some(fix) = &check.fix {
+                println!("      fix: {}", fix);
+            }
+        }
+    }
+
+    if report.healthy {
+        Ok(())
+    } else {
+        Err("Some checks failed".to_string())
+    }
+}
+
+fn dispatch(args: &[String]) -> Result<(), String> {
+    let parsed = parse_args(args)?;
+    let command = parsed.positional.first().map(String::as_str);
+
+    match command {
+        Some("list") => cmd_list(&parsed),
+        Some("install") => {
+            let app_name = app_argument(&parsed, 1)?;
+            let env = if parsed.env.is_empty() {
+                None
+            } else {
+                Some(parse_env_pairs(&parsed.env)?)
+            };
+            println!(
+                "{}",
+                app::install_app(
+                    &app_name,
+                    env,
+                    &resolve_client(&parsed)?,
+                    parsed.project.clone()
+                )?
+            );
+            Ok(())
+        }
+        Some("uninstall") => {
+            let app_name = app_argument(&parsed, 1)?;
+            println!(
+                "{}",
+                app::uninstall(&app_name, &resolve_client(&parsed)?, parsed.project.clone())?
+            );
+            Ok(())
+        }
+        Some("status") => cmd_status(&parsed),
+        Some("projects") => cmd_projects(&parsed),
+        Some("env") => cmd_env(&parsed),
+        Some("registry") => match parsed.positional.get(1).map(String::as_str) {
+            Some("refresh") => {
+                let registry = app::refresh_app_registry()?;
+                println!(
+                    "Registry refreshed: {} apps",
+                    registry.as_array().map_or(0, |apps| apps.len())
+                );
+                Ok(())
+            }
+            _ => Err("Expected 'registry refresh'".to_string()),
+        },
+        Some("auth") => cmd_auth(&parsed),
+        Some("apply") => cmd_apply(&parsed),
+        Some("doctor") => cmd_doctor(&parsed),
+        Some("--version") | Some("-V") => {
+            println!("staten {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        }
+        _ => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    // Release builds use the GUI subsystem, so borrow the parent's console for output
+    unsafe {
+        winapi::um::wincon::AttachConsole(winapi::um::wincon::ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Runs a headless command when the first argument names one. Returns `None`
+/// when Staten should start the GUI instead, otherwise the process exit code.
+pub fn run_cli(args: &[String]) -> Option<i32> {
+    let command = args.first()?;
+    if !COMMANDS.contains(&command.as_str()) {
+        return None;
+    }
+
+    // The gateway and the proxy talk JSON-RPC over the pipes they were started with
+    if command == "gateway" {
+        return Some(crate::gateway::run_gateway());
+    }
+    if command == "proxy" {
+        return Some(crate::remote::run_proxy(&args[1..]));
+    }
+
+    #[cfg(target_os = "windows")]
+    attach_console();
+
+    if !crate::environment::is_test_mode() {
+        if let Err(e) = crate::setup_logger("staten-cli.log") {
+            eprintln!("Failed to initialize logger: {}", e);
+        }
+    }
+    clients::init_client_path_configs();
+
+    match dispatch(args) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            Some(1)
+        }
+    }
+}
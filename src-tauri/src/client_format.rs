@@ -0,0 +1,568 @@
+use crate::clients::ClientType;
+use crate::remote::{self, RemoteServer, RemoteTransport};
+use crate::secrets::is_secret_env_name;
+use serde_json::{json, Map, Value};
+
+// Staten works on one shape for every client, `{"mcpServers": {key: {command,
+// args, env}}}` or `{type, url, headers}` for remote servers, and converts to
+// and from each client's own shape only when a config file is read or written.
+
+const INPUT_PREFIX: &str = "${input:";
+
+fn servers_mut(config: &mut Value) -> Option<&mut Map<String, Value>> {
+    config.get_mut("mcpServers").and_then(|v| v.as_object_mut())
+}
+
+// Local servers are written with `"type": "stdio"`, which is the default
+fn strip_stdio_type(config: &mut Value) {
+    if let Some(servers) = servers_mut(config) {
+        for entry in servers.values_mut().filter_map(|e| e.as_object_mut()) {
+            if entry.get("type") == Some(&json!("stdio")) {
+                entry.remove("type");
+            }
+        }
+    }
+}
+
+fn add_stdio_type(config: &mut Value) {
+    if let Some(servers) = servers_mut(config) {
+        for entry in servers.values_mut().filter_map(|e| e.as_object_mut()) {
+            if entry.contains_key("command") && !entry.contains_key("type") {
+                entry.insert("type".to_string(), json!("stdio"));
+            }
+        }
+    }
+}
+
+// Zed nests the process under `command: {path, args, env}`. Entries without
+// a command come from Zed extensions and are left alone.
+fn flatten_zed_entries(config: &mut Value) {
+    if let Some(servers) = servers_mut(config) {
+        for entry in servers.values_mut().filter_map(|e| e.as_object_mut()) {
+            // Zed also takes the flat `command: "path"` form, which is ours
+            if !entry.get("command").is_some_and(Value::is_object) {
+                continue;
+            }
+            let Some(Value::Object(mut command)) = entry.remove("command") else {
+                continue;
+            };
+            if let Some(path) = command.remove("path") {
+                entry.insert("command".to_string(), path);
+            }
+            for key in ["args", "env"] {
+                if let Some(value) = command.remove(key) {
+                    entry.insert(key.to_string(), value);
+                }
+            }
+        }
+    }
+}
+
+fn nest_zed_entries(config: &mut Value) {
+    if let Some(servers) = servers_mut(config) {
+        for entry in servers.values_mut().filter_map(|e| e.as_object_mut()) {
+            let Some(path) = entry.remove("command") else {
+                continue;
+            };
+            let mut command = Map::new();
+            command.insert("path".to_string(), path);
+            for key in ["args", "env"] {
+                if let Some(value) = entry.remove(key) {
+                    command.insert(key.to_string(), value);
+                }
+            }
+            entry.insert("command".to_string(), Value::Object(command));
+        }
+    }
+}
+
+// Goose calls the process `cmd` and its environment `envs`, and needs a few
+// fields that Staten's entries do not have.
+fn from_goose_entries(config: &mut Value) {
+    if let Some(servers) = servers_mut(config) {
+        for entry in servers.values_mut().filter_map(|e| e.as_object_mut()) {
+            if let Some(cmd) = entry.remove("cmd") {
+                entry.insert("command".to_string(), cmd);
+            }
+            if let Some(envs) = entry.remove("envs") {
+                entry.insert("env".to_string(), envs);
+            }
+        }
+    }
+    strip_stdio_type(config);
+}
+
+fn to_goose_entries(config: &mut Value) {
+    if let Some(servers) = servers_mut(config) {
+        for (key, entry) in servers.iter_mut() {
+            let Some(entry) = entry.as_object_mut() else {
+                continue;
+            };
+            match entry.remove("command") {
+                Some(command) => {
+                    entry.insert("cmd".to_string(), command);
+                    let envs = entry.remove("env").unwrap_or_else(|| json!({}));
+                    entry.insert("envs".to_string(), envs);
+                    entry.insert("type".to_string(), json!("stdio"));
+                }
+                // Built-in extensions have neither a command nor a uri
+                None if !entry.contains_key("uri") => continue,
+                None => {}
+            }
+            entry.entry("name").or_insert_with(|| json!(key));
+            entry.entry("enabled").or_insert(json!(true));
+            entry.entry("timeout").or_insert(json!(GOOSE_TIMEOUT_SECS));
+        }
+    }
+}
+
+const GOOSE_TIMEOUT_SECS: u64 = 300;
+
+// The list as Continue had it, kept next to the servers so that writing it
+// back keeps its order and the entries Staten can't key, like hub blocks
+// that are only `uses: owner/block`
+const CONTINUE_LIST_KEY: &str = "_statenContinueList";
+
+fn list_name(entry: &Value) -> Option<&str> {
+    entry.get("name").and_then(|name| name.as_str())
+}
+
+fn named_entry(name: &str, entry: Value) -> Value {
+    let mut named = Map::new();
+    named.insert("name".to_string(), json!(name));
+    if let Value::Object(entry) = entry {
+        named.extend(entry);
+    }
+    Value::Object(named)
+}
+
+// Continue lists its servers and tells them apart by `name`
+fn servers_from_list(config: &mut Value) {
+    let Some(list) = config
+        .get_mut("mcpServers")
+        .and_then(|v| v.as_array_mut())
+        .map(std::mem::take)
+    else {
+        return;
+    };
+
+    let mut servers = Map::new();
+    for entry in &list {
+        let (Some(name), Value::Object(entry)) = (list_name(entry), entry) else {
+            continue;
+        };
+        let mut entry = entry.clone();
+        entry.remove("name");
+        servers.insert(name.to_string(), Value::Object(entry));
+    }
+    config["mcpServers"] = Value::Object(servers);
+    config[CONTINUE_LIST_KEY] = Value::Array(list);
+}
+
+fn servers_to_list(config: &mut Value) {
+    let previous = config
+        .as_object_mut()
+        .and_then(|object| object.remove(CONTINUE_LIST_KEY));
+    let Some(mut servers) = servers_mut(config).map(std::mem::take) else {
+        return;
+    };
+
+    // Listed servers stay in place, removed ones drop out and new ones go last
+    let mut list = Vec::new();
+    for entry in previous
+        .and_then(|p| p.as_array().cloned())
+        .unwrap_or_default()
+    {
+        match list_name(&entry).map(String::from) {
+            Some(name) => {
+                if let Some(server) = servers.remove(&name) {
+                    list.push(named_entry(&name, server));
+                }
+            }
+            None => list.push(entry),
+        }
+    }
+    list.extend(
+        servers
+            .into_iter()
+            .map(|(name, entry)| named_entry(&name, entry)),
+    );
+    config["mcpServers"] = Value::Array(list);
+}
+
+// How a client writes remote servers
+struct RemoteStyle {
+    url_key: &'static str,
+    /// Key of the transport, if the client has one
+    type_key: Option<&'static str>,
+    http_type: &'static str,
+    sse_type: &'static str,
+}
+
+// Clients without a style can only start local processes and get a
+// `staten proxy` entry instead, as do servers that need OAuth.
+fn remote_style(client: &ClientType) -> Option<RemoteStyle> {
+    let (url_key, type_key, http_type) = match client {
+        ClientType::ClaudeCode | ClientType::VSCode => ("url", Some("type"), "http"),
+        ClientType::Cursor => ("url", None, "http"),
+        ClientType::Windsurf => ("serverUrl", None, "http"),
+        ClientType::Goose => ("uri", Some("type"), "streamable_http"),
+        _ => return None,
+    };
+    Some(RemoteStyle {
+        url_key,
+        type_key,
+        http_type,
+        sse_type: "sse",
+    })
+}
+
+fn to_remote_entries(client: &ClientType, config: &mut Value) {
+    let style = remote_style(client);
+    let Some(servers) = servers_mut(config) else {
+        return;
+    };
+
+    for entry in servers.values_mut() {
+        let Some(server) = RemoteServer::from_entry(entry) else {
+            continue;
+        };
+        let Some(entry) = entry.as_object_mut() else {
+            continue;
+        };
+        for key in ["type", "url", "headers"] {
+            entry.remove(key);
+        }
+
+        let Some(style) = style.as_ref().filter(|_| !server.oauth) else {
+            entry.insert("command".to_string(), json!(remote::proxy_command()));
+            entry.insert("args".to_string(), json!(remote::proxy_args(&server)));
+            let env = remote::proxy_env(&server);
+            if !env.is_empty() {
+                entry.insert("env".to_string(), Value::Object(env));
+            }
+            continue;
+        };
+        entry.insert(style.url_key.to_string(), json!(server.url));
+        if let Some(type_key) = style.type_key {
+            let transport = match server.transport {
+                RemoteTransport::Http => style.http_type,
+                RemoteTransport::Sse => style.sse_type,
+            };
+            entry.insert(type_key.to_string(), json!(transport));
+        }
+        if !server.headers.is_empty() {
+            entry.insert("headers".to_string(), json!(server.headers));
+        }
+    }
+}
+
+fn from_remote_entries(client: &ClientType, config: &mut Value) {
+    let style = remote_style(client);
+    let Some(servers) = servers_mut(config) else {
+        return;
+    };
+
+    for entry in servers.values_mut().filter_map(|e| e.as_object_mut()) {
+        if let Some(command) = entry.get("command").and_then(|c| c.as_str()) {
+            let args: Vec<String> = entry
+                .get("args")
+                .and_then(|a| a.as_array())
+                .map(|args| {
+                    args.iter()
+                        .filter_map(|a| a.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let env = entry.get("env").and_then(|e| e.as_object());
+            let Some(server) = remote::parse_proxy_entry(command, &args, env) else {
+                continue;
+            };
+            entry.remove("command");
+            entry.remove("args");
+            entry.remove("env");
+            if let Value::Object(remote_entry) = server.to_entry() {
+                entry.extend(remote_entry);
+            }
+            continue;
+        }
+
+        let Some(style) = &style else {
+            continue;
+        };
+        let Some(url) = entry.remove(style.url_key) else {
+            continue;
+        };
+        let transport = match style.type_key.and_then(|key| entry.remove(key)) {
+            Some(Value::String(t)) if t == style.sse_type => RemoteTransport::Sse,
+            Some(_) => RemoteTransport::Http,
+            // Without a type, servers at `.../sse` are the only hint
+            None if url
+                .as_str()
+                .is_some_and(|url| url.trim_end_matches('/').ends_with("/sse")) =>
+            {
+                RemoteTransport::Sse
+            }
+            None => RemoteTransport::Http,
+        };
+        entry.insert("type".to_string(), json!(transport.as_str()));
+        entry.insert("url".to_string(), url);
+    }
+}
+
+fn rename_root_key(config: &mut Value, from: &str, to: &str) {
+    if let Some(object) = config.as_object_mut() {
+        if let Some(servers) = object.remove(from) {
+            object.insert(to.to_string(), servers);
+        }
+    }
+}
+
+/// The empty config Staten creates for a client that has no config file yet.
+pub fn initial_config(client: &ClientType) -> Value {
+    let mut config = to_client_format(client, &json!({ "mcpServers": {} }));
+    if *client == ClientType::Continue {
+        // Continue rejects a config.yaml without these
+        config["name"] = json!("Local Assistant");
+        config["version"] = json!("1.0.0");
+        config["schema"] = json!("v1");
+    }
+    config
+}
+
+/// Converts a config as stored by `client` into Staten's shape.
+pub fn from_client_format(client: &ClientType, mut config: Value) -> Value {
+    match client {
+        ClientType::ClaudeCode => strip_stdio_type(&mut config),
+        ClientType::VSCode => {
+            // mcp.json keeps servers under `servers`, settings.json under `mcp.servers`
+            let servers = match config.get_mut("servers") {
+                Some(servers) => Some(servers.take()),
+                None => config
+                    .get_mut("mcp")
+                    .and_then(|mcp| mcp.as_object_mut())
+                    .and_then(|mcp| mcp.remove("servers")),
+            };
+            if let Some(object) = config.as_object_mut() {
+                object.remove("servers");
+                if let Some(servers) = servers {
+                    object.insert("mcpServers".to_string(), servers);
+                }
+            }
+            strip_stdio_type(&mut config);
+        }
+        ClientType::Zed => {
+            rename_root_key(&mut config, "context_servers", "mcpServers");
+            flatten_zed_entries(&mut config);
+        }
+        ClientType::Codex => rename_root_key(&mut config, "mcp_servers", "mcpServers"),
+        ClientType::Goose => {
+            rename_root_key(&mut config, "extensions", "mcpServers");
+            from_goose_entries(&mut config);
+        }
+        ClientType::Continue => servers_from_list(&mut config),
+        _ => {}
+    }
+    from_remote_entries(client, &mut config);
+
+    config
+}
+
+/// Converts a config in Staten's shape into the one `client` reads.
+pub fn to_client_format(client: &ClientType, config: &Value) -> Value {
+    let mut config = config.clone();
+    to_remote_entries(client, &mut config);
+
+    match client {
+        ClientType::ClaudeCode => add_stdio_type(&mut config),
+        ClientType::VSCode => {
+            add_stdio_type(&mut config);
+            if let Some(object) = config.as_object_mut() {
+                let servers = object.remove("mcpServers").unwrap_or_else(|| json!({}));
+                match object.get_mut("mcp").and_then(|mcp| mcp.as_object_mut()) {
+                    Some(mcp) => {
+                        mcp.insert("servers".to_string(), servers);
+                    }
+                    None => {
+                        object.insert("servers".to_string(), servers);
+                    }
+                }
+            }
+        }
+        ClientType::Zed => {
+            nest_zed_entries(&mut config);
+            rename_root_key(&mut config, "mcpServers", "context_servers");
+        }
+        ClientType::Codex => rename_root_key(&mut config, "mcpServers", "mcp_servers"),
+        ClientType::Goose => {
+            to_goose_entries(&mut config);
+            rename_root_key(&mut config, "mcpServers", "extensions");
+        }
+        ClientType::Continue => servers_to_list(&mut config),
+        _ => {}
+    }
+
+    config
+}
+
+/// Whether the client prompts for secrets itself instead of storing them in
+/// its config.
+pub fn uses_inputs(client: &ClientType) -> bool {
+    *client == ClientType::VSCode
+}
+
+/// The id of the input that holds env variable `name` of server `mcp_key`.
+pub fn input_id(mcp_key: &str, name: &str) -> String {
+    format!("{}-{}", mcp_key, name)
+}
+
+fn input_reference(id: &str) -> String {
+    format!("{}{}}}", INPUT_PREFIX, id)
+}
+
+// Inputs sit next to the servers: under `mcp` in settings.json, at the top
+// of mcp.json. `to_client_format` picks the same place for the servers.
+fn inputs_parent_mut(config: &mut Value) -> Option<&mut Map<String, Value>> {
+    let object = config.as_object_mut()?;
+    if object.get("mcp").is_some_and(|mcp| mcp.is_object()) {
+        object.get_mut("mcp").and_then(|mcp| mcp.as_object_mut())
+    } else {
+        Some(object)
+    }
+}
+
+/// `env` with its secret values replaced by the `${input:...}` references
+/// `prompt_for_secrets` declares. Used before the entry is built, so the
+/// secrets filled into args, url and headers are references as well.
+pub fn input_env(env: &Value, mcp_key: &str) -> Value {
+    let mut env = env.clone();
+    if let Some(env) = env.as_object_mut() {
+        for (name, value) in env.iter_mut() {
+            if is_secret_env_name(name) {
+                *value = json!(input_reference(&input_id(mcp_key, name)));
+            }
+        }
+    }
+    env
+}
+
+/// `env` with its secret values replaced by references to environment
+/// variables of the same name, for project configs that are checked in and
+/// shared. Also returns the names that were replaced.
+pub fn project_env(client: &ClientType, env: &Value) -> (Value, Vec<String>) {
+    let mut env = env.clone();
+    let mut names = Vec::new();
+    if let Some(env) = env.as_object_mut() {
+        for (name, value) in env.iter_mut() {
+            if is_secret_env_name(name) {
+                *value = json!(match client {
+                    ClientType::Cursor => format!("${{env:{}}}", name),
+                    _ => format!("${{{}}}", name),
+                });
+                names.push(name.clone());
+            }
+        }
+    }
+    (env, names)
+}
+
+// The names behind the `${input:<mcp_key>-<NAME>}` references in `value`
+fn collect_input_names(value: &Value, mcp_key: &str, names: &mut Vec<String>) {
+    match value {
+        Value::String(text) => {
+            let own = format!("{}{}", INPUT_PREFIX, input_id(mcp_key, ""));
+            for (start, _) in text.match_indices(&own) {
+                let rest = &text[start + own.len()..];
+                if let Some(name) = rest.split_once('}').map(|(name, _)| name) {
+                    if !name.is_empty() && !names.iter().any(|n| n == name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_input_names(item, mcp_key, names)),
+        Value::Object(map) => map
+            .values()
+            .for_each(|item| collect_input_names(item, mcp_key, names)),
+        _ => {}
+    }
+}
+
+/// Replaces the secret env values of server `mcp_key` with `${input:...}`
+/// references and declares a password prompt for each reference in its
+/// entry, so the secret is asked for by the client and never written to its
+/// config. Secrets in args, url and headers are only covered when the entry
+/// was built from `input_env`.
+pub fn prompt_for_secrets(config: &mut Value, mcp_key: &str) {
+    let mut names = Vec::new();
+
+    if let Some(entry) = servers_mut(config).and_then(|servers| servers.get_mut(mcp_key)) {
+        if let Some(env) = entry.get_mut("env").and_then(|env| env.as_object_mut()) {
+            for (name, value) in env.iter_mut() {
+                if is_secret_env_name(name) {
+                    *value = json!(input_reference(&input_id(mcp_key, name)));
+                }
+            }
+        }
+        collect_input_names(entry, mcp_key, &mut names);
+    }
+    let prompts: Vec<(String, String)> = names
+        .into_iter()
+        .map(|name| (input_id(mcp_key, &name), name))
+        .collect();
+
+    if prompts.is_empty() {
+        return;
+    }
+
+    let Some(object) = inputs_parent_mut(config) else {
+        return;
+    };
+    let inputs = object.entry("inputs").or_insert_with(|| json!([]));
+    if !inputs.is_array() {
+        *inputs = json!([]);
+    }
+    let inputs = inputs.as_array_mut().unwrap();
+
+    for (id, name) in prompts {
+        if inputs.iter().any(|input| input["id"] == json!(id)) {
+            continue;
+        }
+        inputs.push(json!({
+            "type": "promptString",
+            "id": id,
+            "description": format!("{} for {}", name, mcp_key),
+            "password": true
+        }));
+    }
+}
+
+/// Drops the inputs `prompt_for_secrets` declared for server `mcp_key`. Call
+/// before the server entry itself is removed.
+pub fn remove_secret_inputs(config: &mut Value, mcp_key: &str) {
+    let entry = config
+        .get("mcpServers")
+        .and_then(|servers| servers.get(mcp_key));
+    let mut names: Vec<String> = entry
+        .and_then(|entry| entry.get("env"))
+        .and_then(|env| env.as_object())
+        .map(|env| env.keys().cloned().collect())
+        .unwrap_or_default();
+    if let Some(entry) = entry {
+        collect_input_names(entry, mcp_key, &mut names);
+    }
+    let ids: Vec<String> = names.iter().map(|name| input_id(mcp_key, name)).collect();
+
+    if let Some(inputs) = inputs_parent_mut(config)
+        .and_then(|object| object.get_mut("inputs"))
+        .and_then(|v| v.as_array_mut())
+    {
+        inputs.retain(|input| {
+            input["id"]
+                .as_str()
+                .is_none_or(|id| !ids.iter().any(|own| own == id))
+        });
+    }
+}
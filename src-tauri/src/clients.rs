@@ -1,23 +1,23 @@
 use crate::os::OSType;
 use dirs;
 use lazy_static::lazy_static;
-use log::{debug, info};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-#[cfg(target_os = "windows")]
-use crate::environment::CREATE_NO_WINDOW;
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-#[cfg(target_os = "windows")]
-use std::process::Command;
-
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ClientType {
     Claude,
     Cursor,
     Windsurf,
+    ClaudeCode,
+    VSCode,
+    Zed,
+    Codex,
+    Goose,
+    Continue,
 }
 
 impl ClientType {
@@ -26,6 +26,12 @@ impl ClientType {
             ClientType::Claude => "Claude",
             ClientType::Cursor => "Cursor",
             ClientType::Windsurf => "Windsurf",
+            ClientType::ClaudeCode => "ClaudeCode",
+            ClientType::VSCode => "VSCode",
+            ClientType::Zed => "Zed",
+            ClientType::Codex => "Codex",
+            ClientType::Goose => "Goose",
+            ClientType::Continue => "Continue",
         }
     }
 
@@ -34,24 +40,98 @@ impl ClientType {
             "Claude" => Some(ClientType::Claude),
             "Cursor" => Some(ClientType::Cursor),
             "Windsurf" => Some(ClientType::Windsurf),
+            "ClaudeCode" => Some(ClientType::ClaudeCode),
+            "VSCode" => Some(ClientType::VSCode),
+            "Zed" => Some(ClientType::Zed),
+            "Codex" => Some(ClientType::Codex),
+            "Goose" => Some(ClientType::Goose),
+            "Continue" => Some(ClientType::Continue),
             _ => None,
         }
     }
 
     pub fn all() -> Vec<ClientType> {
-        vec![ClientType::Claude, ClientType::Cursor, ClientType::Windsurf]
+        vec![
+            ClientType::Claude,
+            ClientType::Cursor,
+            ClientType::Windsurf,
+            ClientType::ClaudeCode,
+            ClientType::VSCode,
+            ClientType::Zed,
+            ClientType::Codex,
+            ClientType::Goose,
+            ClientType::Continue,
+        ]
     }
 
     pub fn all_as_str() -> Vec<&'static str> {
-        vec!["Claude", "Cursor", "Windsurf"]
+        vec![
+            "Claude",
+            "Cursor",
+            "Windsurf",
+            "ClaudeCode",
+            "VSCode",
+            "Zed",
+            "Codex",
+            "Goose",
+            "Continue",
+        ]
     }
 
     pub fn default() -> ClientType {
         ClientType::Claude
     }
+
+    /// Only the desktop apps read their config once at startup. CLI agents
+    /// read it on every start and the editors watch their config files.
+    pub fn needs_restart(&self) -> bool {
+        matches!(
+            self,
+            ClientType::Claude | ClientType::Cursor | ClientType::Windsurf
+        )
+    }
+}
+
+/// Which config file of a client to work on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConfigScope {
+    /// The client's own config in the user's home
+    User,
+    /// A config checked into a project directory
+    Project(PathBuf),
+}
+
+impl ConfigScope {
+    /// `None`, `""` and `"user"` mean the user scope, anything else is a
+    /// project directory.
+    pub fn parse(scope: Option<&str>) -> Result<ConfigScope, String> {
+        match scope {
+            None | Some("") | Some("user") => Ok(ConfigScope::User),
+            Some(dir) => {
+                let dir = PathBuf::from(dir);
+                if !dir.is_absolute() || !dir.is_dir() {
+                    return Err(format!("Project directory not found: {}", dir.display()));
+                }
+                Ok(ConfigScope::Project(dir))
+            }
+        }
+    }
+}
+
+/// The project-level config file of a client inside `project_dir`.
+pub fn project_config_path(client: &ClientType, project_dir: &Path) -> Result<PathBuf, String> {
+    match client {
+        ClientType::Cursor => Ok(project_dir.join(".cursor").join("mcp.json")),
+        ClientType::ClaudeCode => Ok(project_dir.join(".mcp.json")),
+        ClientType::VSCode => Ok(project_dir.join(".vscode").join("mcp.json")),
+        _ => Err(format!(
+            "{} does not support project-level MCP configs",
+            client.as_str()
+        )),
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ClientPathConfig {
     pub base_dir: PathBuf,
     pub config_filename: String,
@@ -59,82 +139,252 @@ pub struct ClientPathConfig {
 }
 
 lazy_static! {
-    static ref CLIENT_PATH_CONFIGS: Mutex<std::collections::HashMap<ClientType, ClientPathConfig>> =
-        Mutex::new(std::collections::HashMap::new());
+    static ref CLIENT_PATH_CONFIGS: Mutex<HashMap<ClientType, ClientPathConfig>> =
+        Mutex::new(HashMap::new());
 }
 
-pub fn init_client_path_configs() {
-    let mut configs = CLIENT_PATH_CONFIGS.lock().unwrap();
+// Codex reads its config from $CODEX_HOME when that is set
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn codex_home(home_dir: &Path) -> PathBuf {
+    std::env::var_os("CODEX_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home_dir.join(".codex"))
+}
 
-    if configs.is_empty() {
-        if let Some(home_dir) = dirs::home_dir() {
-            #[cfg(target_os = "macos")]
-            {
-                configs.insert(
-                    ClientType::Claude,
-                    ClientPathConfig {
-                        base_dir: home_dir.join("Library/Application Support/Claude"),
-                        config_filename: "claude_desktop_config.json".to_string(),
-                        os: OSType::MacOS,
-                    },
-                );
+fn default_client_path_configs() -> HashMap<ClientType, ClientPathConfig> {
+    let mut configs = HashMap::new();
+
+    if let Some(home_dir) = dirs::home_dir() {
+        #[cfg(target_os = "macos")]
+        {
+            configs.insert(
+                ClientType::Claude,
+                ClientPathConfig {
+                    base_dir: home_dir.join("Library/Application Support/Claude"),
+                    config_filename: "claude_desktop_config.json".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+
+            configs.insert(
+                ClientType::Cursor,
+                ClientPathConfig {
+                    base_dir: home_dir.join(".cursor/"),
+                    config_filename: "mcp.json".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+
+            configs.insert(
+                ClientType::Windsurf,
+                ClientPathConfig {
+                    base_dir: home_dir.join(".codeium/windsurf"),
+                    config_filename: "mcp_config.json".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+
+            configs.insert(
+                ClientType::ClaudeCode,
+                ClientPathConfig {
+                    base_dir: home_dir.clone(),
+                    config_filename: ".claude.json".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+
+            configs.insert(
+                ClientType::VSCode,
+                ClientPathConfig {
+                    base_dir: home_dir.join("Library/Application Support/Code/User"),
+                    config_filename: "mcp.json".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+
+            configs.insert(
+                ClientType::Zed,
+                ClientPathConfig {
+                    base_dir: home_dir.join(".config/zed"),
+                    config_filename: "settings.json".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+
+            configs.insert(
+                ClientType::Codex,
+                ClientPathConfig {
+                    base_dir: codex_home(&home_dir),
+                    config_filename: "config.toml".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+
+            configs.insert(
+                ClientType::Goose,
+                ClientPathConfig {
+                    base_dir: home_dir.join(".config/goose"),
+                    config_filename: "config.yaml".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+
+            configs.insert(
+                ClientType::Continue,
+                ClientPathConfig {
+                    base_dir: home_dir.join(".continue"),
+                    config_filename: "config.yaml".to_string(),
+                    os: OSType::MacOS,
+                },
+            );
+        }
 
-                configs.insert(
-                    ClientType::Cursor,
-                    ClientPathConfig {
-                        base_dir: home_dir.join(".cursor/"),
-                        config_filename: "mcp.json".to_string(),
-                        os: OSType::MacOS,
-                    },
-                );
+        #[cfg(target_os = "windows")]
+        {
+            let appdata_roaming =
+                dirs::config_dir().unwrap_or_else(|| home_dir.join("AppData/Roaming"));
+            configs.insert(
+                ClientType::Claude,
+                ClientPathConfig {
+                    base_dir: appdata_roaming.join("Claude"),
+                    config_filename: "claude_desktop_config.json".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+
+            // TODO: this might not work but I don't care about cursor in windows for now
+            configs.insert(
+                ClientType::Cursor,
+                ClientPathConfig {
+                    base_dir: home_dir.join(".cursor/"),
+                    config_filename: "mcp.json".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+
+            // TODO: this might not work but I don't care about windsurf in windows for now
+            configs.insert(
+                ClientType::Windsurf,
+                ClientPathConfig {
+                    base_dir: home_dir.join(".codeium/windsurf"),
+                    config_filename: "mcp_config.json".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+
+            configs.insert(
+                ClientType::ClaudeCode,
+                ClientPathConfig {
+                    base_dir: home_dir.clone(),
+                    config_filename: ".claude.json".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+
+            configs.insert(
+                ClientType::VSCode,
+                ClientPathConfig {
+                    base_dir: appdata_roaming.join("Code/User"),
+                    config_filename: "mcp.json".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+
+            configs.insert(
+                ClientType::Zed,
+                ClientPathConfig {
+                    base_dir: appdata_roaming.join("Zed"),
+                    config_filename: "settings.json".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+
+            configs.insert(
+                ClientType::Codex,
+                ClientPathConfig {
+                    base_dir: codex_home(&home_dir),
+                    config_filename: "config.toml".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+
+            configs.insert(
+                ClientType::Goose,
+                ClientPathConfig {
+                    base_dir: appdata_roaming.join("Block/goose/config"),
+                    config_filename: "config.yaml".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+
+            configs.insert(
+                ClientType::Continue,
+                ClientPathConfig {
+                    base_dir: home_dir.join(".continue"),
+                    config_filename: "config.yaml".to_string(),
+                    os: OSType::Windows,
+                },
+            );
+        }
+    }
 
-                configs.insert(
-                    ClientType::Windsurf,
-                    ClientPathConfig {
-                        base_dir: home_dir.join(".codeium/windsurf"),
-                        config_filename: "mcp_config.json".to_string(),
-                        os: OSType::MacOS,
-                    },
-                );
-            }
+    configs
+}
 
-            #[cfg(target_os = "windows")]
-            {
-                let appdata_roaming =
-                    dirs::config_dir().unwrap_or_else(|| home_dir.join("AppData/Roaming"));
-                configs.insert(
-                    ClientType::Claude,
-                    ClientPathConfig {
-                        base_dir: appdata_roaming.join("Claude"),
-                        config_filename: "claude_desktop_config.json".to_string(),
-                        os: OSType::Windows,
-                    },
-                );
+pub fn init_client_path_configs() {
+    let mut configs = CLIENT_PATH_CONFIGS.lock().unwrap();
 
-                // TODO: this might not work but I don't care about cursor in windows for now
-                configs.insert(
-                    ClientType::Cursor,
-                    ClientPathConfig {
-                        base_dir: home_dir.join(".cursor/"),
-                        config_filename: "mcp.json".to_string(),
-                        os: OSType::Windows,
-                    },
-                );
+    if configs.is_empty() {
+        *configs = default_client_path_configs();
+        apply_path_overrides(&mut configs);
+    }
+}
 
-                // TODO: this might not work but I don't care about windsurf in windows for now
+// Overlays the overrides saved by set_client_config_path on the defaults
+fn apply_path_overrides(configs: &mut HashMap<ClientType, ClientPathConfig>) {
+    let settings = match crate::settings::load_settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            warn!("Ignoring client path overrides: {}", e);
+            return;
+        }
+    };
+
+    for (client, path_override) in settings.client_path_overrides {
+        match ClientType::from_str(&client) {
+            Some(client_type) => {
+                debug!(
+                    "Using saved path for {}: {}",
+                    client, path_override.base_dir
+                );
                 configs.insert(
-                    ClientType::Windsurf,
+                    client_type,
                     ClientPathConfig {
-                        base_dir: home_dir.join(".codeium/windsurf"),
-                        config_filename: "mcp_config.json".to_string(),
-                        os: OSType::Windows,
+                        base_dir: PathBuf::from(path_override.base_dir),
+                        config_filename: path_override.config_filename,
+                        os: OSType::current(),
                     },
                 );
             }
+            None => warn!("Ignoring path override for unknown client {}", client),
         }
     }
 }
 
+/// Drops the in-memory path configs so the next lookup re-reads the defaults
+/// and saved overrides.
+pub fn reload_client_path_configs() {
+    CLIENT_PATH_CONFIGS.lock().unwrap().clear();
+    init_client_path_configs();
+}
+
+/// The path configuration a client has when nothing overrides it.
+pub fn get_default_client_path_config(client: &ClientType) -> Result<ClientPathConfig, String> {
+    default_client_path_configs()
+        .remove(client)
+        .ok_or_else(|| format!("No path configuration for client: {}", client.as_str()))
+}
+
 pub fn get_client_path_config(client: &ClientType) -> Result<ClientPathConfig, String> {
     init_client_path_configs();
 
@@ -168,105 +418,36 @@ pub fn validate_client(client: &ClientType) -> Result<(), String> {
     Ok(())
 }
 
+/// The client from settings, else the first one installed, else Claude.
 pub fn get_default_client() -> ClientType {
-    ClientType::default()
+    crate::settings::load_settings()
+        .ok()
+        .and_then(|settings| settings.default_client)
+        .and_then(|client| ClientType::from_str(&client))
+        .or_else(crate::detect::detect_default_client)
+        .unwrap_or_else(ClientType::default)
 }
 
 pub fn check_client_installed(client: &ClientType) -> Result<bool, String> {
     validate_client(client)?;
 
-    #[cfg(target_os = "macos")]
-    {
-        let app_path = std::path::PathBuf::from(format!("/Applications/{}.app", client.as_str()));
-        debug!(
-            "Checking for {}.app at: {}",
-            client.as_str(),
-            app_path.display()
-        );
-        return Ok(app_path.exists());
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        if *client == ClientType::Claude {
-            if let Some(local_app_data) = dirs::data_local_dir() {
-                let app_dir = local_app_data.join("AnthropicClaude");
-                let exe_path = app_dir.join("claude.exe");
-
-                debug!("Checking for claude.exe at: {}", exe_path.display());
-
-                let exists = exe_path.exists();
-                info!(
-                    "Claude {} at {}",
-                    if exists { "found" } else { "not found" },
-                    exe_path.display()
-                );
-                return Ok(exists);
-            }
-            info!("Claude not found - could not locate AppData directory");
-            return Ok(false);
+    let installation = crate::detect::find_installation(client);
+    info!(
+        "{} {}",
+        client.as_str(),
+        match &installation {
+            Some(installation) => format!("found at {}", installation.path.display()),
+            None => "not found".to_string(),
         }
-
-        debug!("Unknown client type for Windows: {}", client.as_str());
-        return Ok(false);
-    }
+    );
+    Ok(installation.is_some())
 }
 
 pub fn restart_client_app(client: &ClientType) -> Result<String, String> {
     validate_client(client)?;
-
-    info!("Restarting {} app...", client.as_str());
-
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("pkill")
-            .arg("-x")
-            .arg(client.as_str())
-            .output()
-            .map_err(|e| format!("Failed to kill {} app: {}", client.as_str(), e))?;
-
-        std::thread::sleep(std::time::Duration::from_millis(500));
-
-        std::process::Command::new("open")
-            .arg("-a")
-            .arg(client.as_str())
-            .output()
-            .map_err(|e| format!("Failed to relaunch {} app: {}", client.as_str(), e))?;
-
-        return Ok(format!("{} app restarted successfully", client.as_str()));
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        let _ = Command::new("taskkill")
-            .args(&["/F", "/IM", &format!("{}.exe", client.as_str())])
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-
-        std::thread::sleep(std::time::Duration::from_millis(1000));
-
-        if *client == ClientType::Claude {
-            if let Some(local_app_data) = dirs::data_local_dir() {
-                let app_dir = local_app_data.join("AnthropicClaude");
-                let exe_path = app_dir.join("claude.exe");
-
-                if exe_path.exists() {
-                    info!("Claude executable found at: {}", exe_path.display());
-                    Command::new(&exe_path)
-                        .creation_flags(CREATE_NO_WINDOW)
-                        .spawn()
-                        .map_err(|e| format!("Failed to restart Claude: {}", e))?;
-                    return Ok("Claude app restarted successfully".to_string());
-                }
-
-                info!("Claude executable not found at: {}", exe_path.display());
-                return Err("Could not find claude.exe to restart".to_string());
-            }
-        }
-
-        return Err(format!(
-            "Restart not implemented for client: {}",
-            client.as_str()
-        ));
+    if !client.needs_restart() {
+        info!("{} does not need a restart", client.as_str());
+        return Ok(format!("Restart not needed for {}", client.as_str()));
     }
+    crate::restart::restart_client(client)
 }
@@ -0,0 +1,235 @@
+use crate::clients::ClientType;
+use serde_json::{Map, Value};
+
+/// How a client's config file is stored on disk. Documents are always handled
+/// as JSON values, whatever the file format is.
+pub trait ConfigFormat {
+    /// The format's name for messages, such as "JSON".
+    fn name(&self) -> &'static str;
+
+    /// Parses file text into a value.
+    fn parse(&self, text: &str) -> Result<Value, String>;
+
+    /// Renders `config` as file text. `previous` is the current file; formats
+    /// that support it only rewrite the parts Staten manages and keep the rest,
+    /// comments included, as it was.
+    fn render(&self, config: &Value, previous: Option<&str>) -> Result<String, String>;
+}
+
+/// Plain JSON, rewritten as a whole.
+pub struct Json;
+
+/// JSON with comments, as used by editor settings files.
+pub struct Jsonc {
+    /// Top-level members Staten writes
+    pub managed_keys: &'static [&'static str],
+}
+
+/// YAML, with only the managed top-level keys rewritten.
+pub struct Yaml {
+    /// Top-level keys Staten writes
+    pub managed_keys: &'static [&'static str],
+}
+
+/// TOML, edited in place.
+pub struct Toml {
+    /// Top-level tables Staten writes
+    pub managed_keys: &'static [&'static str],
+}
+
+/// Whether Staten may keep `client`'s parsed config in memory between calls.
+/// Claude Code rewrites `~/.claude.json` on its own all the time, so that file
+/// is always read fresh.
+pub fn is_cacheable(client: &ClientType) -> bool {
+    !matches!(client, ClientType::ClaudeCode)
+}
+
+/// The format `client` stores its MCP config in.
+pub fn for_client(client: &ClientType) -> Box<dyn ConfigFormat> {
+    match client {
+        ClientType::ClaudeCode => Box::new(Jsonc {
+            managed_keys: &["mcpServers"],
+        }),
+        ClientType::VSCode => Box::new(Jsonc {
+            managed_keys: &["servers", "inputs", "mcp"],
+        }),
+        ClientType::Zed => Box::new(Jsonc {
+            managed_keys: &["context_servers"],
+        }),
+        ClientType::Codex => Box::new(Toml {
+            managed_keys: &["mcp_servers"],
+        }),
+        ClientType::Goose => Box::new(Yaml {
+            managed_keys: &["extensions"],
+        }),
+        ClientType::Continue => Box::new(Yaml {
+            managed_keys: &["mcpServers"],
+        }),
+        _ => Box::new(Json),
+    }
+}
+
+impl ConfigFormat for Json {
+    fn name(&self) -> &'static str {
+        "JSON"
+    }
+
+    fn parse(&self, text: &str) -> Result<Value, String> {
+        serde_json::from_str(text).map_err(|e| e.to_string())
+    }
+
+    fn render(&self, config: &Value, _previous: Option<&str>) -> Result<String, String> {
+        serde_json::to_string_pretty(config).map_err(|e| e.to_string())
+    }
+}
+
+impl ConfigFormat for Jsonc {
+    fn name(&self) -> &'static str {
+        "JSONC"
+    }
+
+    fn parse(&self, text: &str) -> Result<Value, String> {
+        crate::jsonc::parse(text)
+    }
+
+    fn render(&self, config: &Value, previous: Option<&str>) -> Result<String, String> {
+        let Some(previous) = previous else {
+            return Json.render(config, None);
+        };
+
+        let mut text = previous.to_string();
+        for key in self.managed_keys {
+            if let Some(value) = config.get(*key) {
+                text = crate::jsonc::set_member(&text, key, value)?;
+            }
+        }
+        Ok(text)
+    }
+}
+
+impl ConfigFormat for Yaml {
+    fn name(&self) -> &'static str {
+        "YAML"
+    }
+
+    fn parse(&self, text: &str) -> Result<Value, String> {
+        crate::yaml::parse(text)
+    }
+
+    fn render(&self, config: &Value, previous: Option<&str>) -> Result<String, String> {
+        let Some(previous) = previous else {
+            return crate::yaml::render(config);
+        };
+
+        let mut text = previous.to_string();
+        for key in self.managed_keys {
+            if let Some(value) = config.get(*key) {
+                text = crate::yaml::set_member(&text, key, value)?;
+            }
+        }
+        Ok(text)
+    }
+}
+
+impl ConfigFormat for Toml {
+    fn name(&self) -> &'static str {
+        "TOML"
+    }
+
+    fn parse(&self, text: &str) -> Result<Value, String> {
+        let table: toml::Table = toml::from_str(text).map_err(|e| e.to_string())?;
+        serde_json::to_value(table).map_err(|e| e.to_string())
+    }
+
+    fn render(&self, config: &Value, previous: Option<&str>) -> Result<String, String> {
+        let empty = Map::new();
+        let config = config.as_object().unwrap_or(&empty);
+
+        let mut document = match previous {
+            Some(previous) => previous
+                .parse::<toml_edit::DocumentMut>()
+                .map_err(|e| e.to_string())?,
+            None => toml_edit::DocumentMut::new(),
+        };
+
+        let keys: Vec<&str> = match previous {
+            Some(_) => self.managed_keys.to_vec(),
+            None => config.keys().map(String::as_str).collect(),
+        };
+        for key in keys {
+            if let Some(value) = config.get(key) {
+                update_item(document.as_table_mut(), key, value, 1);
+            }
+        }
+
+        Ok(document.to_string())
+    }
+}
+
+// New objects down to this depth are written as `[a.b]` tables, deeper ones
+// as inline tables, e.g. `[mcp_servers.name]` with `env = { ... }`.
+const TOML_TABLE_DEPTH: usize = 2;
+
+fn to_toml_value(value: &Value) -> Option<toml_edit::Value> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => toml_edit::Value::from(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => toml_edit::Value::from(i),
+            None => toml_edit::Value::from(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => toml_edit::Value::from(s.as_str()),
+        Value::Array(items) => {
+            toml_edit::Value::Array(items.iter().filter_map(to_toml_value).collect())
+        }
+        Value::Object(map) => {
+            let mut table = toml_edit::InlineTable::new();
+            for (key, value) in map {
+                if let Some(value) = to_toml_value(value) {
+                    table.insert(key, value);
+                }
+            }
+            toml_edit::Value::InlineTable(table)
+        }
+    })
+}
+
+// Sets `table[key]` to `value`, reusing existing tables so that comments and
+// key order of untouched entries survive.
+fn update_item(table: &mut toml_edit::Table, key: &str, value: &Value, depth: usize) {
+    match value {
+        Value::Null => {
+            table.remove(key);
+        }
+        Value::Object(map)
+            if depth <= TOML_TABLE_DEPTH || table.get(key).is_some_and(|item| item.is_table()) =>
+        {
+            if !table.get(key).is_some_and(|item| item.is_table()) {
+                let mut new_table = toml_edit::Table::new();
+                new_table.set_implicit(depth < TOML_TABLE_DEPTH);
+                table.insert(key, toml_edit::Item::Table(new_table));
+            }
+            let Some(child) = table.get_mut(key).and_then(|item| item.as_table_mut()) else {
+                return;
+            };
+            child.retain(|existing, _| map.contains_key(existing));
+            for (child_key, child_value) in map {
+                update_item(child, child_key, child_value, depth + 1);
+            }
+        }
+        _ => {
+            let Some(mut new_value) = to_toml_value(value) else {
+                return;
+            };
+            match table.get_mut(key).and_then(|item| item.as_value_mut()) {
+                Some(existing) => {
+                    *new_value.decor_mut() = existing.decor().clone();
+                    *existing = new_value;
+                }
+                None => {
+                    table.insert(key, toml_edit::Item::Value(new_value));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use serde_json::{Map, Value};
+
+// Apps that ship as container images. They run with `docker run -i --rm`, or
+// the same through Podman, get their env variables passed through by name and
+// see the host directories that setup fields point at as volumes.
+
+/// A host directory picked in a setup field and mounted into the container.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerMount {
+    /// Key of the setup field, which is also the env variable with the path
+    pub key: String,
+    /// Path inside the container
+    pub target: String,
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerImage {
+    pub image: String,
+    /// `sha256:...` digest that pins the image to one exact build
+    pub digest: Option<String>,
+    pub mounts: Vec<ContainerMount>,
+}
+
+// A colon after the last slash starts a tag, one before it is a registry port
+fn strip_tag(image: &str) -> &str {
+    let image = image.split('@').next().unwrap_or(image);
+    match image.rfind(':') {
+        Some(colon) if !image[colon..].contains('/') => &image[..colon],
+        _ => image,
+    }
+}
+
+impl ContainerImage {
+    /// Reads the `image` and `digest` of a registry app's config, with mounts
+    /// from the app's setup fields that have a `mount` target.
+    pub fn from_registry(app: &Value, config: &Map<String, Value>) -> Result<Self, String> {
+        let image = config
+            .get("image")
+            .and_then(|i| i.as_str())
+            .ok_or("image is missing")?
+            .to_string();
+        let digest = config
+            .get("digest")
+            .and_then(|d| d.as_str())
+            .map(String::from);
+        if digest.as_deref().is_some_and(|d| !d.starts_with("sha256:")) {
+            return Err(format!("Invalid digest for {}, expected sha256:...", image));
+        }
+
+        let mounts = app["setup"]
+            .as_array()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter_map(|field| {
+                        Some(ContainerMount {
+                            key: field["key"].as_str()?.to_string(),
+                            target: field["mount"].as_str()?.to_string(),
+                            read_only: field["readOnly"].as_bool().unwrap_or(false),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(ContainerImage {
+            image,
+            digest,
+            mounts,
+        })
+    }
+
+    /// The image reference to run, by digest when there is one.
+    pub fn reference(&self) -> String {
+        match &self.digest {
+            Some(digest) => format!("{}@{}", strip_tag(&self.image), digest),
+            None => self.image.clone(),
+        }
+    }
+
+    /// Pins the image to `version`, either a `sha256:` digest or a tag.
+    pub fn pin(&mut self, version: &str) {
+        if version.starts_with("sha256:") {
+            self.digest = Some(version.to_string());
+        } else {
+            self.image = format!("{}:{}", strip_tag(&self.image), version);
+            self.digest = None;
+        }
+    }
+
+    /// The arguments of `run`. `env` has the app's env values, setup fields
+    /// included, and `args` are passed on to the container.
+    pub fn run_args(&self, env: &Value, args: &[String]) -> Vec<String> {
+        let mut run: Vec<String> = ["run", "-i", "--rm"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+
+        // Values stay in the entry's env, the engine passes them on by name
+        if let Some(env) = env.as_object() {
+            for key in env.keys() {
+                if self.mounts.iter().any(|mount| &mount.key == key) {
+                    continue;
+                }
+                run.push("-e".to_string());
+                run.push(key.clone());
+            }
+        }
+
+        for mount in &self.mounts {
+            let Some(source) = env
+                .get(&mount.key)
+                .and_then(|v| v.as_str())
+                .filter(|source| !source.is_empty())
+            else {
+                continue;
+            };
+            let mut volume = format!("{}:{}", source, mount.target);
+            if mount.read_only {
+                volume.push_str(":ro");
+            }
+            run.push("-v".to_string());
+            run.push(volume);
+        }
+
+        run.push(self.reference());
+        run.extend(args.iter().cloned());
+        run
+    }
+}
@@ -0,0 +1,456 @@
+use crate::clients::{self, ClientType};
+use lazy_static::lazy_static;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use crate::environment::CREATE_NO_WINDOW;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+lazy_static! {
+    // Detection spawns processes on some platforms, so the default client
+    // is only worked out once per run
+    static ref DETECTED_DEFAULT_CLIENT: Mutex<Option<ClientType>> = Mutex::new(None);
+}
+
+/// Where each client is normally installed. Each platform reads only its own
+/// fields.
+#[allow(dead_code)]
+struct InstallHints {
+    /// macOS app bundle name
+    bundle: Option<&'static str>,
+    /// `DisplayName` prefix in the Windows uninstall registry keys
+    windows_display_name: Option<&'static str>,
+    /// Executable paths relative to `%LOCALAPPDATA%`
+    windows_executables: &'static [&'static str],
+    /// Desktop entry `Name=` on Linux
+    desktop_name: Option<&'static str>,
+    /// Launcher commands the client puts on PATH
+    cli_names: &'static [&'static str],
+}
+
+fn install_hints(client: &ClientType) -> InstallHints {
+    match client {
+        ClientType::Claude => InstallHints {
+            bundle: Some("Claude.app"),
+            windows_display_name: Some("Claude"),
+            windows_executables: &["AnthropicClaude/claude.exe"],
+            desktop_name: Some("Claude"),
+            cli_names: &[],
+        },
+        ClientType::Cursor => InstallHints {
+            bundle: Some("Cursor.app"),
+            windows_display_name: Some("Cursor"),
+            windows_executables: &["Programs/cursor/Cursor.exe"],
+            desktop_name: Some("Cursor"),
+            cli_names: &["cursor"],
+        },
+        ClientType::Windsurf => InstallHints {
+            bundle: Some("Windsurf.app"),
+            windows_display_name: Some("Windsurf"),
+            windows_executables: &["Programs/Windsurf/Windsurf.exe"],
+            desktop_name: Some("Windsurf"),
+            cli_names: &["windsurf"],
+        },
+        ClientType::ClaudeCode => InstallHints {
+            bundle: None,
+            windows_display_name: None,
+            windows_executables: &[],
+            desktop_name: None,
+            cli_names: &["claude"],
+        },
+        ClientType::VSCode => InstallHints {
+            bundle: Some("Visual Studio Code.app"),
+            windows_display_name: Some("Microsoft Visual Studio Code"),
+            windows_executables: &["Programs/Microsoft VS Code/Code.exe"],
+            desktop_name: Some("Visual Studio Code"),
+            cli_names: &["code"],
+        },
+        ClientType::Zed => InstallHints {
+            bundle: Some("Zed.app"),
+            windows_display_name: Some("Zed"),
+            windows_executables: &["Programs/Zed/Zed.exe"],
+            desktop_name: Some("Zed"),
+            cli_names: &["zed"],
+        },
+        ClientType::Codex => InstallHints {
+            bundle: None,
+            windows_display_name: None,
+            windows_executables: &[],
+            desktop_name: None,
+            cli_names: &["codex"],
+        },
+        ClientType::Goose => InstallHints {
+            bundle: Some("Goose.app"),
+            windows_display_name: Some("Goose"),
+            windows_executables: &["Programs/Goose/Goose.exe"],
+            desktop_name: Some("Goose"),
+            cli_names: &["goose"],
+        },
+        // A VS Code and JetBrains extension; only its CLI can be found
+        ClientType::Continue => InstallHints {
+            bundle: None,
+            windows_display_name: None,
+            windows_executables: &[],
+            desktop_name: None,
+            cli_names: &["cn"],
+        },
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DetectionSource {
+    AppBundle,
+    Registry,
+    Executable,
+    DesktopEntry,
+    Path,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedClient {
+    pub client: String,
+    pub installed: bool,
+    pub install_path: Option<String>,
+    pub version: Option<String>,
+    pub detected_by: Option<DetectionSource>,
+    pub config_path: Option<String>,
+    pub config_exists: bool,
+}
+
+/// An installation found on disk, before the version is looked up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Installation {
+    pub path: PathBuf,
+    pub version: Option<String>,
+    pub source: DetectionSource,
+}
+
+/// One key of `reg query ... /s` output.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RegistryEntry {
+    pub display_name: Option<String>,
+    pub display_version: Option<String>,
+    pub install_location: Option<String>,
+    pub display_icon: Option<String>,
+}
+
+/// Splits the output of `reg query <Uninstall key> /s` into its keys.
+pub fn parse_registry_entries(output: &str) -> Vec<RegistryEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<RegistryEntry> = None;
+
+    for line in output.lines() {
+        if line.starts_with("HKEY_") {
+            entries.extend(current.take());
+            current = Some(RegistryEntry::default());
+            continue;
+        }
+
+        // Values look like `    DisplayName    REG_SZ    Cursor (User)`
+        let mut parts = line.trim().splitn(3, "    ");
+        let (Some(name), Some(kind), value) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        if !kind.trim().starts_with("REG_") {
+            continue;
+        }
+        let value = value.unwrap_or_default().trim().to_string();
+
+        if let Some(entry) = current.as_mut() {
+            match name.trim() {
+                "DisplayName" => entry.display_name = Some(value),
+                "DisplayVersion" => entry.display_version = Some(value),
+                "InstallLocation" => entry.install_location = Some(value),
+                "DisplayIcon" => entry.display_icon = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    entries.extend(current);
+    entries
+}
+
+/// Picks the uninstall entry whose display name starts with `display_name`.
+pub fn find_registry_installation(
+    entries: &[RegistryEntry],
+    display_name: &str,
+) -> Option<Installation> {
+    let entry = entries.iter().find(|entry| {
+        entry
+            .display_name
+            .as_deref()
+            .is_some_and(|name| name.starts_with(display_name))
+    })?;
+
+    // DisplayIcon is usually `C:\...\App.exe,0`
+    let path = entry
+        .install_location
+        .clone()
+        .filter(|location| !location.is_empty())
+        .or_else(|| {
+            entry.display_icon.as_ref().map(|icon| {
+                icon.split(',')
+                    .next()
+                    .unwrap_or_default()
+                    .trim_matches('"')
+                    .to_string()
+            })
+        })?;
+
+    Some(Installation {
+        path: PathBuf::from(path),
+        version: entry.display_version.clone(),
+        source: DetectionSource::Registry,
+    })
+}
+
+/// Returns `(Name, Exec)` from the `[Desktop Entry]` group of a .desktop file.
+pub fn parse_desktop_entry(content: &str) -> Option<(String, String)> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut exec = None;
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("Name=") {
+            name = Some(value.to_string());
+        } else if let Some(value) = line.strip_prefix("Exec=") {
+            // Drop field codes such as %U
+            let command: Vec<&str> = value
+                .split_whitespace()
+                .filter(|part| !part.starts_with('%'))
+                .collect();
+            exec = Some(command.join(" "));
+        }
+    }
+
+    Some((name?, exec?))
+}
+
+/// Looks for `name` in each directory of a PATH-style list.
+pub fn find_in_path(name: &str, path_var: &std::ffi::OsStr) -> Option<PathBuf> {
+    let candidates: Vec<String> = if cfg!(target_os = "windows") {
+        vec![format!("{}.cmd", name), format!("{}.exe", name)]
+    } else {
+        vec![name.to_string()]
+    };
+
+    std::env::split_paths(path_var).find_map(|dir| {
+        candidates
+            .iter()
+            .map(|candidate| dir.join(candidate))
+            .find(|path| path.is_file())
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn read_bundle_version(bundle: &Path) -> Option<String> {
+    let output = std::process::Command::new("/usr/libexec/PlistBuddy")
+        .args(["-c", "Print :CFBundleShortVersionString"])
+        .arg(bundle.join("Contents/Info.plist"))
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+#[cfg(target_os = "macos")]
+fn find_platform_installation(hints: &InstallHints) -> Option<Installation> {
+    let bundle = hints.bundle?;
+    let mut locations = vec![PathBuf::from("/Applications")];
+    if let Some(home) = dirs::home_dir() {
+        locations.push(home.join("Applications"));
+    }
+
+    locations
+        .into_iter()
+        .map(|dir| dir.join(bundle))
+        .find(|bundle| bundle.exists())
+        .map(|bundle| Installation {
+            version: read_bundle_version(&bundle),
+            path: bundle,
+            source: DetectionSource::AppBundle,
+        })
+}
+
+#[cfg(target_os = "windows")]
+fn find_in_uninstall_keys(display_name: &str) -> Option<Installation> {
+    const UNINSTALL_KEYS: &[&str] = &[
+        r"HKCU\Software\Microsoft\Windows\CurrentVersion\Uninstall",
+        r"HKLM\Software\Microsoft\Windows\CurrentVersion\Uninstall",
+        r"HKLM\Software\WOW6432Node\Microsoft\Windows\CurrentVersion\Uninstall",
+    ];
+
+    UNINSTALL_KEYS.iter().find_map(|key| {
+        let output = std::process::Command::new("reg")
+            .args(["query", key, "/s"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+
+        let entries = parse_registry_entries(&String::from_utf8_lossy(&output.stdout));
+        find_registry_installation(&entries, display_name)
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn find_platform_installation(hints: &InstallHints) -> Option<Installation> {
+    if let Some(display_name) = hints.windows_display_name {
+        if let Some(installation) = find_in_uninstall_keys(display_name) {
+            return Some(installation);
+        }
+    }
+
+    let local_app_data = dirs::data_local_dir()?;
+    hints
+        .windows_executables
+        .iter()
+        .map(|relative| local_app_data.join(relative))
+        .find(|exe| exe.exists())
+        .map(|exe| Installation {
+            path: exe,
+            version: None,
+            source: DetectionSource::Executable,
+        })
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn find_platform_installation(hints: &InstallHints) -> Option<Installation> {
+    let desktop_name = hints.desktop_name?;
+    let mut locations = vec![
+        PathBuf::from("/usr/share/applications"),
+        PathBuf::from("/var/lib/flatpak/exports/share/applications"),
+    ];
+    if let Some(data_dir) = dirs::data_dir() {
+        locations.insert(0, data_dir.join("applications"));
+    }
+
+    for dir in locations {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("desktop") {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some((name, exec)) = parse_desktop_entry(&content) {
+                if name == desktop_name {
+                    let program = exec.split_whitespace().next().unwrap_or(&exec);
+                    return Some(Installation {
+                        path: PathBuf::from(program),
+                        version: None,
+                        source: DetectionSource::DesktopEntry,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+fn read_cli_version(cli: &Path) -> Option<String> {
+    let mut command = crate::environment::create_windowless_command(&cli.to_string_lossy());
+    let output = command.arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+fn find_path_installation(hints: &InstallHints) -> Option<Installation> {
+    let path_var = std::env::var_os("PATH")?;
+    hints
+        .cli_names
+        .iter()
+        .find_map(|name| find_in_path(name, &path_var))
+        .map(|cli| Installation {
+            version: read_cli_version(&cli),
+            path: cli,
+            source: DetectionSource::Path,
+        })
+}
+
+/// Finds where a client is installed, checking the platform's app locations
+/// before its launcher on PATH.
+pub fn find_installation(client: &ClientType) -> Option<Installation> {
+    let hints = install_hints(client);
+    find_platform_installation(&hints).or_else(|| find_path_installation(&hints))
+}
+
+pub fn detect_client(client: &ClientType) -> DetectedClient {
+    let installation = find_installation(client);
+    let config_path = clients::get_client_path_config(client)
+        .ok()
+        .map(|config| config.base_dir.join(config.config_filename));
+
+    debug!(
+        "Detected {}: {:?}",
+        client.as_str(),
+        installation.as_ref().map(|i| &i.path)
+    );
+
+    DetectedClient {
+        client: client.as_str().to_string(),
+        installed: installation.is_some(),
+        install_path: installation
+            .as_ref()
+            .map(|i| i.path.to_string_lossy().to_string()),
+        version: installation.as_ref().and_then(|i| i.version.clone()),
+        detected_by: installation.map(|i| i.source),
+        config_exists: config_path.as_ref().is_some_and(|path| path.exists()),
+        config_path: config_path.map(|path| path.to_string_lossy().to_string()),
+    }
+}
+
+/// The first installed client in `ClientType::all()` order, if any.
+pub fn detect_default_client() -> Option<ClientType> {
+    if crate::environment::is_test_mode() {
+        return None;
+    }
+
+    let mut detected = DETECTED_DEFAULT_CLIENT.lock().unwrap();
+    if detected.is_none() {
+        *detected = ClientType::all()
+            .into_iter()
+            .find(|client| find_installation(client).is_some());
+        info!("Detected default client: {:?}", *detected);
+    }
+    detected.clone()
+}
+
+#[tauri::command]
+pub async fn detect_clients() -> Result<Value, String> {
+    // Detection reads the registry and runs executables for their versions
+    let detected: Vec<DetectedClient> = tauri::async_runtime::spawn_blocking(|| {
+        ClientType::all().iter().map(detect_client).collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to detect clients: {}", e))?;
+    serde_json::to_value(detected).map_err(|e| format!("Failed to serialize clients: {}", e))
+}
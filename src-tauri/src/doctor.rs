@@ -0,0 +1,622 @@
+use crate::clients::{ClientType, ConfigScope};
+use crate::environment::{self, create_windowless_command, is_test_mode};
+use log::{debug, info, warn};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Environment checks behind `staten doctor` and the troubleshooting view.
+// Every check is reported, healthy or not, with a severity and, when
+// something is wrong, a fix the user can act on. Nothing is installed or
+// repaired here; environment setup does that.
+
+/// Below this much free space, Node.js, Python and app downloads fail
+pub const MIN_FREE_BYTES: u64 = 500 * 1024 * 1024;
+/// Below this much free space, a few more downloads may fill the disk
+pub const LOW_FREE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    /// Worth knowing, but nothing is broken
+    Info,
+    /// Works for now, or only some apps are affected
+    Warning,
+    /// Apps can't be installed or started until it's fixed
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Check {
+    /// Stable identifier, such as `uv` or `client.claude`
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    pub message: String,
+    /// What the user can do about it, set unless the check is ok
+    pub fix: Option<String>,
+    /// Paths, versions and other facts the check looked at
+    pub details: Value,
+}
+
+impl Check {
+    fn new(id: &str, title: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Check {
+            id: id.to_string(),
+            title: title.to_string(),
+            severity,
+            message: message.into(),
+            fix: None,
+            details: Value::Null,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+
+    fn details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DoctorReport {
+    /// No check is an error
+    pub healthy: bool,
+    /// The worst severity of any check
+    pub severity: Severity,
+    pub checks: Vec<Check>,
+}
+
+// `--version` output of a tool, `None` in test mode or when it doesn't run
+fn tool_version(path: &Path) -> Option<String> {
+    if is_test_mode() {
+        return None;
+    }
+
+    let output = create_windowless_command(&path.to_string_lossy())
+        .arg("--version")
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn check_uv() -> Check {
+    let pinned = crate::uv::uv_manifest()
+        .map(|manifest| manifest.version)
+        .ok();
+    let setup_fix = format!(
+        "Run environment setup in Staten to download uv{}, or install uv yourself",
+        pinned
+            .as_ref()
+            .map(|version| format!(" {}", version))
+            .unwrap_or_default()
+    );
+
+    let Some(uvx) = environment::find_existing_uvx().map(PathBuf::from) else {
+        return Check::new("uv", "uv", Severity::Error, "uvx was not found")
+            .fix(setup_fix)
+            .details(json!({ "pinnedVersion": pinned }));
+    };
+
+    let (staten_uv, staten_uvx) = crate::uv::uv_paths();
+    let managed = uvx == staten_uvx;
+    let uv = if managed {
+        staten_uv
+    } else {
+        uvx.with_file_name(format!("uv{}", std::env::consts::EXE_SUFFIX))
+    };
+    let version = tool_version(&uvx);
+    let details = json!({
+        "uvx": uvx,
+        "uv": uv.is_file().then_some(&uv),
+        "version": version,
+        "managed": managed,
+        "pinnedVersion": pinned,
+    });
+
+    if !is_test_mode() && version.is_none() {
+        return Check::new(
+            "uv",
+            "uv",
+            Severity::Error,
+            format!("uvx at {} does not run", uvx.display()),
+        )
+        .fix("Reinstall uv, or remove it so Staten installs its own")
+        .details(details);
+    }
+
+    let installed = crate::uv::installed_uv_version();
+    if managed && pinned.is_some() && installed != pinned {
+        return Check::new(
+            "uv",
+            "uv",
+            Severity::Warning,
+            format!(
+                "Staten's uv is {}, this version of Staten uses {}",
+                installed.as_deref().unwrap_or("unknown"),
+                pinned.as_deref().unwrap_or_default()
+            ),
+        )
+        .fix("Run environment setup in Staten to update uv")
+        .details(details);
+    }
+
+    Check::new(
+        "uv",
+        "uv",
+        Severity::Ok,
+        format!("uvx found at {}", uvx.display()),
+    )
+    .details(details)
+}
+
+fn check_node() -> Check {
+    let version = crate::node::default_node_version();
+    let (node, npx) = crate::node::node_paths(&version);
+    let mut details = json!({
+        "version": version,
+        "node": node,
+        "npx": npx,
+        "nodeDir": crate::node::node_root(),
+        "installedVersions": crate::node::installed_versions(),
+    });
+
+    if !crate::node::is_node_installed(&version) {
+        return Check::new(
+            "node",
+            "Node.js",
+            Severity::Error,
+            format!("Node.js {} is not installed", version),
+        )
+        .fix(format!(
+            "Run environment setup in Staten to download Node.js {}",
+            version
+        ))
+        .details(details);
+    }
+
+    let reported = tool_version(&node);
+    if !is_test_mode() && reported.is_none() {
+        return Check::new(
+            "node",
+            "Node.js",
+            Severity::Error,
+            format!("Node.js at {} does not run", node.display()),
+        )
+        .fix(format!(
+            "Remove {} and run environment setup in Staten to download it again",
+            crate::node::node_dir(&version).display()
+        ))
+        .details(details);
+    }
+    details["reportedVersion"] = json!(reported);
+
+    Check::new(
+        "node",
+        "Node.js",
+        Severity::Ok,
+        format!("Node.js {} is installed", version),
+    )
+    .details(details)
+}
+
+fn check_shims() -> Vec<Check> {
+    let npx_shim = environment::get_npx_shim_path();
+    let (node, _) = crate::node::node_paths(&crate::node::default_node_version());
+    let npx_details = json!({ "path": npx_shim });
+    let npx = if !npx_shim.is_file() {
+        Check::new(
+            "npxShim",
+            "npx shim",
+            Severity::Warning,
+            "The npx shim has not been created",
+        )
+        .fix("Run environment setup in Staten to create it")
+    } else if !environment::is_shim_current(&npx_shim)
+        || !std::fs::read_to_string(&npx_shim)
+            .is_ok_and(|content| content.contains(&*node.to_string_lossy()))
+    {
+        Check::new(
+            "npxShim",
+            "npx shim",
+            Severity::Warning,
+            "The npx shim is outdated or runs another Node.js version",
+        )
+        .fix("Run environment setup in Staten to rewrite it")
+    } else {
+        Check::new(
+            "npxShim",
+            "npx shim",
+            Severity::Ok,
+            "The npx shim is up to date",
+        )
+    };
+
+    let uvx_shim = environment::get_uvx_shim_path();
+    let uvx_details = json!({ "path": uvx_shim });
+    let uvx = if !uvx_shim.is_file() {
+        Check::new(
+            "uvxShim",
+            "uvx shim",
+            Severity::Info,
+            "The uvx shim is created when the first uvx app is installed",
+        )
+    } else if !environment::is_shim_current(&uvx_shim) {
+        Check::new(
+            "uvxShim",
+            "uvx shim",
+            Severity::Warning,
+            "The uvx shim was written by an older version of Staten",
+        )
+        .fix("Reinstall any uvx app to rewrite it")
+    } else {
+        Check::new(
+            "uvxShim",
+            "uvx shim",
+            Severity::Ok,
+            "The uvx shim is up to date",
+        )
+    };
+
+    vec![npx.details(npx_details), uvx.details(uvx_details)]
+}
+
+fn check_client(client: &ClientType) -> Check {
+    let id = format!("client.{}", client.as_str().to_lowercase());
+    let detected = crate::detect::detect_client(client);
+    let mut details = serde_json::to_value(&detected).unwrap_or_default();
+    let config_path = match crate::app::get_config_path(client, &ConfigScope::User) {
+        Ok(path) => path,
+        Err(e) => {
+            return Check::new(&id, client.as_str(), Severity::Error, e)
+                .fix("Reset the client's config path in Staten's settings")
+                .details(details);
+        }
+    };
+    details["configPath"] = json!(config_path);
+
+    if !config_path.exists() {
+        let message = if detected.installed {
+            "Installed, Staten creates its config on the first install"
+        } else {
+            "Not installed"
+        };
+        return Check::new(&id, client.as_str(), Severity::Info, message).details(details);
+    }
+
+    let parsed = std::fs::read_to_string(&config_path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| crate::config_format::for_client(client).parse(&text));
+    match parsed {
+        Ok(_) => Check::new(
+            &id,
+            client.as_str(),
+            Severity::Ok,
+            format!("Config at {} is valid", config_path.display()),
+        )
+        .details(details),
+        Err(e) => Check::new(
+            &id,
+            client.as_str(),
+            Severity::Error,
+            format!("Config at {} can't be read: {}", config_path.display(), e),
+        )
+        .fix(format!(
+            "Fix the error in {}, or move the file aside so Staten creates a new one",
+            config_path.display()
+        ))
+        .details(details),
+    }
+}
+
+fn check_registry_source(url: &str) -> Result<usize, String> {
+    let registry: Value = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?
+        .json()
+        .map_err(|e| format!("Not valid JSON: {}", e))?;
+    registry
+        .as_array()
+        .map(Vec::len)
+        .ok_or_else(|| "Not a list of apps".to_string())
+}
+
+fn check_registry() -> Check {
+    let sources = crate::settings::load_settings()
+        .map(|settings| settings.registry_sources)
+        .unwrap_or_else(|_| vec![crate::settings::DEFAULT_REGISTRY_URL.to_string()]);
+
+    let results: Vec<(String, Result<usize, String>)> = sources
+        .into_iter()
+        .map(|url| {
+            let result = check_registry_source(&url);
+            debug!("Registry source {}: {:?}", url, result);
+            (url, result)
+        })
+        .collect();
+    let details = json!(results
+        .iter()
+        .map(|(url, result)| match result {
+            Ok(apps) => json!({ "url": url, "reachable": true, "apps": apps }),
+            Err(e) => json!({ "url": url, "reachable": false, "error": e }),
+        })
+        .collect::<Vec<_>>());
+
+    let failed: Vec<&String> = results
+        .iter()
+        .filter(|(_, result)| result.is_err())
+        .map(|(url, _)| url)
+        .collect();
+    let fix = "Check your network connection, or the registry sources in Staten's settings";
+    if failed.is_empty() {
+        Check::new(
+            "registry",
+            "App registry",
+            Severity::Ok,
+            "The app registry is reachable",
+        )
+        .details(details)
+    } else if failed.len() < results.len() {
+        Check::new(
+            "registry",
+            "App registry",
+            Severity::Warning,
+            format!(
+                "Some registry sources can't be reached: {}",
+                failed
+                    .iter()
+                    .map(|url| url.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+        .fix(fix)
+        .details(details)
+    } else {
+        Check::new(
+            "registry",
+            "App registry",
+            Severity::Error,
+            "No registry source can be reached",
+        )
+        .fix(fix)
+        .details(details)
+    }
+}
+
+/// Reads the available space from `df -Pk` output.
+pub fn parse_df(output: &str) -> Option<u64> {
+    let available: u64 = output
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(3)?
+        .parse()
+        .ok()?;
+    Some(available * 1024)
+}
+
+pub fn disk_space_severity(free_bytes: u64) -> Severity {
+    if free_bytes < MIN_FREE_BYTES {
+        Severity::Error
+    } else if free_bytes < LOW_FREE_BYTES {
+        Severity::Warning
+    } else {
+        Severity::Ok
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn free_disk_space(path: &Path) -> Option<u64> {
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .ok()?;
+    parse_df(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "windows")]
+fn free_disk_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut free: winapi::um::winnt::ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+    let ok = unsafe {
+        winapi::um::fileapi::GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut free,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then(|| unsafe { *free.QuadPart() })
+}
+
+fn check_disk_space() -> Check {
+    // Node.js, Python and downloaded apps all live next to each other
+    let data_dir = crate::node::node_root()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let existing = data_dir
+        .ancestors()
+        .find(|dir| dir.exists())
+        .unwrap_or(&data_dir)
+        .to_path_buf();
+
+    let Some(free) = free_disk_space(&existing) else {
+        return Check::new(
+            "diskSpace",
+            "Disk space",
+            Severity::Info,
+            "Free disk space could not be determined",
+        )
+        .details(json!({ "path": data_dir }));
+    };
+
+    let details = json!({ "path": data_dir, "freeBytes": free });
+    let free_mb = free / (1024 * 1024);
+    match disk_space_severity(free) {
+        Severity::Ok => Check::new(
+            "diskSpace",
+            "Disk space",
+            Severity::Ok,
+            format!("{} MB free", free_mb),
+        ),
+        severity => Check::new(
+            "diskSpace",
+            "Disk space",
+            severity,
+            format!(
+                "Only {} MB free for Node.js, Python and app downloads",
+                free_mb
+            ),
+        )
+        .fix("Free up disk space on the drive with your home folder"),
+    }
+    .details(details)
+}
+
+/// Problems with a PATH-style list: it's empty, or lists directories that
+/// don't exist or appear more than once.
+pub fn path_issues(path_var: &OsStr) -> Vec<String> {
+    let entries: Vec<PathBuf> = std::env::split_paths(path_var)
+        .filter(|entry| !entry.as_os_str().is_empty())
+        .collect();
+    if entries.is_empty() {
+        return vec!["PATH is empty".to_string()];
+    }
+
+    let mut issues = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if entries[..i].contains(entry) {
+            issues.push(format!("{} is listed more than once", entry.display()));
+        } else if !entry.is_dir() {
+            issues.push(format!("{} does not exist", entry.display()));
+        }
+    }
+    issues
+}
+
+fn check_path() -> Check {
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    let issues = path_issues(&path_var);
+    let details = json!({
+        "entries": std::env::split_paths(&path_var).collect::<Vec<_>>(),
+        "issues": issues,
+        "uvx": crate::detect::find_in_path("uvx", &path_var),
+        "npx": crate::detect::find_in_path("npx", &path_var),
+    });
+
+    if path_var.is_empty() {
+        return Check::new("path", "PATH", Severity::Warning, "PATH is not set")
+            .fix("Set PATH in your shell profile, Staten and its clients use it to find system tools")
+            .details(details);
+    }
+    if issues.is_empty() {
+        return Check::new("path", "PATH", Severity::Ok, "PATH looks fine").details(details);
+    }
+
+    // Staten's shims use absolute paths, so these never break apps
+    Check::new(
+        "path",
+        "PATH",
+        Severity::Info,
+        format!("PATH has {} unusual entries", issues.len()),
+    )
+    .fix("Remove missing or duplicate directories from PATH in your shell profile")
+    .details(details)
+}
+
+fn check_container_engine() -> Check {
+    let status = environment::get_container_engine_status();
+    let title = "Container engine";
+    if status["running"] == json!(true) {
+        Check::new(
+            "containerEngine",
+            title,
+            Severity::Ok,
+            format!(
+                "{} is running",
+                status["engine"].as_str().unwrap_or_default()
+            ),
+        )
+    } else if status["available"] == json!(true) {
+        Check::new(
+            "containerEngine",
+            title,
+            Severity::Warning,
+            format!(
+                "{} is installed but not running",
+                status["engine"].as_str().unwrap_or_default()
+            ),
+        )
+        .fix("Start Docker Desktop or the Podman machine to use container apps")
+    } else {
+        // Only container apps need an engine
+        Check::new(
+            "containerEngine",
+            title,
+            Severity::Info,
+            "No container engine found",
+        )
+        .fix("Install Docker Desktop or Podman to use container apps")
+    }
+    .details(status)
+}
+
+/// Runs every check.
+pub fn run_checks() -> DoctorReport {
+    info!("Running environment checks");
+    let mut checks = vec![check_uv(), check_node()];
+    checks.extend(check_shims());
+    checks.extend(ClientType::all().iter().map(check_client));
+    checks.push(check_registry());
+    checks.push(check_disk_space());
+    checks.push(check_path());
+    checks.push(check_container_engine());
+
+    for check in checks
+        .iter()
+        .filter(|check| check.severity >= Severity::Warning)
+    {
+        warn!("{}: {}", check.id, check.message);
+    }
+
+    let severity = checks
+        .iter()
+        .map(|check| check.severity)
+        .max()
+        .unwrap_or(Severity::Ok);
+    DoctorReport {
+        healthy: severity < Severity::Error,
+        severity,
+        checks,
+    }
+}
+
+#[tauri::command]
+pub async fn get_doctor_report() -> Result<DoctorReport, String> {
+    // The checks run commands and hit the network
+    tauri::async_runtime::spawn_blocking(run_checks)
+        .await
+        .map_err(|e| format!("Failed to run environment checks: {}", e))
+}
@@ -0,0 +1,366 @@
+use flate2::read::GzDecoder;
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Apps that ship as prebuilt executables, typically Go or Rust servers. The
+// registry lists a download per OS and architecture with its SHA-256, and the
+// executable is unpacked into ~/.local/share/staten/apps/<app>/<version>.
+
+static TEST_APPS_DIR: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+#[cfg(feature = "test-utils")]
+pub fn set_test_apps_dir(dir: Option<PathBuf>) {
+    *TEST_APPS_DIR.lock().unwrap() = dir;
+}
+
+/// Where downloaded apps are kept, one directory per app.
+pub fn apps_dir() -> PathBuf {
+    if let Some(dir) = TEST_APPS_DIR.lock().unwrap().clone() {
+        return dir;
+    }
+    if crate::environment::is_test_mode() {
+        return PathBuf::from("/test/.local/share/staten/apps");
+    }
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".local/share/staten/apps")
+}
+
+// The mcpKey names the app's directory, so it must be a single plain path
+// component that stays inside the apps directory
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':'])
+}
+
+// `bin` is joined onto the unpacked download and must stay inside it
+fn is_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// The registry key for this machine, such as `macos-aarch64`.
+pub fn platform_key() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveKind {
+    /// The download is the executable itself
+    Raw,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "raw" | "none" => Some(ArchiveKind::Raw),
+            "tar.gz" | "tgz" => Some(ArchiveKind::TarGz),
+            "zip" => Some(ArchiveKind::Zip),
+            _ => None,
+        }
+    }
+
+    fn from_url(url: &str) -> Self {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            ArchiveKind::TarGz
+        } else if path.ends_with(".zip") {
+            ArchiveKind::Zip
+        } else {
+            ArchiveKind::Raw
+        }
+    }
+}
+
+/// The download for one platform.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryAsset {
+    pub url: String,
+    /// Hex SHA-256 of the downloaded file
+    pub sha256: String,
+    pub archive: ArchiveKind,
+    /// Path of the executable, relative to the unpacked download
+    pub bin: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryApp {
+    /// Directory name under the apps directory, the app's mcpKey
+    pub name: String,
+    pub version: String,
+    /// None when the app has no build for this platform
+    pub asset: Option<BinaryAsset>,
+}
+
+impl BinaryApp {
+    /// Reads the `version` and the `platforms` downloads of a registry app's
+    /// config, keeping the one for this machine.
+    pub fn from_registry(name: &str, config: &Map<String, Value>) -> Result<Self, String> {
+        Self::for_platform(name, config, &platform_key())
+    }
+
+    pub fn for_platform(
+        name: &str,
+        config: &Map<String, Value>,
+        platform: &str,
+    ) -> Result<Self, String> {
+        if !is_plain_name(name) {
+            return Err(format!("Invalid app key: {}", name));
+        }
+        let version = config
+            .get("version")
+            .and_then(|v| v.as_str())
+            .ok_or("version is missing")?
+            .to_string();
+        // The version becomes a directory name
+        if version.is_empty() || version.contains(['/', '\\']) || version.starts_with('.') {
+            return Err(format!("Invalid version for {}: {}", name, version));
+        }
+        let platforms = config
+            .get("platforms")
+            .and_then(|p| p.as_object())
+            .ok_or("platforms is missing")?;
+
+        let asset = match platforms.get(platform) {
+            Some(download) => {
+                let url = download["url"]
+                    .as_str()
+                    .ok_or_else(|| format!("url is missing for {}", platform))?
+                    .to_string();
+                let sha256 = download["sha256"]
+                    .as_str()
+                    .filter(|s| s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit()))
+                    .ok_or_else(|| format!("Invalid or missing sha256 for {}", platform))?
+                    .to_lowercase();
+                let archive = match download["archive"].as_str() {
+                    Some(kind) => ArchiveKind::parse(kind)
+                        .ok_or_else(|| format!("Unknown archive type: {}", kind))?,
+                    None => ArchiveKind::from_url(&url),
+                };
+                let bin = match download["bin"].as_str() {
+                    Some(bin) => bin.to_string(),
+                    None if archive == ArchiveKind::Raw => {
+                        format!("{}{}", name, std::env::consts::EXE_SUFFIX)
+                    }
+                    None => return Err(format!("bin is missing for {}", platform)),
+                };
+                if !is_relative_path(&bin) {
+                    return Err(format!("Invalid bin for {}: {}", platform, bin));
+                }
+                Some(BinaryAsset {
+                    url,
+                    sha256,
+                    archive,
+                    bin,
+                })
+            }
+            None => None,
+        };
+
+        Ok(BinaryApp {
+            name: name.to_string(),
+            version,
+            asset,
+        })
+    }
+
+    pub fn install_dir(&self) -> PathBuf {
+        apps_dir().join(&self.name).join(&self.version)
+    }
+
+    /// Where the executable ends up, which is the command clients start.
+    pub fn executable(&self) -> Option<PathBuf> {
+        self.asset
+            .as_ref()
+            .map(|asset| self.install_dir().join(&asset.bin))
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Unpacks a verified download into `dest`, which must exist.
+pub fn unpack(bytes: &[u8], asset: &BinaryAsset, dest: &Path) -> Result<(), String> {
+    match asset.archive {
+        ArchiveKind::Raw => fs::write(dest.join(&asset.bin), bytes)
+            .map_err(|e| format!("Failed to write {}: {}", asset.bin, e)),
+        kind => unpack_archive(bytes, kind, dest),
+    }
+}
+
+/// Unpacks a tar.gz or zip archive into `dest`, which must exist.
+pub(crate) fn unpack_archive(bytes: &[u8], kind: ArchiveKind, dest: &Path) -> Result<(), String> {
+    match kind {
+        ArchiveKind::Raw => Err("Not an archive".to_string()),
+        ArchiveKind::TarGz => tar::Archive::new(GzDecoder::new(bytes))
+            .unpack(dest)
+            .map_err(|e| format!("Failed to unpack archive: {}", e)),
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(Cursor::new(bytes))
+                .map_err(|e| format!("Failed to read archive: {}", e))?;
+            for i in 0..archive.len() {
+                let mut file = archive
+                    .by_index(i)
+                    .map_err(|e| format!("Failed to read archive: {}", e))?;
+                // Entries that would land outside `dest` are skipped
+                let Some(path) = file.enclosed_name().map(|p| dest.join(p)) else {
+                    warn!("Skipping unsafe archive entry: {}", file.name());
+                    continue;
+                };
+                if file.is_dir() {
+                    fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+                    continue;
+                }
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let mut contents = Vec::new();
+                file.read_to_end(&mut contents)
+                    .map_err(|e| format!("Failed to unpack {}: {}", file.name(), e))?;
+                fs::write(&path, contents)
+                    .map_err(|e| format!("Failed to unpack {}: {}", file.name(), e))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(unix)]
+pub(crate) fn mark_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .map_err(|e| format!("Failed to mark {} executable: {}", path.display(), e))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn mark_executable(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+pub(crate) fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    let response = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(300))
+        .build()
+        .map_err(|e| e.to_string())?
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    response
+        .bytes()
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("Failed to download {}: {}", url, e))
+}
+
+/// Downloads, verifies and unpacks the app unless this version is already
+/// there, and returns the path of the executable. Other versions stay until
+/// `remove_other_versions` finds them unused.
+pub fn ensure_binary(app: &BinaryApp) -> Result<PathBuf, String> {
+    let asset = app.asset.as_ref().ok_or_else(|| {
+        format!(
+            "{} has no download for this platform ({})",
+            app.name,
+            platform_key()
+        )
+    })?;
+    let install_dir = app.install_dir();
+    let executable = install_dir.join(&asset.bin);
+    if executable.is_file() {
+        debug!("{} {} is already downloaded", app.name, app.version);
+        return Ok(executable);
+    }
+
+    info!(
+        "Downloading {} {} from {}",
+        app.name, app.version, asset.url
+    );
+    let bytes = fetch(&asset.url)?;
+    let checksum = sha256_hex(&bytes);
+    if checksum != asset.sha256 {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            asset.url, asset.sha256, checksum
+        ));
+    }
+
+    // Unpacked next to the final directory and moved in place, so a failed
+    // download never leaves a half-written version behind
+    let app_dir = apps_dir().join(&app.name);
+    let partial = app_dir.join(format!(".{}.partial", app.version));
+    if partial.exists() {
+        fs::remove_dir_all(&partial).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(&partial)
+        .map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+
+    let unpacked = unpack(&bytes, asset, &partial).and_then(|_| {
+        let bin = partial.join(&asset.bin);
+        if !bin.is_file() {
+            return Err(format!("{} was not found in the download", asset.bin));
+        }
+        mark_executable(&bin)
+    });
+    if let Err(e) = unpacked {
+        let _ = fs::remove_dir_all(&partial);
+        return Err(e);
+    }
+
+    if install_dir.exists() {
+        fs::remove_dir_all(&install_dir).map_err(|e| e.to_string())?;
+    }
+    fs::rename(&partial, &install_dir)
+        .map_err(|e| format!("Failed to move {} in place: {}", app.name, e))?;
+    info!(
+        "Installed {} {} at {}",
+        app.name,
+        app.version,
+        executable.display()
+    );
+
+    Ok(executable)
+}
+
+/// Removes the app's other versions, except those `in_use` says a config
+/// still starts. Clients update one at a time, so an older version can
+/// outlive the install of a newer one.
+pub fn remove_other_versions(app: &BinaryApp, in_use: impl Fn(&Path) -> bool) {
+    let Ok(entries) = fs::read_dir(apps_dir().join(&app.name)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if entry.file_name() == app.version.as_str() || in_use(&entry.path()) {
+            continue;
+        }
+        info!("Removing {} {:?}", app.name, entry.file_name());
+        if let Err(e) = fs::remove_dir_all(entry.path()) {
+            warn!("Failed to remove {}: {}", entry.path().display(), e);
+        }
+    }
+}
+
+/// Removes every downloaded version of the app.
+pub fn remove_binary(name: &str) -> Result<(), String> {
+    if !is_plain_name(name) {
+        return Err(format!("Invalid app key: {}", name));
+    }
+    let dir = apps_dir().join(name);
+    if !dir.exists() {
+        return Ok(());
+    }
+    info!("Removing downloaded files of {}", name);
+    fs::remove_dir_all(&dir).map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))
+}
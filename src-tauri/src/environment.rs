@@ -12,13 +12,14 @@ use std::os::windows::process::CommandExt;
 pub const CREATE_NO_WINDOW: u32 = 0x08000000;
 
 static UV_INSTALLED: AtomicBool = AtomicBool::new(false);
-static NVM_INSTALLED: AtomicBool = AtomicBool::new(false);
 static NODE_INSTALLED: AtomicBool = AtomicBool::new(false);
 static ENVIRONMENT_SETUP_STARTED: AtomicBool = AtomicBool::new(false);
 static ENVIRONMENT_SETUP_COMPLETED: AtomicBool = AtomicBool::new(false);
-static NODE_VERSION: &str = "v20.9.0";
 static IS_TEST_MODE: AtomicBool = AtomicBool::new(false);
 
+// Bump when the generated shim scripts change so existing shims get rewritten
+const SHIM_VERSION: u32 = 6;
+
 // Lock to prevent concurrent environment setup operations
 static ENVIRONMENT_SETUP_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
 
@@ -78,11 +79,55 @@ pub fn get_npx_shim_path() -> std::path::PathBuf {
     }
 }
 
-fn find_existing_uvx() -> Option<String> {
+pub fn get_uvx_shim_path() -> std::path::PathBuf {
+    if is_test_mode() {
+        return std::path::PathBuf::from("/test/.local/share/staten/bin/uvx-staten");
+    }
+
+    #[cfg(target_os = "windows")]
+    let path = dirs::data_local_dir()
+        .unwrap_or_default()
+        .join("staten")
+        .join("bin")
+        .join("uvx-staten.cmd");
+
+    #[cfg(not(target_os = "windows"))]
+    let path = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".local/share/staten/bin/uvx-staten");
+
+    path
+}
+
+/// The uvx shim of apps with a `requires-python`, which run on the
+/// interpreters Staten installs.
+pub fn get_uvx_python_shim_path() -> std::path::PathBuf {
+    let name = if cfg!(target_os = "windows") {
+        "uvx-staten-python.cmd"
+    } else {
+        "uvx-staten-python"
+    };
+    crate::uv::uv_bin_dir().join(name)
+}
+
+/// Whether `path` is one of the placeholders test mode uses for Staten's
+/// files, which are never written or checked.
+pub fn is_test_placeholder(path: &std::path::Path) -> bool {
+    is_test_mode() && path.starts_with("/test")
+}
+
+pub(crate) fn find_existing_uvx() -> Option<String> {
     if is_test_mode() {
         return Some("/test/.local/bin/uvx".to_string());
     }
 
+    // The one Staten installed comes first
+    let (_, staten_uvx) = crate::uv::uv_paths();
+    if staten_uvx.is_file() {
+        debug!("Found Staten's uvx at {}", staten_uvx.display());
+        return Some(staten_uvx.to_string_lossy().to_string());
+    }
+
     #[cfg(target_os = "macos")]
     {
         let home_dir = match dirs::home_dir() {
@@ -231,117 +276,167 @@ pub fn get_uvx_path() -> Result<String, String> {
     Err("uvx not found in PATH and installation failed. Please install it manually.".to_string())
 }
 
-pub fn get_nvm_node_paths() -> Result<(String, String), String> {
-    debug!("get_nvm_node_paths called, test_mode: {}", is_test_mode());
-
+/// uv itself, which is installed next to uvx.
+pub fn get_uv_path() -> Result<String, String> {
     if is_test_mode() {
-        debug!("Using test mode paths for nvm/node");
-        return Ok((
-            "/test/.nvm/versions/node/v20.9.0/bin/node".to_string(),
-            "/test/.nvm/versions/node/v20.9.0/bin/npx".to_string(),
-        ));
+        return Ok("/test/.local/bin/uv".to_string());
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        let shell_command = format!(
-            r#"
-        export NVM_DIR="$HOME/.nvm"
-        [ -s "$NVM_DIR/nvm.sh" ] && \. "$NVM_DIR/nvm.sh"
-        nvm use {} > /dev/null 2>&1
-        which node
-        which npx
-    "#,
-            NODE_VERSION
-        );
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to get node paths: {}", e))?;
-
-        if !output.status.success() {
-            return Err("Failed to get node and npx paths".to_string());
-        }
+    let uvx_path = get_uvx_path()?;
+    let uv_path = std::path::Path::new(&uvx_path)
+        .with_file_name(format!("uv{}", std::env::consts::EXE_SUFFIX));
+    if uv_path.exists() {
+        return Ok(uv_path.to_string_lossy().to_string());
+    }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let mut lines = output_str.lines();
+    // uvx was found through PATH, so uv should be too
+    debug!("uv not found next to {}, using the one in PATH", uvx_path);
+    Ok("uv".to_string())
+}
 
-        let node_path = lines
-            .next()
-            .ok_or("Failed to get node path")?
-            .trim()
-            .to_string();
+/// The node and npx of Staten's managed Node.js, installed first if needed.
+pub fn get_node_paths(version: &str) -> Result<(String, String), String> {
+    debug!(
+        "get_node_paths called for {}, test_mode: {}",
+        version,
+        is_test_mode()
+    );
 
-        let npx_path = lines
-            .next()
-            .ok_or("Failed to get npx path")?
-            .trim()
-            .to_string();
+    if is_test_mode() {
+        debug!("Using test mode paths for node");
+        return Ok((
+            format!("/test/.local/share/staten/node/{}/bin/node", version),
+            format!("/test/.local/share/staten/node/{}/bin/npx", version),
+        ));
+    }
 
-        // Only validate paths in non-test mode
-        if !is_test_mode() && !node_path.contains(".nvm/versions/node") {
-            debug!("Node path validation failed: {}", node_path);
-            return Err("Node path is not from nvm installation".to_string());
-        }
+    crate::node::install_node(version)
+}
 
-        Ok((node_path, npx_path))
+/// The npx shim of apps that run on a Node.js version other than the default.
+pub fn get_versioned_npx_shim_path(version: &str) -> std::path::PathBuf {
+    if is_test_mode() {
+        return std::path::PathBuf::from(format!(
+            "/test/.local/share/staten/bin/npx-staten-{}",
+            version
+        ));
     }
 
     #[cfg(target_os = "windows")]
-    {
-        if !check_nvm_installed() {
-            return Err("NVM for Windows is not installed".to_string());
-        }
+    let path = dirs::data_local_dir()
+        .unwrap_or_default()
+        .join("staten")
+        .join("bin")
+        .join(format!("npx-staten-{}.cmd", version));
+
+    #[cfg(not(target_os = "windows"))]
+    let path = dirs::home_dir()
+        .unwrap_or_default()
+        .join(format!(".local/share/staten/bin/npx-staten-{}", version));
+
+    path
+}
 
-        let nvm_root = std::env::var("NVM_HOME")
-            .ok()
-            .map(std::path::PathBuf::from)
-            .or_else(|| dirs::home_dir().map(|p| p.join("AppData").join("Roaming").join("nvm")))
-            .ok_or("Could not determine NVM_HOME")?;
+fn shim_marker() -> String {
+    format!("staten-shim-version: {}", SHIM_VERSION)
+}
 
-        let version_no_v = NODE_VERSION.trim_start_matches('v');
+pub(crate) fn is_shim_current(shim_path: &std::path::Path) -> bool {
+    std::fs::read_to_string(shim_path)
+        .map(|content| content.contains(&shim_marker()))
+        .unwrap_or(false)
+}
 
-        let _ = Command::new("nvm")
-            .arg("use")
-            .arg(version_no_v)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
+// POSIX part shared by the npx and uvx shims. When STATEN_LOG_APP is set on the
+// server entry, stderr is teed into the app's log folder (and still forwarded to
+// the client), and every JSON-RPC line is appended to protocol.log.
+#[cfg(not(target_os = "windows"))]
+fn unix_shim_exec(exec_var: &str) -> String {
+    format!(
+        r#"if [ -n "${log_env}" ]; then
+  LOG_DIR="{logs_dir}/${log_env}"
+  mkdir -p "$LOG_DIR"
+
+  for f in {stderr_log} {protocol_log}; do
+    if [ -f "$LOG_DIR/$f" ] && [ "$(wc -c < "$LOG_DIR/$f")" -gt {max_bytes} ]; then
+      i={max_rotated}
+      while [ "$i" -gt 1 ]; do
+        prev=$((i - 1))
+        [ -f "$LOG_DIR/$f.$prev" ] && mv -f "$LOG_DIR/$f.$prev" "$LOG_DIR/$f.$i"
+        i=$prev
+      done
+      mv -f "$LOG_DIR/$f" "$LOG_DIR/$f.1"
+    fi
+  done
+
+  log_traffic() {{
+    while IFS= read -r line || [ -n "$line" ]; do
+      printf '%s\n' "$line"
+      printf '%s %s\n' "$1" "$line" >> "$LOG_DIR/{protocol_log}"
+    done
+  }}
+
+  # The pipeline's status is the last log_traffic's, so the server's own
+  # status goes through a file
+  STATUS_FILE="$LOG_DIR/.exit-status.$$"
+  {{ {{ log_traffic "-->" | {{ "${exec_var}" "$@"; echo $? > "$STATUS_FILE"; }} 2>&1 1>&3 3>&- | tee -a "$LOG_DIR/{stderr_log}" 1>&2; }} 3>&1 | log_traffic "<--"; }}
+  status=$(cat "$STATUS_FILE" 2>/dev/null || echo 1)
+  rm -f "$STATUS_FILE"
+  exit "$status"
+fi
+
+exec "${exec_var}" "$@"
+"#,
+        log_env = crate::logs::LOG_APP_ENV,
+        logs_dir = crate::logs::get_app_logs_dir().display(),
+        stderr_log = crate::logs::STDERR_LOG,
+        protocol_log = crate::logs::PROTOCOL_LOG,
+        max_bytes = crate::logs::MAX_LOG_BYTES,
+        max_rotated = crate::logs::MAX_ROTATED_LOGS,
+        exec_var = exec_var
+    )
+}
 
-        let possible_node_paths = vec![
-            nvm_root.join(version_no_v).join("node.exe"),
-            nvm_root.join(format!("v{}", version_no_v)).join("node.exe"),
-        ];
+// cmd has no tee, so on Windows only stderr is captured when logging is enabled.
+#[cfg(target_os = "windows")]
+fn windows_shim_exec(exec_var: &str) -> String {
+    format!(
+        r#"if not defined {log_env} goto run
+
+set LOG_DIR={logs_dir}\%{log_env}%
+if not exist "%LOG_DIR%" mkdir "%LOG_DIR%"
+for %%F in ("%LOG_DIR%\{stderr_log}") do if %%~zF GTR {max_bytes} move /Y "%%~F" "%%~F.1" >nul
+"%{exec_var}%" %* 2>>"%LOG_DIR%\{stderr_log}"
+exit /b %ERRORLEVEL%
+
+:run
+"%{exec_var}%" %*
+"#,
+        log_env = crate::logs::LOG_APP_ENV,
+        logs_dir = crate::logs::get_app_logs_dir().display(),
+        stderr_log = crate::logs::STDERR_LOG,
+        max_bytes = crate::logs::MAX_LOG_BYTES,
+        exec_var = exec_var
+    )
+}
 
-        let node_path = possible_node_paths
-            .iter()
-            .find(|path| path.exists())
-            .ok_or_else(|| {
-                format!(
-                    "Node.js executable not found at any of the expected paths: {:?}",
-                    possible_node_paths
-                )
-            })?;
-
-        let parent_dir = node_path
-            .parent()
-            .ok_or("Could not determine parent directory of node.exe")?;
+fn write_shim(shim_path: &std::path::Path, shim_content: &str) -> Result<(), String> {
+    if let Some(parent) = shim_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create shim directory: {}", e))?;
+    }
 
-        let npx_path = parent_dir.join("npx.cmd");
+    std::fs::write(shim_path, shim_content)
+        .map_err(|e| format!("Failed to write shim script: {}", e))?;
 
-        if !npx_path.exists() {
-            return Err(format!(
-                "NPX executable not found at expected path: {}",
-                npx_path.display()
-            ));
-        }
+    #[cfg(not(target_os = "windows"))]
+    Command::new("chmod")
+        .arg("+x")
+        .arg(shim_path)
+        .output()
+        .map_err(|e| format!("Failed to make shim executable: {}", e))?;
 
-        Ok((
-            node_path.to_string_lossy().to_string(),
-            npx_path.to_string_lossy().to_string(),
-        ))
-    }
+    Ok(())
 }
 
 pub fn ensure_npx_shim() -> Result<String, String> {
@@ -350,58 +445,61 @@ pub fn ensure_npx_shim() -> Result<String, String> {
         return Ok("/test/.local/share/staten/bin/npx-staten".to_string());
     }
 
-    let shim_path = get_npx_shim_path();
+    write_npx_shim(&crate::node::default_node_version(), &get_npx_shim_path())
+}
 
-    // Only create the shim if it doesn't exist
-    if shim_path.exists() {
+/// Installs Node.js `version` and writes the npx shim of the apps that run on it.
+pub fn ensure_versioned_npx_shim(version: &str) -> Result<String, String> {
+    let shim_path = get_versioned_npx_shim_path(version);
+    if is_test_mode() {
+        debug!("Using test mode path for npx shim");
+        return Ok(shim_path.to_string_lossy().to_string());
+    }
+
+    write_npx_shim(version, &shim_path)
+}
+
+fn write_npx_shim(version: &str, shim_path: &std::path::Path) -> Result<String, String> {
+    // Only create the shim if it doesn't exist, was written by an older Staten
+    // or runs another Node.js version than the one asked for
+    let (node, _) = crate::node::node_paths(version);
+    let runs_version = std::fs::read_to_string(shim_path)
+        .is_ok_and(|content| content.contains(&*node.to_string_lossy()));
+    if runs_version && is_shim_current(shim_path) && node.exists() {
         debug!("NPX shim already exists at {}", shim_path.display());
         return Ok(shim_path.to_string_lossy().to_string());
     }
 
-    info!("Creating NPX shim...");
+    info!("Creating NPX shim for Node.js {}...", version);
 
-    #[cfg(target_os = "macos")]
+    #[cfg(not(target_os = "windows"))]
     {
-        let (node_path, npx_path) = get_nvm_node_paths()?;
-
-        if let Some(parent) = shim_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create shim directory: {}", e))?;
-        }
+        let (node_path, npx_path) = get_node_paths(version)?;
 
         let shim_content = format!(
             r#"#!/bin/sh
 # NPX shim for Staten
+# {}
 
 NODE="{}"
 NPX="{}"
 
 export PATH="$(dirname "$NODE"):$PATH"
 
-exec "$NPX" "$@"
-"#,
-            node_path, npx_path
+{}"#,
+            shim_marker(),
+            node_path,
+            npx_path,
+            unix_shim_exec("NPX")
         );
 
-        std::fs::write(&shim_path, shim_content)
-            .map_err(|e| format!("Failed to write shim script: {}", e))?;
-
-        Command::new("chmod")
-            .arg("+x")
-            .arg(&shim_path)
-            .output()
-            .map_err(|e| format!("Failed to make shim executable: {}", e))?;
+        write_shim(shim_path, &shim_content)?;
     }
 
     #[cfg(target_os = "windows")]
     {
-        match get_nvm_node_paths() {
+        match get_node_paths(version) {
             Ok((node_path, npx_path)) => {
-                if let Some(parent) = shim_path.parent() {
-                    std::fs::create_dir_all(parent)
-                        .map_err(|e| format!("Failed to create shim directory: {}", e))?;
-                }
-
                 let node_dir = std::path::Path::new(&node_path)
                     .parent()
                     .ok_or("Could not determine parent directory of node.exe")?
@@ -410,21 +508,22 @@ exec "$NPX" "$@"
                 let shim_content = format!(
                     r#"@echo off
 :: NPX shim for Staten on Windows
+:: {}
 
 set NODE_PATH={}
 set NODE={}
 set NPX={}
 set PATH=%NODE_PATH%;%PATH%
 
-"%NPX%" %*
-"#,
-                    node_dir, node_path, npx_path
+{}"#,
+                    shim_marker(),
+                    node_dir,
+                    node_path,
+                    npx_path,
+                    windows_shim_exec("NPX")
                 );
 
-                std::fs::write(&shim_path, shim_content)
-                    .map_err(|e| format!("Failed to write shim script: {}", e))?;
-
-                info!("NPX shim created at {}", shim_path.display());
+                write_shim(shim_path, &shim_content)?;
             }
             Err(e) => {
                 error!("Failed to get node paths for shim creation: {}", e);
@@ -437,390 +536,77 @@ set PATH=%NODE_PATH%;%PATH%
     Ok(shim_path.to_string_lossy().to_string())
 }
 
-fn check_node_version() -> Result<String, String> {
-    if is_test_mode() {
-        return Ok(NODE_VERSION.to_string());
-    }
-
-    // If we already confirmed node is installed with correct version, return early
-    if NODE_INSTALLED.load(Ordering::SeqCst) {
-        debug!("Node.js already confirmed as installed");
-        return Ok(NODE_VERSION.to_string());
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        let shell_command = format!(
-            r#"
-          export NVM_DIR="$HOME/.nvm"
-          [ -s "$NVM_DIR/nvm.sh" ] && \. "$NVM_DIR/nvm.sh"
-          nvm list | grep -w "{}" || true
-      "#,
-            NODE_VERSION
-        );
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to check nvm node version: {}", e))?;
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        if output_str.contains(NODE_VERSION) {
-            info!("Node.js {} is already installed via nvm", NODE_VERSION);
-            NODE_INSTALLED.store(true, Ordering::SeqCst);
-            return Ok(NODE_VERSION.to_string());
-        }
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        if check_nvm_installed() {
-            let nvm_cmd = Command::new("nvm")
-                .arg("list")
-                .creation_flags(CREATE_NO_WINDOW)
-                .output()
-                .map_err(|e| format!("Failed to check nvm node version: {}", e))?;
-
-            let output_str = String::from_utf8_lossy(&nvm_cmd.stdout);
-            let version_no_v = NODE_VERSION.trim_start_matches('v');
-
-            if output_str.contains(NODE_VERSION) || output_str.contains(version_no_v) {
-                info!("Node.js {} is already installed via nvm", NODE_VERSION);
-                NODE_INSTALLED.store(true, Ordering::SeqCst);
-                return Ok(NODE_VERSION.to_string());
-            }
-
-            let nvm_root = std::env::var("NVM_HOME")
-                .ok()
-                .map(std::path::PathBuf::from)
-                .or_else(|| dirs::home_dir().map(|p| p.join("AppData").join("Roaming").join("nvm")))
-                .ok_or("Could not determine NVM_HOME")?;
-
-            let node_exists = nvm_root.join(version_no_v).join("node.exe").exists()
-                || nvm_root
-                    .join(format!("v{}", version_no_v))
-                    .join("node.exe")
-                    .exists();
-
-            if node_exists {
-                info!("Node.js {} binary found via nvm", NODE_VERSION);
-                NODE_INSTALLED.store(true, Ordering::SeqCst);
-                return Ok(NODE_VERSION.to_string());
-            }
-        }
-    }
-
-    #[cfg(target_os = "macos")]
-    let version_command = Command::new("node")
-        .arg("--version")
-        .output()
-        .map_err(|e| format!("Failed to check node version: {}", e))?;
-
-    #[cfg(target_os = "windows")]
-    let version_command = Command::new("node")
-        .arg("--version")
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-        .map_err(|e| format!("Failed to check node version: {}", e))?;
-
-    if version_command.status.success() {
-        let version = String::from_utf8_lossy(&version_command.stdout)
-            .trim()
-            .to_string();
-
-        if version == NODE_VERSION {
-            info!("Node.js {} is already installed system-wide", NODE_VERSION);
-            NODE_INSTALLED.store(true, Ordering::SeqCst);
-            return Ok(version);
-        }
-
-        info!("Found Node.js {} but {} is required", version, NODE_VERSION);
-        return Ok(version);
-    }
-
-    Err("Node.js not found".to_string())
-}
-
-fn check_nvm_version() -> Result<String, String> {
-    if is_test_mode() {
-        return Ok("0.40.1".to_string());
-    }
-
-    let shell_command = r#"
-        export NVM_DIR="$HOME/.nvm"
-        [ -s "$NVM_DIR/nvm.sh" ] && \. "$NVM_DIR/nvm.sh"
-        nvm --version
-    "#;
-
-    let output = Command::new("bash")
-        .arg("-c")
-        .arg(shell_command)
-        .output()
-        .map_err(|e| format!("Failed to check nvm version: {}", e))?;
-
-    if !output.status.success() {
-        return Err("Failed to get nvm version".to_string());
-    }
-
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-}
-
-fn install_node() -> Result<(), String> {
+pub fn ensure_uvx_shim() -> Result<String, String> {
     if is_test_mode() {
-        return Ok(());
-    }
-
-    match check_node_version() {
-        Ok(version) if version == NODE_VERSION => {
-            info!(
-                "Node.js {} is already installed, skipping installation",
-                NODE_VERSION
-            );
-            NODE_INSTALLED.store(true, Ordering::Relaxed);
-            return Ok(());
-        }
-        _ => {}
-    }
-
-    info!("Installing Node.js {}", NODE_VERSION);
-
-    if !check_nvm_installed() {
-        return Err("nvm is required to install Node.js".to_string());
+        debug!("Using test mode path for uvx shim");
+        return Ok("/test/.local/share/staten/bin/uvx-staten".to_string());
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        let shell_command = format!(
-            r#"
-          export NVM_DIR="$HOME/.nvm"
-          [ -s "$NVM_DIR/nvm.sh" ] && \. "$NVM_DIR/nvm.sh"
-          nvm install {} --no-progress
-      "#,
-            NODE_VERSION
-        );
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to run node installation: {}", e))?;
-
-        if !output.status.success() {
-            return Err(format!(
-                "Node installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        let version_without_v = NODE_VERSION.trim_start_matches('v');
-
-        let output = Command::new("nvm")
-            .arg("install")
-            .arg(version_without_v)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to run node installation: {}", e))?;
-
-        if !output.status.success() {
-            return Err(format!(
-                "Node installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-
-        let use_output = Command::new("nvm")
-            .arg("use")
-            .arg(version_without_v)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to set node version: {}", e))?;
-
-        if !use_output.status.success() {
-            return Err(format!(
-                "Failed to set node version: {}",
-                String::from_utf8_lossy(&use_output.stderr)
-            ));
-        }
-
-        let nvm_root = std::env::var("NVM_HOME")
-            .ok()
-            .map(std::path::PathBuf::from)
-            .or_else(|| dirs::home_dir().map(|p| p.join("AppData").join("Roaming").join("nvm")))
-            .unwrap_or_default();
-
-        let node_exists = nvm_root.join(version_without_v).join("node.exe").exists()
-            || nvm_root
-                .join(format!("v{}", version_without_v))
-                .join("node.exe")
-                .exists();
-
-        if !node_exists {
-            return Err(format!(
-                "Node.js {} installation verification failed. Binary not found at expected locations.",
-                NODE_VERSION
-            ));
-        }
-    }
-
-    NODE_INSTALLED.store(true, Ordering::Relaxed);
-    info!("Node.js {} installed successfully", NODE_VERSION);
-    Ok(())
+    write_uvx_shim(&get_uvx_shim_path(), None)
 }
 
-fn check_nvm_installed() -> bool {
-    if is_test_mode() {
-        return true;
-    }
-
-    // If we've already confirmed nvm is installed, return early
-    if NVM_INSTALLED.load(Ordering::Relaxed) {
-        debug!("NVM already confirmed as installed");
-        return true;
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        let nvm_dir = dirs::home_dir()
-            .map(|path| path.join(".nvm"))
-            .filter(|path| path.exists());
-
-        if nvm_dir.is_none() {
-            info!("NVM directory not found");
-            return false;
-        }
-
-        match check_nvm_version() {
-            Ok(version) => {
-                info!("NVM version {} is installed", version);
-                NVM_INSTALLED.store(true, Ordering::Relaxed);
-                true
-            }
-            Err(_) => {
-                info!("NVM directory exists but nvm command failed");
-                false
-            }
-        }
+/// Writes the uvx shim of apps with a `requires-python`. Only these are
+/// pointed at Staten's interpreters, the others keep the user's uv setup.
+pub fn ensure_uvx_python_shim() -> Result<String, String> {
+    let shim_path = get_uvx_python_shim_path();
+    if is_test_placeholder(&shim_path) {
+        debug!("Using test mode path for uvx shim");
+        return Ok(shim_path.to_string_lossy().to_string());
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        let nvm_home = std::env::var("NVM_HOME")
-            .ok()
-            .map(std::path::PathBuf::from)
-            .or_else(|| dirs::home_dir().map(|p| p.join("AppData").join("Roaming").join("nvm")));
-
-        if let Some(nvm_path) = nvm_home {
-            if nvm_path.exists() {
-                let nvm_exe = nvm_path.join("nvm.exe");
-                if nvm_exe.exists() {
-                    info!("NVM for Windows found at {}", nvm_path.display());
-                    NVM_INSTALLED.store(true, Ordering::Relaxed);
-                    return true;
-                }
-            }
-        }
-
-        info!("NVM for Windows not found");
-        false
-    }
+    write_uvx_shim(&shim_path, Some(&crate::python::python_root()))
 }
 
-fn install_nvm() -> Result<(), String> {
-    if is_test_mode() {
-        return Ok(());
-    }
-
-    if check_nvm_installed() {
-        info!("nvm is already installed, skipping installation");
-        return Ok(());
+fn write_uvx_shim(
+    shim_path: &std::path::Path,
+    python_dir: Option<&std::path::Path>,
+) -> Result<String, String> {
+    if shim_path.exists() && is_shim_current(shim_path) {
+        debug!("UVX shim already exists at {}", shim_path.display());
+        return Ok(shim_path.to_string_lossy().to_string());
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        info!("Installing nvm...");
-
-        let shell_command = r#"
-          curl -o- https://raw.githubusercontent.com/nvm-sh/nvm/v0.40.1/install.sh | bash
-      "#;
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to install nvm: {}", e))?;
+    info!("Creating UVX shim...");
+    let uvx_path = get_uvx_path()?;
 
-        if !output.status.success() {
-            return Err(format!(
-                "nvm installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
+    #[cfg(not(target_os = "windows"))]
+    let shim_content = format!(
+        r#"#!/bin/sh
+# UVX shim for Staten
+# {}
 
-        NVM_INSTALLED.store(true, Ordering::Relaxed);
-        info!("nvm installed successfully");
-        Ok(())
-    }
+UVX="{}"
+{}
+{}"#,
+        shim_marker(),
+        uvx_path,
+        python_dir
+            .map(|dir| format!("export UV_PYTHON_INSTALL_DIR=\"{}\"\n", dir.display()))
+            .unwrap_or_default(),
+        unix_shim_exec("UVX")
+    );
 
     #[cfg(target_os = "windows")]
-    {
-        info!("Installing nvm for Windows...");
-
-        let temp_dir = std::env::temp_dir().join("staten_nvm_install");
-        let _ = std::fs::create_dir_all(&temp_dir);
-        let installer_path = temp_dir.join("nvm-setup.exe");
-
-        let nvm_installer_url =
-            "https://github.com/coreybutler/nvm-windows/releases/download/1.1.11/nvm-setup.exe";
-
-        let download_cmd = format!(
-            "Invoke-WebRequest -Uri '{}' -OutFile '{}'",
-            nvm_installer_url,
-            installer_path.to_string_lossy()
-        );
-
-        let dl_output = Command::new("powershell")
-            .arg("-Command")
-            .arg(&download_cmd)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to download nvm installer: {}", e))?;
-
-        if !dl_output.status.success() {
-            return Err(format!(
-                "Failed to download nvm installer: {}",
-                String::from_utf8_lossy(&dl_output.stderr)
-            ));
-        }
-
-        info!("Starting NVM for Windows installer. Please follow the on-screen instructions.");
-        let installer_output = Command::new(&installer_path)
-            .arg("/SILENT")
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to run nvm installer: {}", e))?;
-
-        if !installer_output.status.success() {
-            return Err(format!(
-                "NVM installation failed: {}",
-                String::from_utf8_lossy(&installer_output.stderr)
-            ));
-        }
-
-        let _ = std::fs::remove_file(&installer_path);
-        let _ = std::fs::remove_dir(&temp_dir);
-
-        if check_nvm_installed() {
-            NVM_INSTALLED.store(true, Ordering::Relaxed);
-            info!("nvm for Windows installed successfully");
-            Ok(())
-        } else {
-            Err("nvm for Windows installation completed but verification failed".to_string())
-        }
-    }
+    let shim_content = format!(
+        r#"@echo off
+:: UVX shim for Staten on Windows
+:: {}
+
+set UVX={}
+{}
+{}"#,
+        shim_marker(),
+        uvx_path,
+        python_dir
+            .map(|dir| format!("set UV_PYTHON_INSTALL_DIR={}\n", dir.display()))
+            .unwrap_or_default(),
+        windows_shim_exec("UVX")
+    );
+
+    write_shim(shim_path, &shim_content)?;
+
+    info!("UVX shim created at {}", shim_path.display());
+    Ok(shim_path.to_string_lossy().to_string())
 }
 
 fn check_uv_installed() -> bool {
@@ -893,118 +679,12 @@ fn install_uv() -> Result<(), String> {
     }
 
     info!("Installing uv...");
-
-    #[cfg(target_os = "macos")]
-    {
-        let shell_command = r#"
-          curl -LsSf https://astral.sh/uv/install.sh | sh
-      "#;
-
-        let output = Command::new("bash")
-            .arg("-c")
-            .arg(shell_command)
-            .output()
-            .map_err(|e| format!("Failed to install uv: {}", e))?;
-
-        if !output.status.success() {
-            return Err(format!(
-                "uv installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-
-        let source_cargo_env = r#"
-          source "$HOME/.cargo/env"
-      "#;
-
-        let _ = Command::new("bash")
-            .arg("-c")
-            .arg(source_cargo_env)
-            .output();
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        let ps_command = r#"
-          irm -Uri "https://astral.sh/uv/install.ps1" | iex
-      "#;
-
-        let output = Command::new("powershell")
-            .arg("-ExecutionPolicy")
-            .arg("Bypass")
-            .arg("-Command")
-            .arg(ps_command)
-            .creation_flags(CREATE_NO_WINDOW)
-            .output()
-            .map_err(|e| format!("Failed to install uv: {}", e))?;
-
-        if !output.status.success() {
-            return Err(format!(
-                "uv installation failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
-    }
-
-    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-
-    #[cfg(target_os = "macos")]
-    let uv_path = home_dir.join(".cargo/bin/uv");
-
-    #[cfg(target_os = "windows")]
-    let uv_path = home_dir.join(".cargo").join("bin").join("uv.exe");
-
-    #[cfg(target_os = "macos")]
-    let uvx_path = home_dir.join(".cargo/bin/uvx");
-
-    #[cfg(target_os = "windows")]
-    let uvx_path = home_dir.join(".cargo").join("bin").join("uvx.exe");
-
-    if uv_path.exists() {
-        info!("uv found at {}", uv_path.display());
-        if !uvx_path.exists() {
-            info!(
-                "uvx not found at {} after uv installation, this is unexpected",
-                uvx_path.display()
-            );
-        } else {
-            info!("uvx found at {}", uvx_path.display());
-        }
-    } else {
-        #[cfg(target_os = "macos")]
-        let which_command = "which";
-
-        #[cfg(target_os = "windows")]
-        let which_command = "where";
-
-        #[cfg(target_os = "macos")]
-        let which_output = Command::new(which_command).arg("uv").output();
-
-        #[cfg(target_os = "windows")]
-        let which_output = Command::new(which_command)
-            .arg("uv.exe")
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-
-        match which_output {
-            Ok(output) if output.status.success() => {
-                let path = String::from_utf8_lossy(&output.stdout)
-                    .lines()
-                    .next()
-                    .unwrap_or("")
-                    .trim()
-                    .to_string();
-                if !path.is_empty() {
-                    info!("uv installed at {} (not in the expected location)", path);
-                } else {
-                    info!("uv not found in PATH after installation");
-                }
-            }
-            _ => {
-                info!("uv not found in PATH after installation");
-            }
-        }
-    }
+    let (uv_path, uvx_path) = crate::uv::install_uv()?;
+    info!(
+        "uv installed at {}, uvx at {}",
+        uv_path.display(),
+        uvx_path.display()
+    );
 
     UV_INSTALLED.store(true, Ordering::Relaxed);
     info!("uv installation completed");
@@ -1016,27 +696,9 @@ fn ensure_node_environment() -> Result<String, String> {
         return Ok("Node environment is ready".to_string());
     }
 
-    if !check_nvm_installed() {
-        install_nvm()?;
-    }
-
-    match check_node_version() {
-        Ok(version) => {
-            if version != NODE_VERSION {
-                info!(
-                    "Node.js {} found, but {} required. Installing...",
-                    version, NODE_VERSION
-                );
-                install_node()?;
-            } else {
-                debug!("Node.js {} is already installed", NODE_VERSION);
-                NODE_INSTALLED.store(true, Ordering::Relaxed);
-            }
-        }
-        Err(_) => {
-            info!("Node.js not found. Installing...");
-            install_node()?;
-        }
+    if !NODE_INSTALLED.load(Ordering::SeqCst) {
+        get_node_paths(&crate::node::default_node_version())?;
+        NODE_INSTALLED.store(true, Ordering::SeqCst);
     }
 
     ensure_npx_shim()?;
@@ -1093,6 +755,7 @@ pub fn ensure_environment_sync() -> Result<String, String> {
     if !has_critical_error {
         ENVIRONMENT_SETUP_COMPLETED.store(true, Ordering::SeqCst);
         info!("Synchronous environment setup completed successfully");
+        crate::onboarding::record_step(crate::onboarding::OnboardingStep::EnvironmentReady);
         Ok("Environment setup completed".to_string())
     } else {
         info!("Synchronous environment setup completed with errors");
@@ -1150,6 +813,7 @@ pub async fn ensure_environment() -> Result<String, String> {
         } else {
             ENVIRONMENT_SETUP_COMPLETED.store(true, Ordering::SeqCst);
             info!("Environment setup completed successfully");
+            crate::onboarding::record_step(crate::onboarding::OnboardingStep::EnvironmentReady);
             Ok("Environment setup completed".to_string())
         }
     })
@@ -1176,3 +840,132 @@ pub fn create_windowless_command(program: &str) -> Command {
 pub fn create_windowless_command(program: &str) -> Command {
     Command::new(program)
 }
+
+/// A container engine that runs the images of `docker` apps. Podman accepts
+/// the same `run` arguments as Docker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContainerEngine {
+    /// "docker" or "podman"
+    pub name: String,
+    pub path: String,
+}
+
+static TEST_CONTAINER_ENGINE: Lazy<Mutex<Option<ContainerEngine>>> = Lazy::new(|| {
+    Mutex::new(Some(ContainerEngine {
+        name: "docker".to_string(),
+        path: "/test/bin/docker".to_string(),
+    }))
+});
+
+/// The container engine tests see, `None` for a machine without one.
+#[cfg(feature = "test-utils")]
+pub fn set_test_container_engine(engine: Option<ContainerEngine>) {
+    *TEST_CONTAINER_ENGINE.lock().unwrap() = engine;
+}
+
+fn find_container_binary(name: &str) -> Option<String> {
+    #[cfg(target_os = "macos")]
+    {
+        // Apps started from Finder do not get the shell's PATH
+        let possible_paths = [
+            format!("/usr/local/bin/{}", name),
+            format!("/opt/homebrew/bin/{}", name),
+            format!("/opt/podman/bin/{}", name),
+            format!("/Applications/Docker.app/Contents/Resources/bin/{}", name),
+        ];
+        for path in &possible_paths {
+            if std::path::Path::new(path).exists() {
+                return Some(path.clone());
+            }
+        }
+
+        match Command::new("which").arg(name).output() {
+            Ok(output) if output.status.success() => {
+                let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                (!path.is_empty()).then_some(path)
+            }
+            _ => None,
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let possible_paths = [
+            format!(
+                "C:\\Program Files\\Docker\\Docker\\resources\\bin\\{}.exe",
+                name
+            ),
+            format!("C:\\Program Files\\RedHat\\Podman\\{}.exe", name),
+        ];
+        for path in &possible_paths {
+            if std::path::Path::new(path).exists() {
+                return Some(path.clone());
+            }
+        }
+
+        match Command::new("where")
+            .arg(format!("{}.exe", name))
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+        {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty()),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = name;
+        None
+    }
+}
+
+/// Finds Docker, or Podman when Docker is not installed.
+pub fn find_container_engine() -> Option<ContainerEngine> {
+    if is_test_mode() {
+        return TEST_CONTAINER_ENGINE.lock().unwrap().clone();
+    }
+
+    ["docker", "podman"].iter().find_map(|name| {
+        let path = find_container_binary(name)?;
+        debug!("Found {} at {}", name, path);
+        Some(ContainerEngine {
+            name: name.to_string(),
+            path,
+        })
+    })
+}
+
+/// Whether the engine's daemon or machine is up, which `run` needs.
+pub fn is_container_engine_running(engine: &ContainerEngine) -> bool {
+    if is_test_mode() {
+        return true;
+    }
+
+    create_windowless_command(&engine.path)
+        .arg("info")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn get_container_engine_status() -> serde_json::Value {
+    match find_container_engine() {
+        Some(engine) => serde_json::json!({
+            "available": true,
+            "running": is_container_engine_running(&engine),
+            "engine": engine.name,
+            "path": engine.path
+        }),
+        None => serde_json::json!({
+            "available": false,
+            "running": false,
+            "message": "No container engine found. Install Docker Desktop or Podman to use container apps."
+        }),
+    }
+}
@@ -1,14 +1,28 @@
+use crate::config_format::{ConfigFormat, Json};
 use serde_json::{json, Value};
 use std::fs;
 use std::path::PathBuf;
 
 pub fn ensure_config_file(config_path: &PathBuf) -> Result<(), String> {
-    if !config_path.exists() {
-        let initial_config = json!({
+    ensure_config_file_in(
+        config_path,
+        &Json,
+        &json!({
             "mcpServers": {}
-        });
+        }),
+    )
+}
 
-        let config_str = serde_json::to_string_pretty(&initial_config)
+/// Like `ensure_config_file`, for clients whose config has another format or
+/// shape.
+pub fn ensure_config_file_in(
+    config_path: &PathBuf,
+    format: &dyn ConfigFormat,
+    initial_config: &Value,
+) -> Result<(), String> {
+    if !config_path.exists() {
+        let config_str = format
+            .render(initial_config, None)
             .map_err(|e| format!("Failed to create initial config: {}", e))?;
 
         if let Some(parent) = config_path.parent() {
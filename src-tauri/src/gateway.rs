@@ -0,0 +1,733 @@
+use crate::app::{self, get_config, save_config};
+use crate::clients::ClientType;
+use crate::remote::{self, RemoteServer};
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Key of the single gateway entry written to each client's config.
+pub const GATEWAY_MCP_KEY: &str = "staten-gateway";
+
+/// Separator between the app key and the tool name in namespaced tool names.
+pub const TOOL_SEPARATOR: &str = "__";
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+const CHILD_INIT_TIMEOUT: Duration = Duration::from_secs(60);
+const CHILD_LIST_TIMEOUT: Duration = Duration::from_secs(30);
+const CHILD_CALL_TIMEOUT: Duration = Duration::from_secs(600);
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+lazy_static! {
+    static ref TEST_GATEWAY_CONFIG_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+    // Requests, responses and notifications all go through one stdout
+    static ref STDOUT_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GatewayApp {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: Map<String, Value>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Apps served by the gateway, keyed by mcpKey. The gateway process polls this
+/// file, so enabling or disabling an app takes effect without a client restart.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GatewayConfig {
+    #[serde(default)]
+    pub apps: BTreeMap<String, GatewayApp>,
+}
+
+pub fn set_test_gateway_config_path(path: Option<PathBuf>) {
+    let mut test_path = TEST_GATEWAY_CONFIG_PATH.lock().unwrap();
+    *test_path = path;
+}
+
+pub fn get_gateway_config_path() -> PathBuf {
+    if let Some(path) = TEST_GATEWAY_CONFIG_PATH.lock().unwrap().clone() {
+        return path;
+    }
+
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".staten")
+        .join("gateway.json")
+}
+
+pub fn load_gateway_config() -> Result<GatewayConfig, String> {
+    let path = get_gateway_config_path();
+    if !path.exists() {
+        return Ok(GatewayConfig::default());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read gateway config: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse gateway config: {}", e))
+}
+
+pub fn save_gateway_config(config: &GatewayConfig) -> Result<(), String> {
+    let path = get_gateway_config_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create gateway config directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize gateway config: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write gateway config: {}", e))
+}
+
+// Tool names must match `^[a-zA-Z0-9_-]{1,64}$` in most clients
+fn sanitize_key(app_key: &str) -> String {
+    app_key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The tool name prefix of each app. App keys are reduced to the alphabet
+/// clients accept, and keys that end up the same get a numeric suffix, so
+/// every app keeps a prefix of its own. Keys that need no change come first
+/// and keep their name.
+pub fn tool_prefixes<'a, I>(app_keys: I) -> BTreeMap<String, String>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    let mut keys: Vec<&String> = app_keys.into_iter().collect();
+    keys.sort_by_key(|key| (sanitize_key(key) != **key, *key));
+
+    let mut taken = HashSet::new();
+    let mut prefixes = BTreeMap::new();
+    for key in keys {
+        let base = sanitize_key(key);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while !taken.insert(name.clone()) {
+            name = format!("{}{}", base, suffix);
+            suffix += 1;
+        }
+        prefixes.insert(key.clone(), format!("{}{}", name, TOOL_SEPARATOR));
+    }
+    prefixes
+}
+
+/// Finds which app a namespaced tool belongs to. The longest matching prefix
+/// wins, so app keys that themselves contain the separator still route right.
+pub fn resolve_tool<'a, I>(name: &str, app_keys: I) -> Option<(String, String)>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    tool_prefixes(app_keys)
+        .into_iter()
+        .filter_map(|(key, prefix)| {
+            name.strip_prefix(&prefix)
+                .filter(|tool| !tool.is_empty())
+                .map(|tool| (key, tool.to_string(), prefix.len()))
+        })
+        .max_by_key(|(_, _, prefix_len)| *prefix_len)
+        .map(|(key, tool, _)| (key, tool))
+}
+
+fn write_message(message: &Value) {
+    let _guard = STDOUT_LOCK.lock().unwrap();
+    let mut stdout = std::io::stdout();
+    let _ = writeln!(stdout, "{}", message);
+    let _ = stdout.flush();
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message }
+    })
+}
+
+struct ChildServer {
+    key: String,
+    spec: GatewayApp,
+    process: Mutex<Child>,
+    stdin: Mutex<ChildStdin>,
+    pending: Arc<Mutex<HashMap<u64, Sender<Value>>>>,
+    next_id: AtomicU64,
+}
+
+impl ChildServer {
+    fn start(key: &str, spec: &GatewayApp) -> Result<Arc<ChildServer>, String> {
+        info!(
+            "Starting gateway app {}: {} {:?}",
+            key, spec.command, spec.args
+        );
+
+        let mut command = crate::environment::create_windowless_command(&spec.command);
+        command
+            .args(&spec.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        for (name, value) in &spec.env {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            command.env(name, value);
+        }
+
+        let mut process = command
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", key, e))?;
+        let stdin = process.stdin.take().ok_or("Failed to open child stdin")?;
+        let stdout = process.stdout.take().ok_or("Failed to open child stdout")?;
+
+        let child = Arc::new(ChildServer {
+            key: key.to_string(),
+            spec: spec.clone(),
+            process: Mutex::new(process),
+            stdin: Mutex::new(stdin),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+        });
+
+        let reader_child = Arc::clone(&child);
+        std::thread::spawn(move || reader_child.read_loop(BufReader::new(stdout)));
+
+        let initialized = child
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "staten-gateway", "version": env!("CARGO_PKG_VERSION") }
+                }),
+                CHILD_INIT_TIMEOUT,
+            )
+            .and_then(|_| {
+                child.send(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            });
+        if let Err(e) = initialized {
+            child.stop();
+            return Err(e);
+        }
+
+        info!("Gateway app {} initialized", key);
+        Ok(child)
+    }
+
+    fn read_loop<R: BufRead>(&self, reader: R) {
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let message: Value = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(e) => {
+                    debug!("Ignoring non-JSON output from {}: {}", self.key, e);
+                    continue;
+                }
+            };
+
+            let is_response = message.get("result").is_some() || message.get("error").is_some();
+            match (message.get("id"), message.get("method")) {
+                (Some(id), None) if is_response => {
+                    if let Some(sender) = id
+                        .as_u64()
+                        .and_then(|id| self.pending.lock().unwrap().remove(&id))
+                    {
+                        let _ = sender.send(message);
+                    }
+                }
+                (Some(id), Some(method)) => {
+                    // The gateway offers no client capabilities to its children
+                    debug!("Rejecting request {} from {}", method, self.key);
+                    let _ = self.send(&error_response(id, -32601, "Method not found"));
+                }
+                (None, Some(method)) if method == "notifications/tools/list_changed" => {
+                    write_message(&json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/tools/list_changed"
+                    }));
+                }
+                _ => debug!("Ignoring message from {}: {}", self.key, line),
+            }
+        }
+
+        warn!("Gateway app {} closed its output", self.key);
+        self.pending.lock().unwrap().clear();
+    }
+
+    fn send(&self, message: &Value) -> Result<(), String> {
+        let mut stdin = self.stdin.lock().unwrap();
+        writeln!(stdin, "{}", message)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Failed to write to {}: {}", self.key, e))
+    }
+
+    fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = mpsc::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        }))?;
+
+        let response = receiver.recv_timeout(timeout).map_err(|_| {
+            self.pending.lock().unwrap().remove(&id);
+            format!("{} did not answer {} in time", self.key, method)
+        })?;
+
+        if let Some(error) = response.get("error") {
+            return Err(error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Unknown error")
+                .to_string());
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    fn list_tools(&self) -> Result<Vec<Value>, String> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<Value> = None;
+
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params, CHILD_LIST_TIMEOUT)?;
+
+            if let Some(page) = result.get("tools").and_then(|t| t.as_array()) {
+                tools.extend(page.iter().cloned());
+            }
+
+            cursor = result.get("nextCursor").filter(|c| !c.is_null()).cloned();
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    fn has_exited(&self) -> bool {
+        !matches!(self.process.lock().unwrap().try_wait(), Ok(None))
+    }
+
+    fn stop(&self) {
+        info!("Stopping gateway app {}", self.key);
+        let mut process = self.process.lock().unwrap();
+        let _ = process.kill();
+        let _ = process.wait();
+    }
+}
+
+struct Gateway {
+    children: Mutex<BTreeMap<String, Arc<ChildServer>>>,
+    // One sync at a time, so an app is never started twice
+    syncing: Mutex<()>,
+    // Set once the client has sent `initialize`, before which the gateway
+    // must not send it notifications
+    initialized: AtomicBool,
+}
+
+impl Gateway {
+    /// Starts newly enabled apps, stops disabled or removed ones and restarts
+    /// apps whose command changed or that exited. Returns whether the set of
+    /// apps changed.
+    fn sync(&self, config: &GatewayConfig) -> bool {
+        let _syncing = self.syncing.lock().unwrap();
+
+        let (stale, to_start) = {
+            let mut children = self.children.lock().unwrap();
+            let stale_keys: Vec<String> = children
+                .iter()
+                .filter(|(key, child)| {
+                    child.has_exited()
+                        || !config
+                            .apps
+                            .get(*key)
+                            .is_some_and(|spec| spec.enabled && *spec == child.spec)
+                })
+                .map(|(key, _)| key.clone())
+                .collect();
+            let stale: Vec<Arc<ChildServer>> = stale_keys
+                .iter()
+                .filter_map(|key| children.remove(key))
+                .collect();
+            let to_start: Vec<(&String, &GatewayApp)> = config
+                .apps
+                .iter()
+                .filter(|(key, spec)| spec.enabled && !children.contains_key(*key))
+                .collect();
+            (stale, to_start)
+        };
+
+        let mut changed = !stale.is_empty();
+        for child in stale {
+            child.stop();
+        }
+
+        // Each app can take up to CHILD_INIT_TIMEOUT to initialize, so they
+        // start side by side and join the running ones as they get ready
+        let started = std::thread::scope(|scope| {
+            let starts: Vec<_> = to_start
+                .into_iter()
+                .map(|(key, spec)| {
+                    scope.spawn(move || match ChildServer::start(key, spec) {
+                        Ok(child) => {
+                            self.children.lock().unwrap().insert(key.clone(), child);
+                            true
+                        }
+                        Err(e) => {
+                            error!("Failed to start gateway app {}: {}", key, e);
+                            false
+                        }
+                    })
+                })
+                .collect();
+            starts
+                .into_iter()
+                .map(|start| start.join().unwrap_or(false))
+                .filter(|started| *started)
+                .count()
+        });
+        changed |= started > 0;
+
+        changed
+    }
+
+    fn notify_tools_changed(&self) {
+        if self.initialized.load(Ordering::SeqCst) {
+            write_message(&json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/tools/list_changed"
+            }));
+        }
+    }
+
+    fn has_exited_children(&self) -> bool {
+        self.children
+            .lock()
+            .unwrap()
+            .values()
+            .any(|child| child.has_exited())
+    }
+
+    fn child(&self, key: &str) -> Option<Arc<ChildServer>> {
+        self.children.lock().unwrap().get(key).cloned()
+    }
+
+    fn list_tools(&self) -> Vec<Value> {
+        let children: Vec<Arc<ChildServer>> =
+            self.children.lock().unwrap().values().cloned().collect();
+        let prefixes = tool_prefixes(children.iter().map(|child| &child.key));
+
+        let mut tools = Vec::new();
+        for child in children {
+            match child.list_tools() {
+                Ok(child_tools) => {
+                    for mut tool in child_tools {
+                        if let Some(name) = tool.get("name").and_then(|n| n.as_str()) {
+                            tool["name"] = json!(format!("{}{}", prefixes[&child.key], name));
+                            tools.push(tool);
+                        }
+                    }
+                }
+                Err(e) => error!("Failed to list tools for {}: {}", child.key, e),
+            }
+        }
+        tools
+    }
+
+    fn call_tool(&self, params: &Value) -> Result<Value, String> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or("Tool name is missing")?;
+
+        let keys: Vec<String> = self.children.lock().unwrap().keys().cloned().collect();
+        let (key, tool) =
+            resolve_tool(name, keys.iter()).ok_or_else(|| format!("Unknown tool: {}", name))?;
+        let child = self
+            .child(&key)
+            .ok_or_else(|| format!("App {} is not running", key))?;
+
+        let mut child_params = params.clone();
+        child_params["name"] = json!(tool);
+        child.request("tools/call", child_params, CHILD_CALL_TIMEOUT)
+    }
+
+    fn handle(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id")?;
+        let method = message.get("method").and_then(|m| m.as_str()).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(json!({}));
+
+        let result = match method {
+            "initialize" => {
+                self.initialized.store(true, Ordering::SeqCst);
+                Ok(json!({
+                "protocolVersion": params
+                    .get("protocolVersion")
+                    .cloned()
+                    .unwrap_or(json!(PROTOCOL_VERSION)),
+                "capabilities": { "tools": { "listChanged": true } },
+                "serverInfo": { "name": "staten", "version": env!("CARGO_PKG_VERSION") }
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.list_tools() })),
+            "tools/call" => self.call_tool(&params),
+            _ => return Some(error_response(id, -32601, "Method not found")),
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => error_response(id, -32603, &e),
+        })
+    }
+
+    fn stop_all(&self) {
+        let mut children = self.children.lock().unwrap();
+        for (_, child) in std::mem::take(&mut *children) {
+            child.stop();
+        }
+    }
+}
+
+fn config_modified() -> Option<SystemTime> {
+    fs::metadata(get_gateway_config_path())
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+fn reload_config(gateway: &Gateway) {
+    match load_gateway_config() {
+        Ok(config) => {
+            if gateway.sync(&config) {
+                gateway.notify_tools_changed();
+            }
+        }
+        Err(e) => error!("Failed to load gateway config: {}", e),
+    }
+}
+
+// Starts the enabled apps, then keeps them in line with the config and
+// restarts the ones that exit
+fn watch_config(gateway: Arc<Gateway>) {
+    let mut last_modified = config_modified();
+    reload_config(&gateway);
+    loop {
+        std::thread::sleep(CONFIG_POLL_INTERVAL);
+
+        let modified = config_modified();
+        if modified != last_modified {
+            last_modified = modified;
+            info!("Gateway config changed, reloading apps");
+            reload_config(&gateway);
+        } else if gateway.has_exited_children() {
+            warn!("A gateway app exited, restarting it");
+            reload_config(&gateway);
+        }
+    }
+}
+
+/// Runs Staten as a stdio MCP server that fronts every enabled gateway app.
+/// Returns the process exit code.
+pub fn run_gateway() -> i32 {
+    if let Err(e) = crate::setup_logger("staten-gateway.log") {
+        eprintln!("Failed to initialize logger: {}", e);
+    }
+    info!("Starting Staten MCP gateway");
+
+    let gateway = Arc::new(Gateway {
+        children: Mutex::new(BTreeMap::new()),
+        syncing: Mutex::new(()),
+        initialized: AtomicBool::new(false),
+    });
+
+    // Apps start in the background, the client's `initialize` is answered
+    // right away and `tools/list` reports whatever is running by then
+    let watcher = Arc::clone(&gateway);
+    std::thread::spawn(move || watch_config(watcher));
+
+    let stdin = std::io::stdin();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                error!("Failed to read from stdin: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Invalid JSON-RPC message: {}", e);
+                write_message(&error_response(&Value::Null, -32700, "Parse error"));
+                continue;
+            }
+        };
+
+        // Tool calls can take minutes, so every request gets its own thread
+        let handler = Arc::clone(&gateway);
+        std::thread::spawn(move || {
+            if let Some(response) = handler.handle(&message) {
+                write_message(&response);
+            }
+        });
+    }
+
+    info!("Client disconnected, stopping gateway");
+    gateway.stop_all();
+    0
+}
+
+#[tauri::command]
+pub fn set_gateway_app(
+    app_name: &str,
+    enabled: bool,
+    env_vars: Option<Value>,
+) -> Result<String, String> {
+    info!("Setting gateway app {} enabled={}", app_name, enabled);
+
+    let configs = app::get_app_configs()?;
+    let (_, config) = configs
+        .iter()
+        .find(|(name, _)| name == app_name)
+        .ok_or_else(|| format!("No configuration available for '{}'", app_name))?;
+
+    let mut gateway_config = load_gateway_config()?;
+    let existing_env = gateway_config
+        .apps
+        .get(&config.mcp_key)
+        .map(|app| Value::Object(app.env.clone()))
+        .unwrap_or_else(|| json!({}));
+
+    let env = app::merge_env(existing_env, env_vars);
+    let entry = app::build_server_entry(config, env.clone());
+    let mut env = env.as_object().cloned().unwrap_or_default();
+    // The gateway starts processes, so remote apps go through the proxy
+    let (command, args) = match RemoteServer::from_entry(&entry) {
+        Some(remote) => {
+            env.extend(remote::proxy_env(&remote));
+            (remote::proxy_command(), remote::proxy_args(&remote))
+        }
+        None => (
+            config.command.clone(),
+            entry["args"]
+                .as_array()
+                .map(|args| {
+                    args.iter()
+                        .filter_map(|a| a.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+    };
+    let spec = GatewayApp {
+        command,
+        args,
+        env,
+        enabled,
+    };
+
+    gateway_config.apps.insert(config.mcp_key.clone(), spec);
+    save_gateway_config(&gateway_config)?;
+
+    Ok(format!(
+        "{} {} in the Staten gateway",
+        app_name,
+        if enabled { "enabled" } else { "disabled" }
+    ))
+}
+
+#[tauri::command]
+pub fn get_gateway_apps() -> Result<Value, String> {
+    let config = load_gateway_config()?;
+    serde_json::to_value(&config.apps).map_err(|e| format!("Failed to serialize apps: {}", e))
+}
+
+#[tauri::command]
+pub fn install_staten_gateway(client: &str) -> Result<String, String> {
+    info!("Installing Staten gateway for client: {}", client);
+
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+
+    let exe_path = std::env::current_exe()
+        .map_err(|e| format!("Failed to determine Staten executable: {}", e))?;
+
+    let mut config_json = get_config(&client_type)?;
+    let mcp_servers = config_json
+        .get_mut("mcpServers")
+        .and_then(|v| v.as_object_mut())
+        .ok_or("Failed to find mcpServers in config")?;
+
+    mcp_servers.insert(
+        GATEWAY_MCP_KEY.to_string(),
+        json!({
+            "command": exe_path.to_string_lossy(),
+            "args": ["gateway"]
+        }),
+    );
+    save_config(&config_json, &client_type)?;
+
+    info!("Installed Staten gateway for client: {}", client);
+    Ok("Added Staten gateway configuration".to_string())
+}
+
+#[tauri::command]
+pub fn uninstall_staten_gateway(client: &str) -> Result<String, String> {
+    info!("Uninstalling Staten gateway for client: {}", client);
+
+    let client_type =
+        ClientType::from_str(client).ok_or_else(|| format!("Invalid client: {}", client))?;
+
+    let mut config_json = get_config(&client_type)?;
+    let mcp_servers = config_json
+        .get_mut("mcpServers")
+        .and_then(|v| v.as_object_mut())
+        .ok_or("Failed to find mcpServers in config")?;
+
+    if mcp_servers.remove(GATEWAY_MCP_KEY).is_some() {
+        save_config(&config_json, &client_type)?;
+        Ok("Removed Staten gateway configuration".to_string())
+    } else {
+        Ok("Staten gateway configuration was not found".to_string())
+    }
+}
@@ -0,0 +1,353 @@
+use serde_json::Value;
+
+// JSON with comments and trailing commas, as used by editor settings files.
+// Edits change a single top-level member in place, entry by entry, so the
+// rest of the file, comments and formatting included, stays as the user
+// wrote it.
+
+/// Blanks out comments and trailing commas with spaces. Offsets into the
+/// result are the same as into `text`.
+fn sanitize(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = bytes.to_vec();
+    let mut i = 0;
+    let mut in_string = false;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        if in_string {
+            match b {
+                b'\\' => i += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            i += 1;
+            continue;
+        }
+
+        match b {
+            b'"' => in_string = true,
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    out[i] = b' ';
+                    i += 1;
+                }
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = text[i + 2..]
+                    .find("*/")
+                    .map(|pos| i + 2 + pos + 2)
+                    .unwrap_or(bytes.len());
+                for byte in &mut out[i..end] {
+                    // Keep line breaks so line numbers in errors still match
+                    if *byte != b'\n' {
+                        *byte = b' ';
+                    }
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    // Trailing commas, found on the comment-free text
+    let mut i = 0;
+    in_string = false;
+    while i < out.len() {
+        let b = out[i];
+        if in_string {
+            match b {
+                b'\\' => i += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else if b == b'"' {
+            in_string = true;
+        } else if b == b',' {
+            let next = out[i + 1..]
+                .iter()
+                .find(|c| !c.is_ascii_whitespace())
+                .copied();
+            if matches!(next, Some(b'}') | Some(b']')) {
+                out[i] = b' ';
+            }
+        }
+        i += 1;
+    }
+
+    // Only ASCII bytes were replaced, so this is still valid UTF-8
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Parses JSONC into a value, ignoring comments and trailing commas.
+pub fn parse(text: &str) -> Result<Value, String> {
+    if text.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    serde_json::from_str(&sanitize(text)).map_err(|e| e.to_string())
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+// Returns the offset just past the value starting at `i`
+fn skip_value(bytes: &[u8], mut i: usize) -> Result<usize, String> {
+    match bytes.get(i) {
+        Some(b'"') => {
+            i += 1;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    b'"' => return Ok(i + 1),
+                    _ => i += 1,
+                }
+            }
+            Err("Unterminated string".to_string())
+        }
+        Some(b'{') | Some(b'[') => {
+            let mut depth = 0;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'"' => {
+                        i = skip_value(bytes, i)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(i + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+            Err("Unterminated object or array".to_string())
+        }
+        Some(_) => {
+            while i < bytes.len()
+                && !matches!(bytes[i], b',' | b'}' | b']')
+                && !bytes[i].is_ascii_whitespace()
+            {
+                i += 1;
+            }
+            Ok(i)
+        }
+        None => Err("Unexpected end of input".to_string()),
+    }
+}
+
+struct Member {
+    name: String,
+    key_start: usize,
+    value_start: usize,
+    value_end: usize,
+}
+
+struct ObjectSpan {
+    /// Offset of the closing brace
+    close: usize,
+    members: Vec<Member>,
+}
+
+// Finds the members of the object that `sanitized` consists of
+fn scan_object(sanitized: &str) -> Result<ObjectSpan, String> {
+    let bytes = sanitized.as_bytes();
+    let mut i = skip_whitespace(bytes, 0);
+    if bytes.get(i) != Some(&b'{') {
+        return Err("Settings file is not a JSON object".to_string());
+    }
+    i += 1;
+
+    let mut members = Vec::new();
+    loop {
+        i = skip_whitespace(bytes, i);
+        match bytes.get(i) {
+            Some(b'}') => return Ok(ObjectSpan { close: i, members }),
+            Some(b'"') => {}
+            _ => return Err(format!("Unexpected character at offset {}", i)),
+        }
+
+        let key_start = i;
+        let key_end = skip_value(bytes, i)?;
+        let name: String =
+            serde_json::from_str(&sanitized[i..key_end]).map_err(|e| e.to_string())?;
+        i = skip_whitespace(bytes, key_end);
+        if bytes.get(i) != Some(&b':') {
+            return Err(format!("Expected ':' at offset {}", i));
+        }
+        let value_start = skip_whitespace(bytes, i + 1);
+        let value_end = skip_value(bytes, value_start)?;
+        members.push(Member {
+            name,
+            key_start,
+            value_start,
+            value_end,
+        });
+
+        i = skip_whitespace(bytes, value_end);
+        if bytes.get(i) == Some(&b',') {
+            i += 1;
+        }
+    }
+}
+
+// Pretty prints `value` for a spot whose line is indented by `indent`
+fn render(value: &Value, indent: &str) -> Result<String, String> {
+    let pretty = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    Ok(pretty.replace('\n', &format!("\n{}", indent)))
+}
+
+fn line_start(text: &str, offset: usize) -> usize {
+    text[..offset].rfind('\n').map(|pos| pos + 1).unwrap_or(0)
+}
+
+fn line_indent(text: &str, offset: usize) -> &str {
+    let line = &text[line_start(text, offset)..offset];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Sets top-level member `key` of the JSONC document `text` to `value`,
+/// leaving everything outside that member untouched. When both the old and
+/// the new value are objects, only the entries that changed are rewritten,
+/// so comments next to the others stay.
+pub fn set_member(text: &str, key: &str, value: &Value) -> Result<String, String> {
+    if text.trim().is_empty() {
+        let mut root = serde_json::Map::new();
+        root.insert(key.to_string(), value.clone());
+        return render(&Value::Object(root), "");
+    }
+
+    let sanitized = sanitize(text);
+    let root = scan_object(&sanitized)?;
+    let Some(member) = root.members.iter().find(|m| m.name == key) else {
+        return add_member(text, &sanitized, &root, key, value);
+    };
+
+    let (start, end) = (member.value_start, member.value_end);
+    let current: Value = serde_json::from_str(&sanitized[start..end]).map_err(|e| e.to_string())?;
+    if &current == value {
+        return Ok(text.to_string());
+    }
+    let rendered = match (current.as_object(), value.as_object()) {
+        (Some(entries), Some(new_entries)) if !entries.is_empty() => {
+            set_entries(&text[start..end], new_entries)?
+        }
+        _ => render(value, line_indent(text, start))?,
+    };
+    Ok(format!("{}{}{}", &text[..start], rendered, &text[end..]))
+}
+
+// Edits the object `text` into `entries` one member at a time
+fn set_entries(text: &str, entries: &serde_json::Map<String, Value>) -> Result<String, String> {
+    let mut text = text.to_string();
+    loop {
+        let sanitized = sanitize(&text);
+        let object = scan_object(&sanitized)?;
+        let Some(index) = object
+            .members
+            .iter()
+            .position(|m| !entries.contains_key(&m.name))
+        else {
+            break;
+        };
+        text = remove_member(&text, &sanitized, &object, index);
+    }
+
+    for (name, value) in entries {
+        text = set_member(&text, name, value)?;
+    }
+    Ok(text)
+}
+
+// Removes a member together with its line and separating comma
+fn remove_member(text: &str, sanitized: &str, object: &ObjectSpan, index: usize) -> String {
+    let bytes = sanitized.as_bytes();
+    let member = &object.members[index];
+
+    let mut start = member.key_start;
+    if text[line_start(text, start)..start].trim().is_empty() {
+        start = line_start(text, start);
+    }
+    let mut end = member.value_end;
+    let after = skip_whitespace(bytes, end);
+    let mut comma_before = None;
+    if bytes.get(after) == Some(&b',') {
+        end = after + 1;
+    } else if index > 0 {
+        // The member before becomes the last one
+        let previous_end = object.members[index - 1].value_end;
+        comma_before = sanitized[previous_end..start]
+            .find(',')
+            .map(|pos| previous_end + pos);
+    }
+    // Comments after the member on its line go with it
+    let line_end = sanitized[end..]
+        .find('\n')
+        .map(|pos| end + pos + 1)
+        .unwrap_or(sanitized.len());
+    if start == line_start(text, start) && sanitized[end..line_end].trim().is_empty() {
+        end = line_end;
+    }
+
+    match comma_before {
+        Some(comma) => format!(
+            "{}{}{}",
+            &text[..comma],
+            &text[comma + 1..start],
+            &text[end..]
+        ),
+        None => format!("{}{}", &text[..start], &text[end..]),
+    }
+}
+
+// Adds the member on its own line before the closing brace
+fn add_member(
+    text: &str,
+    sanitized: &str,
+    object: &ObjectSpan,
+    key: &str,
+    value: &Value,
+) -> Result<String, String> {
+    let last_value_end = sanitized[..object.close].trim_end().len();
+    let comma = if !object.members.is_empty()
+        && !text[last_value_end..object.close]
+            .trim_start()
+            .starts_with(',')
+    {
+        ","
+    } else {
+        ""
+    };
+    let indent = format!("{}  ", line_indent(text, object.close));
+    let member = format!(
+        "{}{}: {}\n",
+        indent,
+        serde_json::to_string(key).map_err(|e| e.to_string())?,
+        render(value, &indent)?
+    );
+
+    let close_line_start = line_start(text, object.close);
+    let (insert_at, member) = if text[close_line_start..object.close].trim().is_empty() {
+        (close_line_start, member)
+    } else {
+        (object.close, format!("\n{}", member))
+    };
+
+    Ok(format!(
+        "{}{}{}{}{}",
+        &text[..last_value_end],
+        comma,
+        &text[last_value_end..insert_at],
+        member,
+        &text[insert_at..]
+    ))
+}
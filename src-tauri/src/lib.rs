@@ -1,16 +1,40 @@
 pub mod app;
+pub mod cli;
+pub mod client_format;
 pub mod clients;
+pub mod config_format;
+pub mod container;
+pub mod detect;
+pub mod doctor;
+pub mod download;
 pub mod environment;
 pub mod file_utils;
+pub mod gateway;
+pub mod jsonc;
+pub mod logs;
+pub mod manifest;
+pub mod node;
+pub mod oauth;
+pub mod onboarding;
 pub mod os;
+pub mod projects;
+pub mod python;
+pub mod remote;
+pub mod restart;
+pub mod secrets;
+pub mod settings;
+pub mod setup_bundle;
+pub mod uv;
+pub mod yaml;
 
 use log::{error, info};
 use simplelog::{ConfigBuilder, LevelFilter, WriteLogger};
 use std::fs;
+use tauri::Emitter;
 use tauri_plugin_updater::{Builder as UpdaterBuilder, UpdaterExt};
 use time::macros::format_description;
 
-fn setup_logger() -> Result<(), Box<dyn std::error::Error>> {
+fn setup_logger(file_name: &str) -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(target_os = "macos")]
     let log_dir = {
         let home = dirs::home_dir().ok_or("Could not find home directory")?;
@@ -25,7 +49,7 @@ fn setup_logger() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     fs::create_dir_all(&log_dir)?;
-    let log_file = log_dir.join("staten.log");
+    let log_file = log_dir.join(file_name);
 
     let config = ConfigBuilder::new()
         .set_time_format_custom(format_description!(
@@ -33,20 +57,33 @@ fn setup_logger() -> Result<(), Box<dyn std::error::Error>> {
         ))
         .build();
 
-    #[cfg(debug_assertions)]
-    let level = LevelFilter::Debug;
-
-    #[cfg(not(debug_assertions))]
-    let level = LevelFilter::Info;
+    let level = match settings::load_settings() {
+        Ok(settings) => settings::effective_log_level(&settings),
+        Err(_) if cfg!(debug_assertions) => LevelFilter::Debug,
+        Err(_) => LevelFilter::Info,
+    };
 
-    WriteLogger::init(level, config, fs::File::create(log_file)?)?;
+    // The logger itself passes everything; log::max_level is the only
+    // filter, so a log level changed in the settings applies right away
+    WriteLogger::init(LevelFilter::Trace, config, fs::File::create(log_file)?)?;
+    log::set_max_level(level);
     info!("Logger initialized with level: {:?}", level);
     Ok(())
 }
 
-async fn update(app: tauri::AppHandle) -> tauri_plugin_updater::Result<()> {
+async fn update(
+    app: tauri::AppHandle,
+    policy: settings::AutoUpdatePolicy,
+) -> tauri_plugin_updater::Result<()> {
     if let Some(update) = app.updater()?.check().await? {
         info!("Update available: {}", update.version);
+        if policy == settings::AutoUpdatePolicy::Notify {
+            if let Err(e) = app.emit("update-available", &update.version) {
+                error!("Failed to notify about update: {}", e);
+            }
+            return Ok(());
+        }
+
         let mut downloaded = 0;
         match update
             .download_and_install(
@@ -104,8 +141,9 @@ fn open_system_url(url: String) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
         use std::process::Command;
-        Command::new("cmd")
-            .args(["/c", "start", &url])
+        // Not `cmd /c start`, which cuts the URL at the first `&`
+        Command::new("rundll32")
+            .args(["url.dll,FileProtocolHandler", &url])
             .output()
             .map_err(|e| format!("Failed to open URL: {}", e))?;
     }
@@ -160,7 +198,7 @@ fn open_logs_folder() -> Result<(), String> {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize logger
-    if let Err(e) = setup_logger() {
+    if let Err(e) = setup_logger("staten.log") {
         eprintln!("Failed to initialize logger: {}", e);
     }
 
@@ -189,22 +227,59 @@ pub fn run() {
             app::uninstall_staten_mcp,
             app::check_onboarding_completed,
             app::reset_onboarding_completed,
+            onboarding::get_onboarding_state,
+            onboarding::advance_onboarding,
             app::check_client_installed,
+            app::client_needs_restart,
+            detect::detect_clients,
+            projects::list_projects,
+            projects::add_project,
+            projects::remove_project,
+            oauth::oauth_sign_in,
+            oauth::oauth_sign_out,
+            oauth::oauth_status,
             app::get_supported_clients,
             app::get_default_client_command,
             app::set_client_config_path,
+            app::reset_client_config_path,
             app::get_client_config_path,
             app::refresh_app_registry,
+            app::set_app_logging,
+            app::get_app_logging,
+            logs::list_app_logs,
+            logs::tail_app_log,
+            logs::search_app_logs,
+            manifest::apply_manifest,
+            settings::get_settings,
+            settings::update_settings,
+            setup_bundle::export_setup,
+            setup_bundle::get_setup_import_secrets,
+            setup_bundle::import_setup,
+            gateway::install_staten_gateway,
+            gateway::uninstall_staten_gateway,
+            gateway::set_gateway_app,
+            gateway::get_gateway_apps,
             environment::ensure_environment,
+            environment::get_container_engine_status,
+            python::get_python_report,
+            doctor::get_doctor_report,
             log_from_frontend,
             open_system_url,
             open_logs_folder,
         ])
         .setup(|app| {
             let handle = app.handle().clone();
+            let policy = settings::load_settings()
+                .map(|settings| settings.auto_update)
+                .unwrap_or_default();
+            if policy == settings::AutoUpdatePolicy::Off {
+                info!("Automatic updates are turned off");
+                return Ok(());
+            }
+
             info!("Checking for updates...");
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = update(handle).await {
+                if let Err(e) = update(handle, policy).await {
                     error!("Error checking for updates: {}", e);
                 }
             });
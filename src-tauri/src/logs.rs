@@ -0,0 +1,240 @@
+use lazy_static::lazy_static;
+use log::{debug, info};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// Environment variable the Staten shims look for. When it is set on a server
+/// entry, the shim tees stderr and JSON-RPC traffic into that app's log folder.
+pub const LOG_APP_ENV: &str = "STATEN_LOG_APP";
+
+pub const STDERR_LOG: &str = "stderr.log";
+pub const PROTOCOL_LOG: &str = "protocol.log";
+
+// Rotation limits used by the generated shims
+pub const MAX_LOG_BYTES: u64 = 1024 * 1024;
+pub const MAX_ROTATED_LOGS: u32 = 3;
+
+const DEFAULT_TAIL_LINES: usize = 200;
+const DEFAULT_MAX_SEARCH_RESULTS: usize = 500;
+
+lazy_static! {
+    static ref TEST_LOGS_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+}
+
+pub fn set_test_logs_dir(path: Option<PathBuf>) {
+    let mut test_dir = TEST_LOGS_DIR.lock().unwrap();
+    *test_dir = path;
+    debug!("Test app logs directory set");
+}
+
+pub fn get_app_logs_dir() -> PathBuf {
+    if let Some(path) = TEST_LOGS_DIR.lock().unwrap().clone() {
+        return path;
+    }
+
+    #[cfg(target_os = "macos")]
+    let logs_dir = dirs::home_dir()
+        .unwrap_or_default()
+        .join("Library/Logs/Staten/apps");
+
+    #[cfg(target_os = "windows")]
+    let logs_dir = dirs::data_local_dir()
+        .unwrap_or_default()
+        .join("Staten")
+        .join("Logs")
+        .join("apps");
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let logs_dir = dirs::data_local_dir()
+        .unwrap_or_default()
+        .join("staten/logs/apps");
+
+    logs_dir
+}
+
+// Log folders are named after the app's mcpKey, so reject anything that could
+// escape the logs directory.
+fn validate_log_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\\') {
+        return Err(format!("Invalid log name: {}", name));
+    }
+    Ok(())
+}
+
+fn app_log_path(app: &str, file: &str) -> Result<PathBuf, String> {
+    validate_log_name(app)?;
+    validate_log_name(file)?;
+    Ok(get_app_logs_dir().join(app).join(file))
+}
+
+fn file_entry(path: &Path) -> Option<Value> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Some(json!({
+        "name": path.file_name()?.to_string_lossy(),
+        "size": metadata.len(),
+        "modified": modified
+    }))
+}
+
+/// Returns the last `count` lines of `path`.
+pub fn tail_lines(path: &Path, count: usize) -> Result<Vec<String>, String> {
+    let content =
+        fs::read(path).map_err(|e| format!("Failed to read log {}: {}", path.display(), e))?;
+    let content = String::from_utf8_lossy(&content);
+
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.len().saturating_sub(count);
+    Ok(lines[start..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Case-insensitive search through every log file of every app (or a single
+/// app), newest file first.
+pub fn search_logs(
+    logs_dir: &Path,
+    query: &str,
+    app: Option<&str>,
+    max_results: usize,
+) -> Result<Vec<Value>, String> {
+    let needle = query.to_lowercase();
+    let mut results = Vec::new();
+
+    if !logs_dir.exists() {
+        return Ok(results);
+    }
+
+    let mut app_dirs: Vec<PathBuf> = match app {
+        Some(name) => {
+            validate_log_name(name)?;
+            vec![logs_dir.join(name)]
+        }
+        None => fs::read_dir(logs_dir)
+            .map_err(|e| format!("Failed to read logs directory: {}", e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect(),
+    };
+    app_dirs.sort();
+
+    for app_dir in app_dirs {
+        let app_name = app_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut files: Vec<PathBuf> = match fs::read_dir(&app_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.is_file())
+                .collect(),
+            Err(_) => continue,
+        };
+        // "stderr.log" sorts before "stderr.log.1", so the live file comes first
+        files.sort();
+
+        for file in files {
+            let content = match fs::read(&file) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+                Err(_) => continue,
+            };
+
+            for (index, line) in content.lines().enumerate() {
+                if line.to_lowercase().contains(&needle) {
+                    results.push(json!({
+                        "app": app_name,
+                        "file": file.file_name().map(|n| n.to_string_lossy().to_string()),
+                        "line": index + 1,
+                        "text": line
+                    }));
+
+                    if results.len() >= max_results {
+                        return Ok(results);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+#[tauri::command]
+pub fn list_app_logs() -> Result<Value, String> {
+    let logs_dir = get_app_logs_dir();
+    debug!("Listing app logs in {}", logs_dir.display());
+
+    if !logs_dir.exists() {
+        return Ok(json!([]));
+    }
+
+    let mut app_dirs: Vec<PathBuf> = fs::read_dir(&logs_dir)
+        .map_err(|e| format!("Failed to read logs directory: {}", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    app_dirs.sort();
+
+    let mut apps = Vec::new();
+    for app_dir in app_dirs {
+        let mut files: Vec<PathBuf> = fs::read_dir(&app_dir)
+            .map_err(|e| format!("Failed to read log directory: {}", e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file())
+            .collect();
+        files.sort();
+
+        apps.push(json!({
+            "app": app_dir.file_name().map(|n| n.to_string_lossy().to_string()),
+            "path": app_dir.to_string_lossy(),
+            "files": files.iter().filter_map(|f| file_entry(f)).collect::<Vec<Value>>()
+        }));
+    }
+
+    Ok(Value::Array(apps))
+}
+
+#[tauri::command]
+pub fn tail_app_log(
+    app: &str,
+    file: Option<String>,
+    lines: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let file = file.unwrap_or_else(|| STDERR_LOG.to_string());
+    let path = app_log_path(app, &file)?;
+
+    if !path.exists() {
+        info!("No log file at {}", path.display());
+        return Ok(Vec::new());
+    }
+
+    tail_lines(&path, lines.unwrap_or(DEFAULT_TAIL_LINES))
+}
+
+#[tauri::command]
+pub fn search_app_logs(
+    query: &str,
+    app: Option<String>,
+    max_results: Option<usize>,
+) -> Result<Value, String> {
+    if query.is_empty() {
+        return Err("Search query must not be empty".to_string());
+    }
+
+    let results = search_logs(
+        &get_app_logs_dir(),
+        query,
+        app.as_deref(),
+        max_results.unwrap_or(DEFAULT_MAX_SEARCH_RESULTS),
+    )?;
+
+    Ok(Value::Array(results))
+}
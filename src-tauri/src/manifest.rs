@@ -0,0 +1,383 @@
+use crate::app::{self, AppConfig};
+use crate::clients::ClientType;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+const REDACTED: &str = "********";
+
+/// Where the value of an env variable in a manifest comes from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvSource {
+    /// `KEY = "value"`
+    Literal(String),
+    /// `KEY = { env = "OS_VARIABLE" }`
+    Env { env: String },
+    /// `KEY = { keychain = "service", account = "name" }`
+    Keychain {
+        keychain: String,
+        #[serde(default)]
+        account: Option<String>,
+    },
+}
+
+impl EnvSource {
+    fn resolve(&self, name: &str) -> Result<String, String> {
+        match self {
+            EnvSource::Literal(value) => Ok(value.clone()),
+            EnvSource::Env { env } => std::env::var(env)
+                .map_err(|_| format!("Environment variable {} (for {}) is not set", env, name)),
+            EnvSource::Keychain { keychain, account } => {
+                crate::secrets::read_secret(keychain, account.as_deref())
+            }
+        }
+    }
+
+    fn is_secret(&self) -> bool {
+        !matches!(self, EnvSource::Literal(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestApp {
+    /// App name as listed in the registry
+    pub name: String,
+    /// Package version to pin, written as `package@version` in the args
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Clients to install into; falls back to the manifest's `clients`
+    #[serde(default)]
+    pub clients: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, EnvSource>,
+}
+
+/// A team's MCP setup, read from `staten.toml` or `staten.json`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub clients: Vec<String>,
+    /// Remove registry apps that are installed but not listed. Entries that are
+    /// not from the registry are never touched.
+    #[serde(default)]
+    pub prune: bool,
+    #[serde(default)]
+    pub apps: Vec<ManifestApp>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeAction {
+    Add,
+    Update,
+    Remove,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub client: String,
+    pub app: String,
+    pub mcp_key: String,
+    pub action: ChangeAction,
+    /// Human-readable field differences, secrets redacted
+    pub diff: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plan {
+    pub applied: bool,
+    pub changes: Vec<PlannedChange>,
+}
+
+pub fn parse_manifest(content: &str, path: &Path) -> Result<Manifest, String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(content)
+            .map_err(|e| format!("Failed to parse manifest JSON: {}", e)),
+        _ => toml::from_str(content).map_err(|e| format!("Failed to parse manifest TOML: {}", e)),
+    }
+}
+
+pub fn load_manifest(path: &Path) -> Result<Manifest, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read manifest: {}", e))?;
+    parse_manifest(&content, path)
+}
+
+// Splits `name@version` while leaving the `@` of a scoped npm package alone.
+fn strip_version(package: &str) -> &str {
+    match package.rfind('@') {
+        Some(index) if index > 0 => &package[..index],
+        _ => package,
+    }
+}
+
+/// Rewrites the package argument of an npx or uvx app as `package@version`.
+/// The package is the value of `--from` when given, else the first non-flag arg.
+pub fn pin_version(runtime: &str, args: &[String], version: &str) -> Vec<String> {
+    let mut pinned = args.to_vec();
+    if runtime != "npx" && runtime != "uvx" {
+        return pinned;
+    }
+
+    let index = match args.iter().position(|arg| arg == "--from") {
+        Some(from) if from + 1 < args.len() => Some(from + 1),
+        _ => args.iter().position(|arg| !arg.starts_with('-')),
+    };
+
+    if let Some(index) = index {
+        pinned[index] = format!("{}@{}", strip_version(&args[index]), version);
+    }
+    pinned
+}
+
+fn describe_diff(
+    current: Option<&Value>,
+    desired: Option<&Value>,
+    secret_keys: &BTreeSet<String>,
+) -> Vec<String> {
+    let empty = json!({});
+    let current = current.unwrap_or(&empty);
+    let desired = desired.unwrap_or(&empty);
+    let mut diff = Vec::new();
+
+    for field in ["command", "args", "type", "url"] {
+        if current.get(field) != desired.get(field) {
+            diff.push(format!(
+                "{}: {} -> {}",
+                field,
+                current.get(field).unwrap_or(&Value::Null),
+                desired.get(field).unwrap_or(&Value::Null)
+            ));
+        }
+    }
+
+    // Headers of remote servers usually carry credentials, so only the fact
+    // that they changed is shown
+    if current.get("headers") != desired.get("headers") {
+        diff.push("headers: changed".to_string());
+    }
+
+    let no_env = Map::new();
+    let current_env = current
+        .get("env")
+        .and_then(|e| e.as_object())
+        .unwrap_or(&no_env);
+    let desired_env = desired
+        .get("env")
+        .and_then(|e| e.as_object())
+        .unwrap_or(&no_env);
+    let keys: BTreeSet<&String> = current_env.keys().chain(desired_env.keys()).collect();
+
+    for key in keys {
+        let (before, after) = (current_env.get(key), desired_env.get(key));
+        if before == after {
+            continue;
+        }
+        let show = |value: Option<&Value>| match value {
+            None => "(unset)".to_string(),
+            Some(_) if secret_keys.contains(key) => REDACTED.to_string(),
+            Some(value) => value.to_string(),
+        };
+        diff.push(format!("env.{}: {} -> {}", key, show(before), show(after)));
+    }
+
+    diff
+}
+
+// mcp key -> (app name, desired entry, secret env keys)
+type DesiredEntries = BTreeMap<String, (String, Value, BTreeSet<String>)>;
+
+fn desired_entry(app: &ManifestApp, config: &AppConfig) -> Result<Value, String> {
+    let mut env = Map::new();
+    for (name, source) in &app.env {
+        env.insert(name.clone(), json!(source.resolve(name)?));
+    }
+
+    let mut config = config.clone();
+    if let Some(version) = &app.version {
+        match &mut config.container {
+            Some(container) => container.pin(version),
+            None => config.args = pin_version(&config.runtime, &config.args, version),
+        }
+    }
+
+    Ok(app::build_server_entry(&config, Value::Object(env)))
+}
+
+/// Computes the changes needed to make every client config match the manifest,
+/// and writes them unless `dry_run` is set.
+pub fn reconcile(manifest: &Manifest, dry_run: bool) -> Result<Plan, String> {
+    let registry = app::get_app_configs()?;
+    let find_app = |name: &str| {
+        registry
+            .iter()
+            .find(|(app_name, _)| app_name == name)
+            .map(|(_, config)| config)
+    };
+
+    let mut desired: BTreeMap<String, DesiredEntries> = BTreeMap::new();
+
+    for manifest_app in &manifest.apps {
+        let config = find_app(&manifest_app.name)
+            .ok_or_else(|| format!("App '{}' is not in the registry", manifest_app.name))?;
+
+        let clients = if manifest_app.clients.is_empty() {
+            &manifest.clients
+        } else {
+            &manifest_app.clients
+        };
+        if clients.is_empty() {
+            return Err(format!("No clients listed for app '{}'", manifest_app.name));
+        }
+
+        let entry = desired_entry(manifest_app, config)?;
+        if let Some(binary) = config.binary.as_ref().filter(|_| !dry_run) {
+            crate::download::ensure_binary(binary)?;
+        }
+        if config.runtime == "npx" && !dry_run {
+            match &config.node_version {
+                Some(version) => crate::environment::ensure_versioned_npx_shim(version)?,
+                None => crate::environment::ensure_npx_shim()?,
+            };
+        }
+        if let Some(python) = config.python.as_ref().filter(|_| !dry_run) {
+            crate::python::install_python(python)?;
+            crate::environment::ensure_uvx_python_shim()?;
+        }
+        let secret_keys: BTreeSet<String> = manifest_app
+            .env
+            .iter()
+            .filter(|(_, source)| source.is_secret())
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        for client in clients {
+            let client_type = ClientType::from_str(client)
+                .ok_or_else(|| format!("Invalid client in manifest: {}", client))?;
+            desired
+                .entry(client_type.as_str().to_string())
+                .or_default()
+                .insert(
+                    config.mcp_key.clone(),
+                    (
+                        manifest_app.name.clone(),
+                        entry.clone(),
+                        secret_keys.clone(),
+                    ),
+                );
+        }
+    }
+
+    if manifest.prune {
+        // Only clients that already have a config, pruning must not create one
+        for client in ClientType::all().into_iter().filter(app::has_config) {
+            desired.entry(client.as_str().to_string()).or_default();
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (client, wanted) in &desired {
+        let client_type = ClientType::from_str(client).ok_or("Invalid client")?;
+        let mut config_json = match app::get_config(&client_type) {
+            Ok(config_json) => config_json,
+            Err(e) if wanted.is_empty() => {
+                warn!("Skipping prune for {}: {}", client, e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        let mut client_changed = false;
+
+        {
+            let servers = config_json
+                .get_mut("mcpServers")
+                .and_then(|v| v.as_object_mut())
+                .ok_or("Failed to find mcpServers in config")?;
+
+            for (mcp_key, (app_name, entry, secret_keys)) in wanted {
+                let current = servers.get(mcp_key);
+                let action = match current {
+                    None => ChangeAction::Add,
+                    Some(current) if current == entry => ChangeAction::Unchanged,
+                    Some(_) => ChangeAction::Update,
+                };
+
+                changes.push(PlannedChange {
+                    client: client.clone(),
+                    app: app_name.clone(),
+                    mcp_key: mcp_key.clone(),
+                    diff: describe_diff(current, Some(entry), secret_keys),
+                    action: action.clone(),
+                });
+
+                if action != ChangeAction::Unchanged {
+                    servers.insert(mcp_key.clone(), entry.clone());
+                    client_changed = true;
+                }
+            }
+
+            if manifest.prune {
+                for (app_name, config) in &registry {
+                    if wanted.contains_key(&config.mcp_key) {
+                        continue;
+                    }
+                    if let Some(current) = servers.remove(&config.mcp_key) {
+                        changes.push(PlannedChange {
+                            client: client.clone(),
+                            app: app_name.clone(),
+                            mcp_key: config.mcp_key.clone(),
+                            diff: describe_diff(Some(&current), None, &BTreeSet::new()),
+                            action: ChangeAction::Remove,
+                        });
+                        client_changed = true;
+                    }
+                }
+            }
+        }
+
+        if client_changed && !dry_run {
+            app::save_config(&config_json, &client_type)?;
+        }
+    }
+
+    // Only now do the configs no longer start the versions being replaced
+    if !dry_run {
+        for manifest_app in &manifest.apps {
+            if let Some(binary) = find_app(&manifest_app.name).and_then(|c| c.binary.as_ref()) {
+                app::remove_unused_binary_versions(binary);
+            }
+        }
+    }
+
+    info!(
+        "Manifest plan has {} changes (dry run: {})",
+        changes
+            .iter()
+            .filter(|c| c.action != ChangeAction::Unchanged)
+            .count(),
+        dry_run
+    );
+
+    Ok(Plan {
+        applied: !dry_run,
+        changes,
+    })
+}
+
+#[tauri::command]
+pub async fn apply_manifest(path: String, dry_run: bool) -> Result<Value, String> {
+    info!("Applying manifest {} (dry run: {})", path, dry_run);
+    // Applying downloads what the apps need, which must not hold up the UI
+    let plan = tauri::async_runtime::spawn_blocking(move || {
+        let manifest = load_manifest(Path::new(&path))?;
+        reconcile(&manifest, dry_run)
+    })
+    .await
+    .map_err(|e| format!("Failed to apply manifest: {}", e))??;
+    serde_json::to_value(plan).map_err(|e| format!("Failed to serialize plan: {}", e))
+}
@@ -0,0 +1,366 @@
+use crate::download::{self, ArchiveKind};
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use semver::{Version, VersionReq};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Staten's own Node.js. The official build for the platform is downloaded from
+// nodejs.org, checked against the release's SHASUMS256.txt and unpacked into
+// Staten's data dir, one directory per version. Nothing outside that directory
+// is touched, so it works next to an existing nvm, Volta or system Node.
+//
+// npx apps run on the default version unless the registry gives them an
+// `engines.node` range it doesn't satisfy, in which case they get their own
+// shim for the newest version that does.
+
+/// Built-in default, overridden by the `nodeVersion` setting.
+pub const NODE_VERSION: &str = "v22.12.0";
+
+const DEFAULT_NODE_MIRROR: &str = "https://nodejs.org/dist";
+
+/// Overrides where Node.js releases are downloaded from, for networks that
+/// can't reach nodejs.org. Takes the same layout as nodejs.org/dist.
+pub const NODE_MIRROR_ENV: &str = "STATEN_NODE_MIRROR";
+
+static TEST_NODE_DIR: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+/// A release from the mirror's index.json, newest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRelease {
+    pub version: String,
+    pub lts: bool,
+}
+
+// Keyed by mirror, fetched once per run
+static NODE_INDEX: Lazy<Mutex<HashMap<String, Vec<NodeRelease>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[cfg(feature = "test-utils")]
+pub fn set_test_node_dir(dir: Option<PathBuf>) {
+    *TEST_NODE_DIR.lock().unwrap() = dir;
+}
+
+/// Where managed Node.js versions are kept.
+pub fn node_root() -> PathBuf {
+    if let Some(dir) = TEST_NODE_DIR.lock().unwrap().clone() {
+        return dir;
+    }
+    if crate::environment::is_test_mode() {
+        return PathBuf::from("/test/.local/share/staten/node");
+    }
+
+    #[cfg(target_os = "windows")]
+    let root = dirs::data_local_dir()
+        .unwrap_or_default()
+        .join("staten")
+        .join("node");
+
+    #[cfg(not(target_os = "windows"))]
+    let root = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".local/share/staten/node");
+
+    root
+}
+
+fn node_mirror() -> String {
+    std::env::var(NODE_MIRROR_ENV)
+        .ok()
+        .filter(|mirror| !mirror.trim().is_empty())
+        .map(|mirror| mirror.trim().trim_end_matches('/').to_string())
+        .unwrap_or_else(|| DEFAULT_NODE_MIRROR.to_string())
+}
+
+/// Name of the official build of `version` for an OS and architecture as
+/// `std::env::consts` spells them, such as `node-v20.9.0-darwin-arm64`.
+pub fn dist_name(version: &str, os: &str, arch: &str) -> Option<String> {
+    let os = match os {
+        "macos" => "darwin",
+        "linux" => "linux",
+        "windows" => "win",
+        _ => return None,
+    };
+    let arch = match arch {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        _ => return None,
+    };
+    Some(format!("node-{}-{}-{}", version, os, arch))
+}
+
+fn archive_kind() -> ArchiveKind {
+    if cfg!(target_os = "windows") {
+        ArchiveKind::Zip
+    } else {
+        ArchiveKind::TarGz
+    }
+}
+
+fn archive_name(version: &str) -> Result<String, String> {
+    let name = dist_name(version, std::env::consts::OS, std::env::consts::ARCH)
+        .ok_or_else(|| format!("Node.js has no build for {}", download::platform_key()))?;
+    let extension = match archive_kind() {
+        ArchiveKind::Zip => "zip",
+        _ => "tar.gz",
+    };
+    Ok(format!("{}.{}", name, extension))
+}
+
+/// Finds the checksum of `file` in a SHASUMS256.txt.
+pub fn parse_shasums(shasums: &str, file: &str) -> Option<String> {
+    shasums.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let checksum = parts.next()?;
+        (parts.next()? == file).then(|| checksum.to_lowercase())
+    })
+}
+
+pub fn node_dir(version: &str) -> PathBuf {
+    node_root().join(version)
+}
+
+/// Paths of node and npx in a managed Node.js installation.
+pub fn node_paths(version: &str) -> (PathBuf, PathBuf) {
+    let dir = node_dir(version);
+
+    #[cfg(target_os = "windows")]
+    let paths = (dir.join("node.exe"), dir.join("npx.cmd"));
+
+    #[cfg(not(target_os = "windows"))]
+    let paths = (dir.join("bin").join("node"), dir.join("bin").join("npx"));
+
+    paths
+}
+
+pub fn is_node_installed(version: &str) -> bool {
+    let (node, npx) = node_paths(version);
+    node.is_file() && npx.exists()
+}
+
+/// Downloads, verifies and unpacks Node.js `version` unless it's already
+/// there, and returns the paths of node and npx.
+pub fn install_node(version: &str) -> Result<(String, String), String> {
+    let (node, npx) = node_paths(version);
+    if is_node_installed(version) {
+        debug!("Node.js {} is already installed", version);
+        return Ok((
+            node.to_string_lossy().to_string(),
+            npx.to_string_lossy().to_string(),
+        ));
+    }
+
+    let archive = archive_name(version)?;
+    let base = format!("{}/{}", node_mirror(), version);
+    info!("Downloading Node.js {} from {}", version, base);
+
+    let shasums = download::fetch(&format!("{}/SHASUMS256.txt", base))?;
+    let expected = parse_shasums(&String::from_utf8_lossy(&shasums), &archive)
+        .ok_or_else(|| format!("{} is not listed in SHASUMS256.txt", archive))?;
+    let bytes = download::fetch(&format!("{}/{}", base, archive))?;
+    let checksum = download::sha256_hex(&bytes);
+    if checksum != expected {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            archive, expected, checksum
+        ));
+    }
+
+    // The archive holds a single node-<version>-<platform> directory, which
+    // is moved in place once everything is unpacked
+    let root = node_root();
+    let partial = root.join(format!(".{}.partial", version));
+    if partial.exists() {
+        fs::remove_dir_all(&partial).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(&partial)
+        .map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+
+    let unpacked = download::unpack_archive(&bytes, archive_kind(), &partial).and_then(|_| {
+        let name = archive.trim_end_matches(".tar.gz").trim_end_matches(".zip");
+        let dir = node_dir(version);
+        if dir.exists() {
+            fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+        }
+        fs::rename(partial.join(name), &dir)
+            .map_err(|e| format!("Failed to move Node.js in place: {}", e))
+    });
+    let _ = fs::remove_dir_all(&partial);
+    unpacked?;
+
+    if !is_node_installed(version) {
+        return Err(format!(
+            "Node.js {} was unpacked, but node or npx is missing",
+            version
+        ));
+    }
+
+    info!(
+        "Installed Node.js {} at {}",
+        version,
+        node_dir(version).display()
+    );
+    Ok((
+        node.to_string_lossy().to_string(),
+        npx.to_string_lossy().to_string(),
+    ))
+}
+
+/// Accepts `22.12.0` as well as `v22.12.0` and returns the latter.
+pub fn normalize_version(version: &str) -> Result<String, String> {
+    let bare = version.trim().trim_start_matches('v');
+    Version::parse(bare)
+        .map(|v| format!("v{}", v))
+        .map_err(|_| format!("Invalid Node.js version: {}", version))
+}
+
+/// The version npx apps run on unless they require another one.
+pub fn default_node_version() -> String {
+    crate::settings::load_settings()
+        .ok()
+        .and_then(|settings| settings.node_version)
+        .and_then(|version| match normalize_version(&version) {
+            Ok(version) => Some(version),
+            Err(e) => {
+                warn!("Ignoring the nodeVersion setting: {}", e);
+                None
+            }
+        })
+        .unwrap_or_else(|| NODE_VERSION.to_string())
+}
+
+/// Parses an npm version range such as `>=18 <21 || ^22`, one requirement
+/// per `||` alternative. npm separates comparators with spaces where semver
+/// uses commas, and has hyphen ranges semver lacks.
+pub fn parse_node_range(range: &str) -> Result<Vec<VersionReq>, String> {
+    range
+        .split("||")
+        .map(|alternative| {
+            let alternative = alternative.trim();
+            let req = match alternative.split_once(" - ") {
+                Some((low, high)) => format!(
+                    ">={}, <={}",
+                    low.trim().trim_start_matches('v'),
+                    high.trim().trim_start_matches('v')
+                ),
+                None if alternative.is_empty() => "*".to_string(),
+                None => {
+                    // `>= 18` is one comparator, the operator sticks to the version
+                    let mut comparators: Vec<String> = Vec::new();
+                    let mut operator = String::new();
+                    for token in alternative.split_whitespace() {
+                        if token.chars().all(|c| "<>=~^".contains(c)) {
+                            operator.push_str(token);
+                            continue;
+                        }
+                        let (op, version) =
+                            token.split_at(token.find(|c: char| !"<>=~^".contains(c)).unwrap_or(0));
+                        comparators.push(format!(
+                            "{}{}{}",
+                            operator,
+                            op,
+                            version.trim_start_matches('v')
+                        ));
+                        operator.clear();
+                    }
+                    comparators.join(", ")
+                }
+            };
+            VersionReq::parse(&req).map_err(|e| format!("Invalid Node.js range '{}': {}", range, e))
+        })
+        .collect()
+}
+
+pub fn satisfies(version: &str, range: &[VersionReq]) -> bool {
+    Version::parse(version.trim_start_matches('v'))
+        .is_ok_and(|version| range.iter().any(|req| req.matches(&version)))
+}
+
+fn newest<'a>(versions: impl Iterator<Item = &'a String>, range: &[VersionReq]) -> Option<String> {
+    versions
+        .filter(|version| satisfies(version, range))
+        .max_by_key(|version| Version::parse(version.trim_start_matches('v')).ok())
+        .cloned()
+}
+
+/// Picks the version for a range from the releases in `index`: the default
+/// when it satisfies the range, else the newest installed one that does, else
+/// the newest LTS release, else the newest release.
+pub fn pick_version(
+    range: &str,
+    default: &str,
+    installed: &[String],
+    index: &[NodeRelease],
+) -> Result<Option<String>, String> {
+    let range = parse_node_range(range)?;
+    if satisfies(default, &range) {
+        return Ok(Some(default.to_string()));
+    }
+    if let Some(version) = newest(installed.iter(), &range) {
+        return Ok(Some(version));
+    }
+
+    let lts: Vec<String> = index
+        .iter()
+        .filter(|release| release.lts)
+        .map(|release| release.version.clone())
+        .collect();
+    Ok(newest(lts.iter(), &range)
+        .or_else(|| newest(index.iter().map(|release| &release.version), &range)))
+}
+
+/// Managed versions that are fully installed.
+pub fn installed_versions() -> Vec<String> {
+    fs::read_dir(node_root())
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str().map(String::from))
+                .filter(|name| name.starts_with('v') && is_node_installed(name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn node_index() -> Result<Vec<NodeRelease>, String> {
+    let mirror = node_mirror();
+    if let Some(index) = NODE_INDEX.lock().unwrap().get(&mirror) {
+        return Ok(index.clone());
+    }
+
+    info!("Fetching the Node.js release index from {}", mirror);
+    let bytes = download::fetch(&format!("{}/index.json", mirror))?;
+    let releases: Value = serde_json::from_slice(&bytes)
+        .map_err(|e| format!("Failed to parse the Node.js release index: {}", e))?;
+    let index: Vec<NodeRelease> = releases
+        .as_array()
+        .ok_or("The Node.js release index is not an array")?
+        .iter()
+        .filter_map(|release| {
+            Some(NodeRelease {
+                version: release["version"].as_str()?.to_string(),
+                // `false`, or the name of the LTS line
+                lts: release["lts"].is_string(),
+            })
+        })
+        .collect();
+
+    NODE_INDEX.lock().unwrap().insert(mirror, index.clone());
+    Ok(index)
+}
+
+/// Resolves an app's `engines.node` range to the version it should run on.
+/// The release index is only fetched when neither the default nor an
+/// installed version satisfies the range.
+pub fn resolve_node_version(range: &str) -> Result<String, String> {
+    let default = default_node_version();
+    if let Some(version) = pick_version(range, &default, &installed_versions(), &[])? {
+        return Ok(version);
+    }
+
+    pick_version(range, &default, &[], &node_index()?)?
+        .ok_or_else(|| format!("No Node.js release matches {}", range))
+}
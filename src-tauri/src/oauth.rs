@@ -0,0 +1,547 @@
+use crate::secrets;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use log::{debug, info, warn};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// OAuth 2.1 for remote MCP servers. Staten registers itself as a public client
+// through dynamic client registration, runs the authorization code flow with
+// PKCE in the system browser with a loopback redirect, and keeps the tokens in
+// the OS credential store, one item per server URL.
+
+const SECRET_SERVICE_PREFIX: &str = "staten-oauth:";
+const SECRET_ACCOUNT: &str = "staten";
+const CLIENT_NAME: &str = "Staten";
+const CALLBACK_PATH: &str = "/callback";
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+// Tokens this close to expiry are refreshed before they are used
+const EXPIRY_MARGIN_SECS: u64 = 60;
+
+/// The endpoints of an authorization server.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ServerMetadata {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub registration_endpoint: Option<String>,
+}
+
+/// Tokens for one server, with what is needed to refresh them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Unix time in seconds
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    pub token_endpoint: String,
+}
+
+impl OAuthTokens {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now_secs() + EXPIRY_MARGIN_SECS)
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct RegisteredClient {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+/// A PKCE code verifier. Alphanumerics are a subset of the allowed characters.
+pub fn code_verifier() -> String {
+    random_string(64)
+}
+
+/// The S256 code challenge for `verifier`.
+pub fn code_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn http_client() -> Result<Client, String> {
+    Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+// Well-known URLs for `url`, with the path inserted after the well-known
+// suffix as RFC 8414 and RFC 9728 describe, then at the root.
+fn well_known_urls(url: &Url, suffix: &str) -> Vec<String> {
+    let origin = url.origin().ascii_serialization();
+    let path = url.path().trim_end_matches('/');
+    let mut urls = Vec::new();
+    if !path.is_empty() {
+        urls.push(format!("{}/.well-known/{}{}", origin, suffix, path));
+    }
+    urls.push(format!("{}/.well-known/{}", origin, suffix));
+    urls
+}
+
+fn fetch_json(client: &Client, url: &str) -> Option<Value> {
+    let response = client.get(url).send().ok()?;
+    if !response.status().is_success() {
+        debug!("{} answered with {}", url, response.status());
+        return None;
+    }
+    response.json().ok()
+}
+
+/// Finds the authorization server of the MCP server at `server_url`. Servers
+/// without metadata get the default endpoints at their origin.
+pub fn discover(server_url: &str) -> Result<ServerMetadata, String> {
+    let server = Url::parse(server_url).map_err(|e| format!("Invalid server URL: {}", e))?;
+    let client = http_client()?;
+
+    let issuer = well_known_urls(&server, "oauth-protected-resource")
+        .iter()
+        .filter_map(|url| fetch_json(&client, url))
+        .find_map(|metadata| {
+            metadata["authorization_servers"][0]
+                .as_str()
+                .map(String::from)
+        });
+    let issuer = match issuer {
+        Some(issuer) => Url::parse(&issuer)
+            .map_err(|e| format!("Invalid authorization server {}: {}", issuer, e))?,
+        None => server.clone(),
+    };
+    debug!("Authorization server for {} is {}", server_url, issuer);
+
+    let mut candidates = well_known_urls(&issuer, "oauth-authorization-server");
+    candidates.extend(well_known_urls(&issuer, "openid-configuration"));
+    for url in &candidates {
+        if let Some(metadata) = fetch_json(&client, url) {
+            return serde_json::from_value(metadata)
+                .map_err(|e| format!("Invalid authorization server metadata at {}: {}", url, e));
+        }
+    }
+
+    let origin = issuer.origin().ascii_serialization();
+    info!(
+        "No authorization server metadata for {}, using defaults",
+        origin
+    );
+    Ok(ServerMetadata {
+        authorization_endpoint: format!("{}/authorize", origin),
+        token_endpoint: format!("{}/token", origin),
+        registration_endpoint: Some(format!("{}/register", origin)),
+    })
+}
+
+fn register_client(
+    client: &Client,
+    metadata: &ServerMetadata,
+    redirect_uri: &str,
+) -> Result<RegisteredClient, String> {
+    let endpoint = metadata
+        .registration_endpoint
+        .as_ref()
+        .ok_or("The authorization server does not support dynamic client registration")?;
+
+    let response = client
+        .post(endpoint)
+        .json(&json!({
+            "client_name": CLIENT_NAME,
+            "redirect_uris": [redirect_uri],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "response_types": ["code"],
+            "token_endpoint_auth_method": "none"
+        }))
+        .send()
+        .map_err(|e| format!("Failed to register with {}: {}", endpoint, e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Client registration at {} failed with {}",
+            endpoint,
+            response.status()
+        ));
+    }
+    response
+        .json()
+        .map_err(|e| format!("Invalid client registration response: {}", e))
+}
+
+/// The URL the browser is sent to.
+pub fn authorization_url(
+    metadata: &ServerMetadata,
+    client_id: &str,
+    redirect_uri: &str,
+    challenge: &str,
+    state: &str,
+    resource: &str,
+) -> Result<String, String> {
+    let mut url = Url::parse(&metadata.authorization_endpoint)
+        .map_err(|e| format!("Invalid authorization endpoint: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", client_id)
+        .append_pair("redirect_uri", redirect_uri)
+        .append_pair("code_challenge", challenge)
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("state", state)
+        .append_pair("resource", resource);
+    Ok(url.to_string())
+}
+
+fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!(
+        "<html><body><p>{}</p></body></html>",
+        message.replace('<', "&lt;")
+    );
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+}
+
+// Waits for the browser to come back to the loopback redirect and returns the
+// authorization code.
+fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String, String> {
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to prepare the redirect listener: {}", e))?;
+    let deadline = Instant::now() + CALLBACK_TIMEOUT;
+
+    loop {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                if Instant::now() > deadline {
+                    return Err("Timed out waiting for the browser sign-in".to_string());
+                }
+                std::thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+            Err(e) => return Err(format!("Failed to accept the redirect: {}", e)),
+        };
+        let _ = stream.set_nonblocking(false);
+
+        // Read the whole request head, closing with unread data would reset
+        // the connection before the browser sees the page
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).is_err() {
+            continue;
+        }
+        let mut header = String::new();
+        while reader.read_line(&mut header).is_ok_and(|read| read > 2) {
+            header.clear();
+        }
+        let target = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let Ok(url) = Url::parse(&format!("http://127.0.0.1{}", target)) else {
+            respond(&mut stream, "400 Bad Request", "Invalid request");
+            continue;
+        };
+        // Browsers also ask for things like /favicon.ico
+        if url.path() != CALLBACK_PATH {
+            respond(&mut stream, "404 Not Found", "Not found");
+            continue;
+        }
+
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_string())
+        };
+        // Not our redirect, keep waiting for the one that is
+        if param("state").as_deref() != Some(state) {
+            warn!("Ignoring a sign-in redirect with the wrong state");
+            respond(
+                &mut stream,
+                "400 Bad Request",
+                "Sign-in state did not match",
+            );
+            continue;
+        }
+        if let Some(error) = param("error") {
+            let description = param("error_description").unwrap_or_default();
+            respond(
+                &mut stream,
+                "200 OK",
+                "Sign-in failed. You can close this window.",
+            );
+            return Err(format!("Sign-in failed: {} {}", error, description)
+                .trim()
+                .to_string());
+        }
+        let code = param("code").ok_or("The redirect had no authorization code")?;
+        respond(
+            &mut stream,
+            "200 OK",
+            "Signed in to Staten. You can close this window.",
+        );
+        return Ok(code);
+    }
+}
+
+fn request_tokens(
+    client: &Client,
+    token_endpoint: &str,
+    client_id: &str,
+    client_secret: Option<&str>,
+    form: &[(&str, &str)],
+) -> Result<TokenResponse, String> {
+    let mut form: Vec<(&str, &str)> = form.to_vec();
+    form.push(("client_id", client_id));
+    if let Some(secret) = client_secret {
+        form.push(("client_secret", secret));
+    }
+
+    let response = client
+        .post(token_endpoint)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&form)
+        .send()
+        .map_err(|e| format!("Failed to reach {}: {}", token_endpoint, e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        return Err(format!(
+            "Token request to {} failed with {}: {}",
+            token_endpoint,
+            status,
+            body.trim()
+        ));
+    }
+    response
+        .json()
+        .map_err(|e| format!("Invalid token response: {}", e))
+}
+
+fn secret_service(server_url: &str) -> String {
+    format!("{}{}", SECRET_SERVICE_PREFIX, server_url)
+}
+
+/// The stored tokens for `server_url`, if the user signed in.
+pub fn load_tokens(server_url: &str) -> Option<OAuthTokens> {
+    let stored = secrets::read_secret(&secret_service(server_url), Some(SECRET_ACCOUNT)).ok()?;
+    match serde_json::from_str(&stored) {
+        Ok(tokens) => Some(tokens),
+        Err(e) => {
+            warn!("Ignoring unreadable tokens for {}: {}", server_url, e);
+            None
+        }
+    }
+}
+
+fn store_tokens(server_url: &str, tokens: &OAuthTokens) -> Result<(), String> {
+    let serialized = serde_json::to_string(tokens).map_err(|e| e.to_string())?;
+    secrets::write_secret(&secret_service(server_url), SECRET_ACCOUNT, &serialized)
+}
+
+/// Forgets the tokens for `server_url`.
+pub fn sign_out(server_url: &str) -> Result<(), String> {
+    info!("Signing out of {}", server_url);
+    secrets::delete_secret(&secret_service(server_url), SECRET_ACCOUNT)
+}
+
+/// Runs the authorization flow for `server_url`. `open_url` shows the
+/// authorization page to the user, normally in the system browser.
+pub fn authorize_with(
+    server_url: &str,
+    open_url: impl FnOnce(String) -> Result<(), String>,
+) -> Result<OAuthTokens, String> {
+    info!("Starting OAuth sign-in for {}", server_url);
+    let metadata = discover(server_url)?;
+    let client = http_client()?;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .map_err(|e| format!("Failed to listen for the redirect: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to listen for the redirect: {}", e))?
+        .port();
+    let redirect_uri = format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH);
+
+    let registered = register_client(&client, &metadata, &redirect_uri)?;
+    let verifier = code_verifier();
+    let state = random_string(32);
+    let url = authorization_url(
+        &metadata,
+        &registered.client_id,
+        &redirect_uri,
+        &code_challenge(&verifier),
+        &state,
+        server_url,
+    )?;
+
+    open_url(url)?;
+    let code = wait_for_code(&listener, &state)?;
+
+    let response = request_tokens(
+        &client,
+        &metadata.token_endpoint,
+        &registered.client_id,
+        registered.client_secret.as_deref(),
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", &redirect_uri),
+            ("code_verifier", &verifier),
+            ("resource", server_url),
+        ],
+    )?;
+
+    let tokens = OAuthTokens {
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+        expires_at: response.expires_in.map(|secs| now_secs() + secs),
+        client_id: registered.client_id,
+        client_secret: registered.client_secret,
+        token_endpoint: metadata.token_endpoint,
+    };
+    store_tokens(server_url, &tokens)?;
+    info!("Signed in to {}", server_url);
+    Ok(tokens)
+}
+
+/// Runs the authorization flow for `server_url` in the system browser.
+pub fn authorize(server_url: &str) -> Result<OAuthTokens, String> {
+    authorize_with(server_url, crate::open_system_url)
+}
+
+/// Returns `tokens`, refreshed and stored again if the access token is about
+/// to expire.
+pub fn refresh_if_expired(server_url: &str, tokens: OAuthTokens) -> Result<OAuthTokens, String> {
+    if !tokens.is_expired() {
+        return Ok(tokens);
+    }
+
+    // Another proxy of the same server may have refreshed since `tokens` were
+    // loaded, and servers that rotate refresh tokens have invalidated ours then
+    let tokens = match load_tokens(server_url) {
+        Some(stored) if stored.expires_at > tokens.expires_at => stored,
+        _ => tokens,
+    };
+    if !tokens.is_expired() {
+        debug!("Using the tokens stored for {}", server_url);
+        return Ok(tokens);
+    }
+    let refresh_token = tokens
+        .refresh_token
+        .clone()
+        .ok_or_else(|| format!("The sign-in for {} has expired, sign in again", server_url))?;
+
+    info!("Refreshing access token for {}", server_url);
+    let response = request_tokens(
+        &http_client()?,
+        &tokens.token_endpoint,
+        &tokens.client_id,
+        tokens.client_secret.as_deref(),
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &refresh_token),
+            ("resource", server_url),
+        ],
+    )?;
+
+    let refreshed = OAuthTokens {
+        access_token: response.access_token,
+        // Servers that do not rotate refresh tokens leave it out
+        refresh_token: response.refresh_token.or(Some(refresh_token)),
+        expires_at: response.expires_in.map(|secs| now_secs() + secs),
+        ..tokens
+    };
+    store_tokens(server_url, &refreshed)?;
+    Ok(refreshed)
+}
+
+/// A usable access token for `server_url`, refreshed if needed.
+pub fn access_token(server_url: &str) -> Result<String, String> {
+    let tokens = load_tokens(server_url).ok_or_else(|| {
+        format!(
+            "Not signed in to {}, run `staten auth login` for its app",
+            server_url
+        )
+    })?;
+    Ok(refresh_if_expired(server_url, tokens)?.access_token)
+}
+
+fn oauth_server_url(app_name: &str) -> Result<String, String> {
+    let configs = crate::app::get_app_configs()?;
+    let (_, config) = configs
+        .iter()
+        .find(|(name, _)| name == app_name)
+        .ok_or_else(|| format!("No configuration available for '{}'", app_name))?;
+    match &config.remote {
+        Some(remote) if remote.oauth => Ok(remote.url.clone()),
+        _ => Err(format!("'{}' does not use OAuth", app_name)),
+    }
+}
+
+/// Signs in to `app_name`'s server in the browser. Blocks until the browser
+/// comes back, for up to `CALLBACK_TIMEOUT`.
+pub fn sign_in(app_name: &str) -> Result<String, String> {
+    let server_url = oauth_server_url(app_name)?;
+    authorize(&server_url)?;
+    Ok(format!("Signed in to {}", app_name))
+}
+
+#[tauri::command]
+pub async fn oauth_sign_in(app_name: String) -> Result<String, String> {
+    // Waiting for the browser must not hold up the UI
+    tauri::async_runtime::spawn_blocking(move || sign_in(&app_name))
+        .await
+        .map_err(|e| format!("Failed to sign in: {}", e))?
+}
+
+#[tauri::command]
+pub fn oauth_sign_out(app_name: &str) -> Result<String, String> {
+    sign_out(&oauth_server_url(app_name)?)?;
+    Ok(format!("Signed out of {}", app_name))
+}
+
+#[tauri::command]
+pub fn oauth_status(app_name: &str) -> Result<Value, String> {
+    let server_url = oauth_server_url(app_name)?;
+    let tokens = load_tokens(&server_url);
+    Ok(json!({
+        "signedIn": tokens.is_some(),
+        "expiresAt": tokens.as_ref().and_then(|t| t.expires_at),
+        "canRefresh": tokens.as_ref().is_some_and(|t| t.refresh_token.is_some())
+    }))
+}
@@ -0,0 +1,142 @@
+use crate::settings;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Onboarding steps, in the order the frontend walks through them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    EnvironmentReady,
+    ClientChosen,
+    StatenMcpInstalled,
+    FirstAppInstalled,
+}
+
+impl OnboardingStep {
+    pub fn all() -> Vec<OnboardingStep> {
+        vec![
+            OnboardingStep::EnvironmentReady,
+            OnboardingStep::ClientChosen,
+            OnboardingStep::StatenMcpInstalled,
+            OnboardingStep::FirstAppInstalled,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OnboardingStep::EnvironmentReady => "environment_ready",
+            OnboardingStep::ClientChosen => "client_chosen",
+            OnboardingStep::StatenMcpInstalled => "staten_mcp_installed",
+            OnboardingStep::FirstAppInstalled => "first_app_installed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        OnboardingStep::all()
+            .into_iter()
+            .find(|step| step.as_str() == s)
+    }
+}
+
+/// Step -> RFC 3339 time it was first completed
+pub type OnboardingProgress = BTreeMap<OnboardingStep, String>;
+
+pub(crate) fn timestamp() -> String {
+    time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_default()
+}
+
+/// Every step completed now, for installs that finished onboarding before
+/// steps were tracked.
+pub(crate) fn all_completed() -> OnboardingProgress {
+    let now = timestamp();
+    OnboardingStep::all()
+        .into_iter()
+        .map(|step| (step, now.clone()))
+        .collect()
+}
+
+/// Records a step as done. Steps keep the time they were first completed.
+pub fn complete_step(step: OnboardingStep) -> Result<(), String> {
+    let completed = settings::modify_settings(|current| {
+        if current.onboarding.contains_key(&step) {
+            return Ok(false);
+        }
+        current.onboarding.insert(step, timestamp());
+        Ok(true)
+    })?;
+
+    if completed {
+        info!("Onboarding step completed: {}", step.as_str());
+    }
+    Ok(())
+}
+
+/// Like `complete_step`, for hooks where onboarding must not fail the action.
+pub fn record_step(step: OnboardingStep) {
+    if let Err(e) = complete_step(step) {
+        warn!("Failed to record onboarding step {}: {}", step.as_str(), e);
+    }
+}
+
+pub fn complete_all() -> Result<(), String> {
+    settings::modify_settings(|current| {
+        for (step, completed_at) in all_completed() {
+            current.onboarding.entry(step).or_insert(completed_at);
+        }
+        Ok(())
+    })
+}
+
+pub fn reset() -> Result<(), String> {
+    settings::modify_settings(|current| {
+        current.onboarding.clear();
+        Ok(())
+    })
+}
+
+pub fn is_completed() -> Result<bool, String> {
+    let progress = settings::load_settings()?.onboarding;
+    Ok(OnboardingStep::all()
+        .iter()
+        .all(|step| progress.contains_key(step)))
+}
+
+/// The first step that is not done yet, or `None` when onboarding is finished.
+pub fn current_step(progress: &OnboardingProgress) -> Option<OnboardingStep> {
+    OnboardingStep::all()
+        .into_iter()
+        .find(|step| !progress.contains_key(step))
+}
+
+#[tauri::command]
+pub fn get_onboarding_state() -> Result<Value, String> {
+    let progress = settings::load_settings()?.onboarding;
+    let steps: Vec<Value> = OnboardingStep::all()
+        .into_iter()
+        .map(|step| {
+            json!({
+                "step": step.as_str(),
+                "completedAt": progress.get(&step)
+            })
+        })
+        .collect();
+    let current = current_step(&progress);
+
+    Ok(json!({
+        "steps": steps,
+        "currentStep": current.map(|step| step.as_str()),
+        "completed": current.is_none()
+    }))
+}
+
+#[tauri::command]
+pub fn advance_onboarding(step: &str) -> Result<Value, String> {
+    let step =
+        OnboardingStep::parse(step).ok_or_else(|| format!("Unknown onboarding step: {}", step))?;
+    complete_step(step)?;
+    get_onboarding_state()
+}
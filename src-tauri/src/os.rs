@@ -11,4 +11,15 @@ impl OSType {
     pub fn default() -> OSType {
         OSType::MacOS
     }
+
+    /// The OS Staten was built for.
+    pub fn current() -> OSType {
+        if cfg!(target_os = "windows") {
+            OSType::Windows
+        } else if cfg!(target_os = "macos") {
+            OSType::MacOS
+        } else {
+            OSType::Linux
+        }
+    }
 }
@@ -0,0 +1,110 @@
+use crate::app;
+use crate::clients::{self, ClientType, ConfigScope};
+use crate::settings;
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+// Projects are directories with MCP configs that are checked in next to the
+// code, such as `.cursor/mcp.json`. Staten remembers the ones it installed
+// into, and ones the user added, so they can be listed later.
+
+fn project_dir(path: &str) -> Result<PathBuf, String> {
+    match ConfigScope::parse(Some(path))? {
+        ConfigScope::Project(dir) => Ok(dir),
+        ConfigScope::User => Err(format!("Not a project directory: {}", path)),
+    }
+}
+
+fn add(dir: &Path) -> Result<bool, String> {
+    let dir = dir.to_string_lossy().to_string();
+    let added = settings::modify_settings(|current| {
+        if current.projects.contains(&dir) {
+            return Ok(false);
+        }
+        current.projects.push(dir.clone());
+        Ok(true)
+    })?;
+
+    if added {
+        info!("Added project {}", dir);
+    }
+    Ok(added)
+}
+
+/// Remembers a project after something was installed into it. Failing to
+/// remember it must not fail the install.
+pub fn remember_project(dir: &Path) {
+    if let Err(e) = add(dir) {
+        warn!("Failed to remember project {}: {}", dir.display(), e);
+    }
+}
+
+/// The project-level MCP configs that exist in `dir`, one per client.
+pub fn project_configs(dir: &Path) -> Vec<Value> {
+    let scope = ConfigScope::Project(dir.to_path_buf());
+
+    ClientType::all()
+        .into_iter()
+        .filter_map(|client| {
+            let path = clients::project_config_path(&client, dir).ok()?;
+            if !path.exists() {
+                return None;
+            }
+
+            let servers = match app::get_scoped_config(&client, &scope) {
+                Ok(config) => config["mcpServers"]
+                    .as_object()
+                    .map(|servers| servers.keys().cloned().collect())
+                    .unwrap_or_default(),
+                Err(e) => {
+                    warn!("Failed to read {}: {}", path.display(), e);
+                    Vec::new()
+                }
+            };
+
+            Some(json!({
+                "client": client.as_str(),
+                "path": path.to_string_lossy(),
+                "servers": servers
+            }))
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_projects() -> Result<Value, String> {
+    let projects: Vec<Value> = settings::load_settings()?
+        .projects
+        .iter()
+        .map(|path| {
+            let dir = PathBuf::from(path);
+            json!({
+                "path": path,
+                "exists": dir.is_dir(),
+                "configs": if dir.is_dir() { project_configs(&dir) } else { Vec::new() }
+            })
+        })
+        .collect();
+    Ok(Value::Array(projects))
+}
+
+#[tauri::command]
+pub fn add_project(path: String) -> Result<Value, String> {
+    add(&project_dir(&path)?)?;
+    list_projects()
+}
+
+#[tauri::command]
+pub fn remove_project(path: String) -> Result<Value, String> {
+    settings::modify_settings(|current| {
+        let before = current.projects.len();
+        current.projects.retain(|project| project != &path);
+        if current.projects.len() == before {
+            return Err(format!("Unknown project: {}", path));
+        }
+        Ok(())
+    })?;
+    info!("Removed project {}", path);
+    list_projects()
+}
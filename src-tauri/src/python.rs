@@ -0,0 +1,159 @@
+use crate::app;
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::Command;
+
+// Python interpreters for uvx apps. Apps that declare `requires-python` in the
+// registry get `--python <range>` in their args, and the interpreter is
+// installed with `uv python install` into Staten's data dir. These apps get a
+// uvx shim of their own that points uv at that dir, so they find it without a
+// download on first start and nothing is installed into the user's own uv
+// setup. Other uvx apps keep using the interpreters the user's uv manages.
+
+/// Where uv keeps the interpreters it installs for Staten.
+pub fn python_root() -> PathBuf {
+    if crate::environment::is_test_mode() {
+        return PathBuf::from("/test/.local/share/staten/python");
+    }
+
+    #[cfg(target_os = "windows")]
+    let root = dirs::data_local_dir()
+        .unwrap_or_default()
+        .join("staten")
+        .join("python");
+
+    #[cfg(not(target_os = "windows"))]
+    let root = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".local/share/staten/python");
+
+    root
+}
+
+/// Checks a `requires-python` value, such as `>=3.10,<3.13` or `3.12`, and
+/// returns it without whitespace so it can be passed as a single argument.
+pub fn parse_requirement(requirement: &str) -> Result<String, String> {
+    let clause = regex::Regex::new(r"^(===|==|!=|<=|>=|~=|<|>)?\d+(\.\d+)*(\.\*)?$").unwrap();
+    let compact: String = requirement.split_whitespace().collect();
+    if compact.is_empty() || !compact.split(',').all(|part| clause.is_match(part)) {
+        return Err(format!("Invalid requires-python: {}", requirement));
+    }
+    Ok(compact)
+}
+
+/// The uvx arguments that select the interpreter.
+pub fn python_args(requirement: &str) -> Vec<String> {
+    vec!["--python".to_string(), requirement.to_string()]
+}
+
+// `staten_pythons` points uv at Staten's interpreters, like the shim of apps
+// with a `requires-python` does
+fn uv_command(args: &[&str], staten_pythons: bool) -> Result<Command, String> {
+    let uv = crate::environment::get_uv_path()?;
+    let mut command = crate::environment::create_windowless_command(&uv);
+    command.args(args);
+    if staten_pythons {
+        command.env("UV_PYTHON_INSTALL_DIR", python_root());
+    }
+    Ok(command)
+}
+
+/// Installs an interpreter matching `requirement`, unless one is already there.
+pub fn install_python(requirement: &str) -> Result<(), String> {
+    if crate::environment::is_test_mode() {
+        return Ok(());
+    }
+
+    info!("Installing Python {} with uv", requirement);
+    let output = uv_command(&["python", "install", requirement], true)?
+        .output()
+        .map_err(|e| format!("Failed to run uv python install: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to install Python {}: {}",
+            requirement,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// The interpreter uv picks for `requirement`, or its default one for apps
+/// without a requirement.
+pub fn find_python(requirement: Option<&str>) -> Result<String, String> {
+    if crate::environment::is_test_mode() {
+        return Ok(format!("{}/cpython/bin/python3", python_root().display()));
+    }
+
+    let mut args = vec!["python", "find"];
+    args.extend(requirement);
+    let output = uv_command(&args, requirement.is_some())?
+        .output()
+        .map_err(|e| format!("Failed to run uv python find: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "No Python found for {}: {}",
+            requirement.unwrap_or("the default"),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn python_version(python: &str) -> Option<String> {
+    if crate::environment::is_test_mode() {
+        return None;
+    }
+
+    let output = crate::environment::create_windowless_command(python)
+        .arg("--version")
+        .output()
+        .ok()?;
+    // Older interpreters print the version to stderr
+    let version = [output.stdout, output.stderr]
+        .iter()
+        .map(|out| String::from_utf8_lossy(out).trim().to_string())
+        .find(|out| !out.is_empty())?;
+    Some(version.trim_start_matches("Python ").to_string())
+}
+
+/// Which interpreter each uvx app runs on.
+pub fn python_report() -> Result<Value, String> {
+    debug!("Building the Python report");
+    let apps: Vec<Value> = app::get_app_configs()?
+        .into_iter()
+        .filter(|(_, config)| config.runtime == "uvx")
+        .map(|(name, config)| {
+            let mut report = json!({
+                "app": name,
+                "requiresPython": config.python,
+            });
+            match find_python(config.python.as_deref()) {
+                Ok(python) => {
+                    report["version"] = json!(python_version(&python));
+                    report["python"] = json!(python);
+                }
+                Err(e) => {
+                    warn!("{}: {}", name, e);
+                    report["python"] = Value::Null;
+                    report["error"] = json!(e);
+                }
+            }
+            report
+        })
+        .collect();
+
+    Ok(json!({
+        "pythonDir": python_root(),
+        "apps": apps,
+    }))
+}
+
+#[tauri::command]
+pub async fn get_python_report() -> Result<Value, String> {
+    // Fetches the registry and runs uv once per app
+    tauri::async_runtime::spawn_blocking(python_report)
+        .await
+        .map_err(|e| format!("Failed to build the Python report: {}", e))?
+}
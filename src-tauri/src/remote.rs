@@ -0,0 +1,598 @@
+use crate::oauth::{self, OAuthTokens};
+use lazy_static::lazy_static;
+use log::{error, info, warn};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// MCP servers that run elsewhere and are reached over HTTP. Clients that can
+// only start local processes get `staten proxy`, which passes JSON-RPC lines
+// between its stdio and the remote server.
+
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// Headers for `staten proxy`, one `Name: value` per line. They usually carry
+/// credentials, which as arguments would show up in every user's `ps`.
+pub const PROXY_HEADERS_ENV: &str = "STATEN_PROXY_HEADERS";
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(30);
+
+lazy_static! {
+    // Responses of concurrent requests must not interleave on stdout
+    static ref STDOUT_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// How a remote server is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteTransport {
+    /// Streamable HTTP: every message is POSTed to one endpoint
+    Http,
+    /// The older HTTP+SSE transport: responses arrive on an event stream
+    Sse,
+}
+
+impl RemoteTransport {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RemoteTransport::Http => "http",
+            RemoteTransport::Sse => "sse",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "http" | "streamable-http" | "streamable_http" => Some(RemoteTransport::Http),
+            "sse" => Some(RemoteTransport::Sse),
+            _ => None,
+        }
+    }
+}
+
+/// A remote server. In Staten's config shape it is the entry
+/// `{"type": "http"|"sse", "url", "headers"}`, plus `"auth": "oauth"` for
+/// servers that need a signed in user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteServer {
+    pub transport: RemoteTransport,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    /// Whether requests carry an OAuth access token. Tokens expire, so these
+    /// servers always go through the proxy, which refreshes them.
+    pub oauth: bool,
+}
+
+impl RemoteServer {
+    /// Reads a server entry in Staten's shape. Local servers are `None`.
+    pub fn from_entry(entry: &Value) -> Option<Self> {
+        if entry.get("command").is_some() {
+            return None;
+        }
+        let url = entry.get("url")?.as_str()?.to_string();
+        let transport = match entry.get("type").and_then(|t| t.as_str()) {
+            Some(transport) => RemoteTransport::parse(transport)?,
+            None => RemoteTransport::Http,
+        };
+        let headers = entry
+            .get("headers")
+            .and_then(|h| h.as_object())
+            .map(|headers| {
+                headers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.as_str().unwrap_or("").to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(RemoteServer {
+            transport,
+            url,
+            headers,
+            oauth: entry.get("auth").and_then(|a| a.as_str()) == Some("oauth"),
+        })
+    }
+
+    pub fn to_entry(&self) -> Value {
+        let mut entry = Map::new();
+        entry.insert("type".to_string(), json!(self.transport.as_str()));
+        entry.insert("url".to_string(), json!(self.url));
+        if !self.headers.is_empty() {
+            entry.insert("headers".to_string(), json!(self.headers));
+        }
+        if self.oauth {
+            entry.insert("auth".to_string(), json!("oauth"));
+        }
+        Value::Object(entry)
+    }
+}
+
+/// The executable clients start the proxy with.
+pub fn proxy_command() -> String {
+    std::env::current_exe()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "staten".to_string())
+}
+
+/// The arguments that start `staten proxy` for `remote`. Its headers go in
+/// the environment, see `proxy_env`.
+pub fn proxy_args(remote: &RemoteServer) -> Vec<String> {
+    let mut args = vec![
+        "proxy".to_string(),
+        "--transport".to_string(),
+        remote.transport.as_str().to_string(),
+    ];
+    if remote.oauth {
+        args.push("--oauth".to_string());
+    }
+    args.push(remote.url.clone());
+    args
+}
+
+/// The environment that passes `remote`'s headers to `staten proxy`.
+pub fn proxy_env(remote: &RemoteServer) -> Map<String, Value> {
+    let mut env = Map::new();
+    if !remote.headers.is_empty() {
+        let headers: Vec<String> = remote
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+        env.insert(PROXY_HEADERS_ENV.to_string(), json!(headers.join("\n")));
+    }
+    env
+}
+
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    header
+        .split_once(':')
+        .filter(|(name, _)| !name.trim().is_empty())
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .ok_or_else(|| format!("Expected \"Name: value\", got: {}", header))
+}
+
+/// Reads headers as `proxy_env` writes them.
+pub fn parse_proxy_headers(headers: &str) -> Result<BTreeMap<String, String>, String> {
+    headers
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_header)
+        .collect()
+}
+
+/// Parses the arguments that follow `staten proxy`.
+pub fn parse_proxy_args(args: &[String]) -> Result<RemoteServer, String> {
+    let mut transport = RemoteTransport::Http;
+    let mut headers = BTreeMap::new();
+    let mut oauth = false;
+    let mut url = None;
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--transport" | "-t" => {
+                let value = iter.next().ok_or("--transport requires a value")?;
+                transport = RemoteTransport::parse(value).ok_or_else(|| {
+                    format!("Unknown transport: {} (expected http or sse)", value)
+                })?;
+            }
+            "--header" | "-H" => {
+                let value = iter.next().ok_or("--header requires a value")?;
+                let (name, value) = parse_header(value)?;
+                headers.insert(name, value);
+            }
+            "--oauth" => oauth = true,
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            _ if url.is_some() => return Err(format!("Unexpected argument: {}", arg)),
+            _ => url = Some(arg.clone()),
+        }
+    }
+
+    Ok(RemoteServer {
+        transport,
+        url: url.ok_or("A server URL is required")?,
+        headers,
+        oauth,
+    })
+}
+
+/// Recognizes an entry written by `proxy_args` and `proxy_env` and returns
+/// the server it proxies. The executable may have moved since, so any
+/// `staten` binary counts.
+pub fn parse_proxy_entry(
+    command: &str,
+    args: &[String],
+    env: Option<&Map<String, Value>>,
+) -> Option<RemoteServer> {
+    let is_staten = command == proxy_command()
+        || Path::new(command)
+            .file_stem()
+            .is_some_and(|stem| stem.eq_ignore_ascii_case("staten"));
+    let mut server = match args.split_first() {
+        Some((first, rest)) if is_staten && first == "proxy" => parse_proxy_args(rest).ok()?,
+        _ => return None,
+    };
+    if let Some(headers) = env
+        .and_then(|env| env.get(PROXY_HEADERS_ENV))
+        .and_then(|headers| headers.as_str())
+    {
+        server.headers.extend(parse_proxy_headers(headers).ok()?);
+    }
+    Some(server)
+}
+
+/// One server-sent event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub event: String,
+    pub data: String,
+}
+
+/// Reads server-sent events from `reader` and passes each to `on_event` until
+/// the stream ends or `on_event` returns false.
+pub fn read_events<R: BufRead>(
+    reader: R,
+    mut on_event: impl FnMut(SseEvent) -> bool,
+) -> Result<(), String> {
+    let mut event = String::new();
+    let mut data: Vec<String> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Failed to read event stream: {}", e))?;
+        if line.is_empty() {
+            if !data.is_empty() {
+                let name = if event.is_empty() { "message" } else { &event };
+                let dispatched = SseEvent {
+                    event: name.to_string(),
+                    data: data.join("\n"),
+                };
+                if !on_event(dispatched) {
+                    return Ok(());
+                }
+            }
+            event.clear();
+            data.clear();
+            continue;
+        }
+        if line.starts_with(':') {
+            continue;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_str(), ""),
+        };
+        match field {
+            "event" => event = value.to_string(),
+            "data" => data.push(value.to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn write_line(line: &str) {
+    let _lock = STDOUT_LOCK.lock().unwrap();
+    let mut stdout = std::io::stdout().lock();
+    if let Err(e) = writeln!(stdout, "{}", line).and_then(|_| stdout.flush()) {
+        error!("Failed to write to stdout: {}", e);
+    }
+}
+
+// Answers a request that could not be delivered, so the client is not left
+// waiting. Notifications have no id and get no answer.
+fn write_error(message: &str, error: &str) {
+    let Some(id) = serde_json::from_str::<Value>(message)
+        .ok()
+        .and_then(|message| message.get("id").cloned())
+    else {
+        return;
+    };
+    write_line(
+        &json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32603, "message": error }
+        })
+        .to_string(),
+    );
+}
+
+fn build_client(remote: &RemoteServer) -> Result<Client, String> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &remote.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| format!("Invalid header name {}: {}", name, e))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| format!("Invalid value for header {}: {}", name, e))?;
+        headers.insert(name, value);
+    }
+
+    // Tool calls and event streams can take as long as they take
+    Client::builder()
+        .default_headers(headers)
+        .timeout(None)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+// The OAuth tokens of a proxied server, refreshed as they expire
+struct TokenSource {
+    server_url: String,
+    tokens: Mutex<OAuthTokens>,
+}
+
+impl TokenSource {
+    fn load(remote: &RemoteServer) -> Result<Option<Arc<TokenSource>>, String> {
+        if !remote.oauth {
+            return Ok(None);
+        }
+        let tokens = oauth::load_tokens(&remote.url).ok_or_else(|| {
+            format!(
+                "Not signed in to {}, sign in from Staten or with `staten auth login <app>`",
+                remote.url
+            )
+        })?;
+        Ok(Some(Arc::new(TokenSource {
+            server_url: remote.url.clone(),
+            tokens: Mutex::new(tokens),
+        })))
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> Result<RequestBuilder, String> {
+        let mut tokens = self.tokens.lock().unwrap();
+        if tokens.is_expired() {
+            *tokens = oauth::refresh_if_expired(&self.server_url, tokens.clone())?;
+        }
+        Ok(request.header(AUTHORIZATION, format!("Bearer {}", tokens.access_token)))
+    }
+}
+
+fn authorized(
+    request: RequestBuilder,
+    auth: Option<&Arc<TokenSource>>,
+) -> Result<RequestBuilder, String> {
+    match auth {
+        Some(auth) => auth.authorize(request),
+        None => Ok(request),
+    }
+}
+
+fn stdin_lines() -> impl Iterator<Item = String> {
+    std::io::stdin()
+        .lines()
+        .map_while(|line| {
+            line.map_err(|e| error!("Failed to read from stdin: {}", e))
+                .ok()
+        })
+        .filter(|line| !line.trim().is_empty())
+}
+
+fn post_message(
+    client: &Client,
+    auth: Option<&Arc<TokenSource>>,
+    url: &str,
+    session: &Mutex<Option<String>>,
+    message: &str,
+) -> Result<(), String> {
+    let mut request = authorized(client.post(url), auth)?
+        .header(CONTENT_TYPE, "application/json")
+        .header(ACCEPT, "application/json, text/event-stream")
+        .body(message.to_string());
+    if let Some(id) = session.lock().unwrap().clone() {
+        request = request.header(SESSION_HEADER, id);
+    }
+
+    let response = request
+        .send()
+        .map_err(|e| format!("Failed to reach {}: {}", url, e))?;
+    if let Some(id) = response
+        .headers()
+        .get(SESSION_HEADER)
+        .and_then(|id| id.to_str().ok())
+    {
+        *session.lock().unwrap() = Some(id.to_string());
+    }
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("{} answered with {}", url, status));
+    }
+    if status == StatusCode::ACCEPTED {
+        return Ok(());
+    }
+
+    let is_stream = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|t| t.to_str().ok())
+        .is_some_and(|t| t.starts_with("text/event-stream"));
+    if is_stream {
+        read_events(BufReader::new(response), |event| {
+            if event.event == "message" {
+                write_line(&event.data);
+            }
+            true
+        })
+    } else {
+        let body = response
+            .text()
+            .map_err(|e| format!("Failed to read response from {}: {}", url, e))?;
+        if !body.trim().is_empty() {
+            write_line(body.trim());
+        }
+        Ok(())
+    }
+}
+
+fn run_http(remote: &RemoteServer) -> Result<(), String> {
+    let client = build_client(remote)?;
+    let auth = TokenSource::load(remote)?;
+    let session = Arc::new(Mutex::new(None));
+    let mut initialized = false;
+    let mut requests: Vec<std::thread::JoinHandle<()>> = Vec::new();
+
+    for message in stdin_lines() {
+        let parsed = serde_json::from_str::<Value>(&message).ok();
+        let method = parsed
+            .as_ref()
+            .and_then(|m| m.get("method"))
+            .and_then(|m| m.as_str());
+        let is_request = parsed.as_ref().is_some_and(|m| m.get("id").is_some()) && method.is_some();
+
+        // Until the handshake is done the server has not set the session, so
+        // messages go out one at a time and in order. Notifications and
+        // responses are quick and keep their order after that too.
+        if !initialized || !is_request {
+            initialized |= method == Some("notifications/initialized");
+            if let Err(e) = post_message(&client, auth.as_ref(), &remote.url, &session, &message) {
+                error!("{}", e);
+                write_error(&message, &e);
+            }
+            continue;
+        }
+
+        let client = client.clone();
+        let auth = auth.clone();
+        let session = Arc::clone(&session);
+        let url = remote.url.clone();
+        // A slow tool call must not hold up the requests after it
+        requests.retain(|request| !request.is_finished());
+        requests.push(std::thread::spawn(move || {
+            if let Err(e) = post_message(&client, auth.as_ref(), &url, &session, &message) {
+                error!("{}", e);
+                write_error(&message, &e);
+            }
+        }));
+    }
+
+    // Answers still on their way are delivered before the proxy exits
+    for request in requests {
+        let _ = request.join();
+    }
+
+    // Let the server drop the session; it expires on its own otherwise
+    if let Some(id) = session.lock().unwrap().clone() {
+        if let Ok(request) = authorized(client.delete(&remote.url), auth.as_ref()) {
+            let _ = request.header(SESSION_HEADER, id).send();
+        }
+    }
+    Ok(())
+}
+
+fn run_sse(remote: &RemoteServer) -> Result<(), String> {
+    let client = build_client(remote)?;
+    let auth = TokenSource::load(remote)?;
+    let response = authorized(client.get(&remote.url), auth.as_ref())?
+        .header(ACCEPT, "text/event-stream")
+        .send()
+        .map_err(|e| format!("Failed to reach {}: {}", remote.url, e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "{} answered with {}",
+            remote.url,
+            response.status()
+        ));
+    }
+    let base = response.url().clone();
+
+    let (endpoint_tx, endpoint_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut endpoint_tx = Some(endpoint_tx);
+        let result = read_events(BufReader::new(response), |event| {
+            match event.event.as_str() {
+                "endpoint" => {
+                    if let Some(tx) = endpoint_tx.take() {
+                        let _ = tx.send(event.data);
+                    }
+                }
+                "message" => write_line(&event.data),
+                other => warn!("Ignoring event {}", other),
+            }
+            true
+        });
+        // Without the stream there are no responses, so the client should see
+        // the server go away
+        match result {
+            Ok(()) => info!("Event stream closed by the server"),
+            Err(e) => error!("{}", e),
+        }
+        std::process::exit(1);
+    });
+
+    let endpoint = endpoint_rx
+        .recv_timeout(ENDPOINT_TIMEOUT)
+        .map_err(|_| format!("{} did not announce a message endpoint", remote.url))?;
+    let endpoint = base
+        .join(&endpoint)
+        .map_err(|e| format!("Invalid message endpoint {}: {}", endpoint, e))?;
+    info!("Posting messages to {}", endpoint);
+
+    for message in stdin_lines() {
+        let result = authorized(client.post(endpoint.clone()), auth.as_ref())
+            .and_then(|request| {
+                request
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(message.clone())
+                    .send()
+                    .map_err(|e| format!("Failed to reach {}: {}", endpoint, e))
+            })
+            .and_then(|response| match response.status() {
+                status if status.is_success() => Ok(()),
+                status => Err(format!("{} answered with {}", endpoint, status)),
+            });
+        if let Err(e) = result {
+            error!("{}", e);
+            write_error(&message, &e);
+        }
+    }
+    Ok(())
+}
+
+/// Runs `staten proxy`: JSON-RPC lines on stdin go to the remote server and
+/// its messages come back on stdout.
+pub fn run_proxy(args: &[String]) -> i32 {
+    if let Err(e) = crate::setup_logger("staten-proxy.log") {
+        eprintln!("Failed to initialize logger: {}", e);
+    }
+
+    let remote = parse_proxy_args(args).and_then(|mut remote| {
+        if let Ok(headers) = std::env::var(PROXY_HEADERS_ENV) {
+            remote.headers.extend(parse_proxy_headers(&headers)?);
+        }
+        Ok(remote)
+    });
+    let remote = match remote {
+        Ok(remote) => remote,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            eprintln!(
+                "Usage: staten proxy [--transport http|sse] [--header \"Name: value\"]... <url>"
+            );
+            return 2;
+        }
+    };
+    info!(
+        "Proxying stdio to {} over {}",
+        remote.url,
+        remote.transport.as_str()
+    );
+
+    let result = match remote.transport {
+        RemoteTransport::Http => run_http(&remote),
+        RemoteTransport::Sse => run_sse(&remote),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            error!("{}", e);
+            eprintln!("Error: {}", e);
+            1
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::clients::ClientType;
+use crate::environment::create_windowless_command;
+use log::{info, warn};
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long a client gets to quit on its own before it is killed
+const GRACEFUL_QUIT_TIMEOUT: Duration = Duration::from_secs(10);
+const FORCED_QUIT_TIMEOUT: Duration = Duration::from_secs(3);
+const LAUNCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The name the client's main process runs under: the app name on macOS and
+/// Linux, the executable on Windows.
+pub fn process_name(client: &ClientType) -> &'static str {
+    if cfg!(target_os = "windows") {
+        match client {
+            ClientType::Claude => "claude.exe",
+            ClientType::Cursor => "Cursor.exe",
+            ClientType::Windsurf => "Windsurf.exe",
+            ClientType::ClaudeCode => "claude.exe",
+            ClientType::VSCode => "Code.exe",
+            ClientType::Zed => "Zed.exe",
+            ClientType::Codex => "codex.exe",
+            ClientType::Goose => "Goose.exe",
+            ClientType::Continue => "cn.exe",
+        }
+    } else {
+        match client {
+            ClientType::Claude => "Claude",
+            ClientType::Cursor => "Cursor",
+            ClientType::Windsurf => "Windsurf",
+            ClientType::ClaudeCode => "claude",
+            ClientType::VSCode => "Visual Studio Code",
+            ClientType::Zed => "Zed",
+            ClientType::Codex => "codex",
+            ClientType::Goose => "Goose",
+            ClientType::Continue => "cn",
+        }
+    }
+}
+
+/// Polls `condition` until it holds or `timeout` passes. Returns whether it held.
+pub fn wait_for<F: FnMut() -> bool>(
+    mut condition: F,
+    timeout: Duration,
+    interval: Duration,
+) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if condition() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// Whether `tasklist /FO CSV /NH` output lists a process with this image name.
+pub fn tasklist_contains(output: &str, image_name: &str) -> bool {
+    output.lines().any(|line| {
+        line.split(',')
+            .next()
+            .map(|name| name.trim_matches('"'))
+            .is_some_and(|name| name.eq_ignore_ascii_case(image_name))
+    })
+}
+
+pub fn is_running(client: &ClientType) -> bool {
+    let name = process_name(client);
+
+    if cfg!(target_os = "windows") {
+        create_windowless_command("tasklist")
+            .args([
+                "/FI",
+                &format!("IMAGENAME eq {}", name),
+                "/FO",
+                "CSV",
+                "/NH",
+            ])
+            .output()
+            .map(|output| tasklist_contains(&String::from_utf8_lossy(&output.stdout), name))
+            .unwrap_or(false)
+    } else {
+        create_windowless_command("pgrep")
+            .args(["-x", name])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+}
+
+// Asks the client to quit the way a user would, so it can save its state
+fn request_quit(client: &ClientType) -> Result<(), String> {
+    let name = process_name(client);
+
+    let output = if cfg!(target_os = "macos") {
+        create_windowless_command("osascript")
+            .args(["-e", &format!("tell application \"{}\" to quit", name)])
+            .output()
+    } else if cfg!(target_os = "windows") {
+        // Without /F taskkill sends WM_CLOSE to the app's windows
+        create_windowless_command("taskkill")
+            .args(["/IM", name])
+            .output()
+    } else {
+        create_windowless_command("pkill")
+            .args(["-TERM", "-x", name])
+            .output()
+    };
+
+    output
+        .map(|_| ())
+        .map_err(|e| format!("Failed to ask {} to quit: {}", client.as_str(), e))
+}
+
+fn force_quit(client: &ClientType) -> Result<(), String> {
+    let name = process_name(client);
+
+    let output = if cfg!(target_os = "windows") {
+        create_windowless_command("taskkill")
+            .args(["/F", "/T", "/IM", name])
+            .output()
+    } else {
+        create_windowless_command("pkill")
+            .args(["-KILL", "-x", name])
+            .output()
+    };
+
+    output
+        .map(|_| ())
+        .map_err(|e| format!("Failed to kill {}: {}", client.as_str(), e))
+}
+
+fn launch(client: &ClientType) -> Result<(), String> {
+    if cfg!(target_os = "macos") {
+        let output = create_windowless_command("open")
+            .args(["-a", process_name(client)])
+            .output()
+            .map_err(|e| format!("Failed to relaunch {}: {}", client.as_str(), e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to relaunch {}: {}",
+                client.as_str(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        return Ok(());
+    }
+
+    let installation = crate::detect::find_installation(client)
+        .ok_or_else(|| format!("Could not find {} to relaunch", client.as_str()))?;
+
+    // Registry entries point at the install directory rather than the executable
+    let executable = if installation.path.is_dir() {
+        installation.path.join(process_name(client))
+    } else {
+        installation.path
+    };
+
+    info!(
+        "Launching {} from {}",
+        client.as_str(),
+        executable.display()
+    );
+    create_windowless_command(&executable.to_string_lossy())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to relaunch {}: {}", client.as_str(), e))
+}
+
+/// Quits a client gracefully, killing it only if it does not exit in time,
+/// then starts it again and waits until it is running.
+pub fn restart_client(client: &ClientType) -> Result<String, String> {
+    info!("Restarting {} app...", client.as_str());
+
+    if is_running(client) {
+        request_quit(client)?;
+
+        if !wait_for(|| !is_running(client), GRACEFUL_QUIT_TIMEOUT, POLL_INTERVAL) {
+            warn!(
+                "{} did not quit within {:?}, forcing it to close",
+                client.as_str(),
+                GRACEFUL_QUIT_TIMEOUT
+            );
+            force_quit(client)?;
+
+            if !wait_for(|| !is_running(client), FORCED_QUIT_TIMEOUT, POLL_INTERVAL) {
+                return Err(format!("{} could not be stopped", client.as_str()));
+            }
+        }
+    } else {
+        info!("{} is not running, starting it", client.as_str());
+    }
+
+    launch(client)?;
+
+    if !wait_for(|| is_running(client), LAUNCH_TIMEOUT, POLL_INTERVAL) {
+        return Err(format!(
+            "{} was started but is not running after {:?}",
+            client.as_str(),
+            LAUNCH_TIMEOUT
+        ));
+    }
+
+    info!("{} restarted", client.as_str());
+    Ok(format!("{} app restarted successfully", client.as_str()))
+}
@@ -0,0 +1,314 @@
+use lazy_static::lazy_static;
+use log::debug;
+use std::collections::HashMap;
+#[cfg(not(target_os = "windows"))]
+use std::io::Write;
+#[cfg(not(target_os = "windows"))]
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+lazy_static! {
+    // Tests keep secrets in memory instead of the OS credential store
+    static ref TEST_SECRETS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+}
+
+/// Replaces the OS credential store with an empty in-memory one, or restores
+/// it when `enabled` is false.
+pub fn set_test_secret_store(enabled: bool) {
+    let mut store = TEST_SECRETS.lock().unwrap();
+    *store = if enabled { Some(HashMap::new()) } else { None };
+}
+
+fn test_key(service: &str, account: Option<&str>) -> String {
+    format!("{}\n{}", service, account.unwrap_or_default())
+}
+
+/// Reads a secret from the OS credential store: the login keychain on macOS
+/// (`service`/`account` of a generic password) and Credential Manager on
+/// Windows, where `service` is the generic credential's target name.
+pub fn read_secret(service: &str, account: Option<&str>) -> Result<String, String> {
+    debug!("Reading secret for service {}", service);
+    if let Some(store) = TEST_SECRETS.lock().unwrap().as_ref() {
+        return store
+            .get(&test_key(service, account))
+            .cloned()
+            .ok_or_else(|| format!("No secret found for service '{}'", service));
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut command = Command::new("security");
+        command.args(["find-generic-password", "-s", service, "-w"]);
+        if let Some(account) = account {
+            command.args(["-a", account]);
+        }
+
+        let output = command
+            .output()
+            .map_err(|e| format!("Failed to query keychain: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("No keychain item found for service '{}'", service));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches('\n')
+            .to_string())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = account;
+        read_windows_credential(service)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        // libsecret's CLI, when present
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", service]);
+        if let Some(account) = account {
+            command.args(["account", account]);
+        }
+
+        let output = command
+            .output()
+            .map_err(|e| format!("Failed to query secret store: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("No secret found for service '{}'", service));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+/// Stores a secret in the OS credential store under `service` and `account`,
+/// replacing any previous one. `read_secret` reads it back.
+pub fn write_secret(service: &str, account: &str, secret: &str) -> Result<(), String> {
+    debug!("Writing secret for service {}", service);
+    if let Some(store) = TEST_SECRETS.lock().unwrap().as_mut() {
+        store.insert(test_key(service, Some(account)), secret.to_string());
+        return Ok(());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // Arguments show up in `ps`, so the command goes to `security -i` on
+        // stdin, with the secret hex encoded to keep it clear of quoting
+        let hex: String = secret.bytes().map(|b| format!("{:02x}", b)).collect();
+        let line = format!(
+            "add-generic-password -U -s {} -a {} -X {}\n",
+            quote_security_arg(service),
+            quote_security_arg(account),
+            hex
+        );
+        let mut child = Command::new("security")
+            .arg("-i")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to write to keychain: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(line.as_bytes())
+                .map_err(|e| format!("Failed to write to keychain: {}", e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("Failed to write to keychain: {}", e))?;
+        // Interactive mode exits cleanly even when the command failed
+        if !output.status.success() || !output.stderr.is_empty() {
+            return Err(format!(
+                "Failed to write keychain item for service '{}': {}",
+                service,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        write_windows_credential(service, account, secret)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        // secret-tool reads the secret from stdin
+        let mut child = Command::new("secret-tool")
+            .args([
+                "store",
+                &format!("--label={}", service),
+                "service",
+                service,
+                "account",
+                account,
+            ])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to write to secret store: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(secret.as_bytes())
+                .map_err(|e| format!("Failed to write to secret store: {}", e))?;
+        }
+        let status = child
+            .wait()
+            .map_err(|e| format!("Failed to write to secret store: {}", e))?;
+        if !status.success() {
+            return Err(format!("Failed to store secret for service '{}'", service));
+        }
+        Ok(())
+    }
+}
+
+// Quotes an argument for a command line read by `security -i`
+#[cfg(target_os = "macos")]
+fn quote_security_arg(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Removes a secret written by `write_secret`. Removing one that does not
+/// exist is not an error.
+pub fn delete_secret(service: &str, account: &str) -> Result<(), String> {
+    debug!("Deleting secret for service {}", service);
+    if let Some(store) = TEST_SECRETS.lock().unwrap().as_mut() {
+        store.remove(&test_key(service, Some(account)));
+        return Ok(());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // Fails when there is no such item, which is what we want anyway
+        Command::new("security")
+            .args(["delete-generic-password", "-s", service, "-a", account])
+            .output()
+            .map_err(|e| format!("Failed to delete from keychain: {}", e))?;
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = account;
+        delete_windows_credential(service)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        Command::new("secret-tool")
+            .args(["clear", "service", service, "account", account])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to delete from secret store: {}", e))?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn to_wide(value: &str) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    std::ffi::OsStr::new(value)
+        .encode_wide()
+        .chain(Some(0))
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn write_windows_credential(target: &str, account: &str, secret: &str) -> Result<(), String> {
+    use winapi::um::wincred::{
+        CredWriteW, CREDENTIALW, CRED_MAX_CREDENTIAL_BLOB_SIZE, CRED_PERSIST_LOCAL_MACHINE,
+        CRED_TYPE_GENERIC,
+    };
+
+    let mut blob = secret.as_bytes().to_vec();
+    if blob.len() > CRED_MAX_CREDENTIAL_BLOB_SIZE as usize {
+        return Err(format!(
+            "Secret for '{}' is too large for Credential Manager",
+            target
+        ));
+    }
+    let mut target_wide = to_wide(target);
+    let mut account_wide = to_wide(account);
+
+    let mut credential: CREDENTIALW = unsafe { std::mem::zeroed() };
+    credential.Type = CRED_TYPE_GENERIC;
+    credential.TargetName = target_wide.as_mut_ptr();
+    credential.UserName = account_wide.as_mut_ptr();
+    credential.CredentialBlobSize = blob.len() as u32;
+    credential.CredentialBlob = blob.as_mut_ptr();
+    credential.Persist = CRED_PERSIST_LOCAL_MACHINE;
+
+    let written = unsafe { CredWriteW(&mut credential, 0) };
+    if written == 0 {
+        return Err(format!(
+            "Failed to write credential '{}': {}",
+            target,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn delete_windows_credential(target: &str) -> Result<(), String> {
+    use winapi::um::wincred::{CredDeleteW, CRED_TYPE_GENERIC};
+
+    let target_wide = to_wide(target);
+    unsafe { CredDeleteW(target_wide.as_ptr(), CRED_TYPE_GENERIC, 0) };
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn read_windows_credential(target: &str) -> Result<String, String> {
+    use winapi::um::wincred::{CredFree, CredReadW, CRED_TYPE_GENERIC, PCREDENTIALW};
+
+    let target_wide = to_wide(target);
+    let mut credential: PCREDENTIALW = std::ptr::null_mut();
+
+    let found = unsafe { CredReadW(target_wide.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) };
+    if found == 0 || credential.is_null() {
+        return Err(format!("No credential found for '{}'", target));
+    }
+
+    let blob = unsafe {
+        let credential = &*credential;
+        std::slice::from_raw_parts(
+            credential.CredentialBlob,
+            credential.CredentialBlobSize as usize,
+        )
+        .to_vec()
+    };
+    unsafe { CredFree(credential as *mut _) };
+
+    // cmdkey and most Windows tools store the secret as UTF-16
+    match String::from_utf8(blob.clone()) {
+        Ok(secret) if !secret.contains('\0') => Ok(secret),
+        _ => {
+            let wide: Vec<u16> = blob
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16(&wide).map_err(|e| format!("Invalid credential encoding: {}", e))
+        }
+    }
+}
+
+const SECRET_NAME_MARKERS: &[&str] = &[
+    "KEY",
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "AUTH",
+    "PRIVATE",
+];
+
+/// Whether an env variable name looks like it holds a secret, e.g. `API_KEY`
+/// or `GITHUB_TOKEN`.
+pub fn is_secret_env_name(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    SECRET_NAME_MARKERS
+        .iter()
+        .any(|marker| upper.contains(marker))
+}
@@ -0,0 +1,364 @@
+use crate::onboarding::OnboardingProgress;
+use lazy_static::lazy_static;
+use log::{debug, info, warn, LevelFilter};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Version written by this build. Bump it together with a new step in `migrate`.
+pub const SETTINGS_SCHEMA_VERSION: u32 = 3;
+
+pub const DEFAULT_REGISTRY_URL: &str =
+    "https://raw.githubusercontent.com/aerugo/staten.ai-app-registry/refs/heads/main/apps.json";
+
+// Written by earlier versions before onboarding moved into the settings file
+const ONBOARDING_MARKER: &str = "onboarding_completed";
+
+lazy_static! {
+    static ref TEST_SETTINGS_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+    // Held from load to save, so concurrent updates don't undo each other
+    static ref SETTINGS_UPDATE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+pub fn set_test_settings_path(path: Option<PathBuf>) {
+    *TEST_SETTINGS_PATH.lock().unwrap() = path;
+}
+
+/// A user-chosen location for a client's config file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientPathOverride {
+    pub base_dir: String,
+    pub config_filename: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn to_level_filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// What Staten does when the updater finds a new release.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoUpdatePolicy {
+    /// Download, install and restart
+    #[default]
+    Install,
+    /// Only tell the frontend that an update is available
+    Notify,
+    /// Never check
+    Off,
+}
+
+fn default_registry_sources() -> Vec<String> {
+    vec![DEFAULT_REGISTRY_URL.to_string()]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    pub schema_version: u32,
+    /// Client used when a command does not name one
+    #[serde(default)]
+    pub default_client: Option<String>,
+    /// Registry URLs, tried in order until one responds
+    #[serde(default = "default_registry_sources")]
+    pub registry_sources: Vec<String>,
+    /// `None` keeps the build default (debug for dev builds, info for release)
+    #[serde(default)]
+    pub log_level: Option<LogLevel>,
+    #[serde(default)]
+    pub auto_update: AutoUpdatePolicy,
+    /// Keyed by client name
+    #[serde(default)]
+    pub client_path_overrides: BTreeMap<String, ClientPathOverride>,
+    /// Completed onboarding steps
+    #[serde(default)]
+    pub onboarding: OnboardingProgress,
+    /// Project directories with their own MCP configs
+    #[serde(default)]
+    pub projects: Vec<String>,
+    /// Node.js version for npx apps, `None` keeps the built-in default
+    #[serde(default)]
+    pub node_version: Option<String>,
+    /// Base URL uv releases are downloaded from, `None` uses GitHub
+    #[serde(default)]
+    pub uv_mirror: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            schema_version: SETTINGS_SCHEMA_VERSION,
+            default_client: None,
+            registry_sources: default_registry_sources(),
+            log_level: None,
+            auto_update: AutoUpdatePolicy::default(),
+            client_path_overrides: BTreeMap::new(),
+            onboarding: OnboardingProgress::new(),
+            projects: Vec::new(),
+            node_version: None,
+            uv_mirror: None,
+        }
+    }
+}
+
+/// `~/.staten/settings.json`, or `None` in test mode when no test path is set
+/// so tests never read the real settings.
+pub fn get_settings_path() -> Option<PathBuf> {
+    if let Some(path) = TEST_SETTINGS_PATH.lock().unwrap().clone() {
+        return Some(path);
+    }
+    if crate::environment::is_test_mode() {
+        return None;
+    }
+
+    dirs::home_dir().map(|home| home.join(".staten").join("settings.json"))
+}
+
+fn onboarding_marker_path(settings_path: &std::path::Path) -> Option<PathBuf> {
+    settings_path
+        .parent()
+        .map(|dir| dir.join(ONBOARDING_MARKER))
+}
+
+/// Brings a settings document of any earlier schema up to the current one.
+/// Version 1 (no `schemaVersion`) only held client path overrides, with
+/// onboarding tracked by a marker file next to the settings. Version 2 kept
+/// onboarding as a single `onboardingCompleted` flag.
+pub fn migrate(mut value: Value, onboarding_marker_exists: bool) -> Result<Value, String> {
+    if !value.is_object() {
+        return Err("Settings file is not a JSON object".to_string());
+    }
+
+    let mut version = value
+        .get("schemaVersion")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1) as u32;
+
+    if version > SETTINGS_SCHEMA_VERSION {
+        warn!(
+            "Settings schema {} is newer than {}, unknown fields are ignored",
+            version, SETTINGS_SCHEMA_VERSION
+        );
+        return Ok(value);
+    }
+
+    while version < SETTINGS_SCHEMA_VERSION {
+        match version {
+            1 => {
+                value["onboardingCompleted"] = json!(onboarding_marker_exists);
+            }
+            2 => {
+                let completed = value
+                    .as_object_mut()
+                    .and_then(|settings| settings.remove("onboardingCompleted"))
+                    .and_then(|flag| flag.as_bool())
+                    .unwrap_or(false);
+                if completed {
+                    value["onboarding"] = json!(crate::onboarding::all_completed());
+                }
+            }
+            _ => return Err(format!("No migration from settings schema {}", version)),
+        }
+        version += 1;
+        value["schemaVersion"] = json!(version);
+        info!("Migrated settings to schema {}", version);
+    }
+
+    Ok(value)
+}
+
+pub fn load_settings() -> Result<Settings, String> {
+    let path = match get_settings_path() {
+        Some(path) => path,
+        None => return Ok(Settings::default()),
+    };
+    let marker = onboarding_marker_path(&path);
+    let marker_exists = marker.as_ref().is_some_and(|m| m.exists());
+
+    let raw = if path.exists() {
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read settings: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings: {}", e))?
+    } else if marker_exists {
+        json!({})
+    } else {
+        return Ok(Settings::default());
+    };
+
+    let needs_migration = raw
+        .get("schemaVersion")
+        .and_then(|v| v.as_u64())
+        .is_none_or(|v| v < SETTINGS_SCHEMA_VERSION as u64);
+    let migrated = migrate(raw, marker_exists)?;
+    let settings: Settings =
+        serde_json::from_value(migrated).map_err(|e| format!("Failed to parse settings: {}", e))?;
+
+    if needs_migration {
+        save_settings(&settings)?;
+        if let Some(marker) = marker.filter(|m| m.exists()) {
+            if let Err(e) = fs::remove_file(&marker) {
+                warn!("Failed to remove {}: {}", marker.display(), e);
+            }
+        }
+    }
+
+    Ok(settings)
+}
+
+/// Writes the settings file. A no-op in test mode without a test path.
+pub fn save_settings(settings: &Settings) -> Result<(), String> {
+    let path = match get_settings_path() {
+        Some(path) => path,
+        None if crate::environment::is_test_mode() => return Ok(()),
+        None => return Err("Could not determine the home directory".to_string()),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write settings: {}", e))?;
+    debug!("Saved settings to {}", path.display());
+    Ok(())
+}
+
+/// Loads the settings, lets `change` edit them and saves the result if it
+/// differs, all under one process-wide lock. Nothing is saved when `change`
+/// fails.
+pub fn modify_settings<T, F>(change: F) -> Result<T, String>
+where
+    F: FnOnce(&mut Settings) -> Result<T, String>,
+{
+    let _lock = SETTINGS_UPDATE_LOCK.lock().unwrap();
+    let current = load_settings()?;
+    let mut updated = current.clone();
+    let result = change(&mut updated)?;
+    if updated != current {
+        save_settings(&updated)?;
+    }
+    Ok(result)
+}
+
+fn validate_settings(settings: &Settings) -> Result<(), String> {
+    if let Some(client) = &settings.default_client {
+        crate::clients::ClientType::from_str(client)
+            .ok_or_else(|| format!("Invalid default client: {}", client))?;
+    }
+    if settings.registry_sources.is_empty() {
+        return Err("At least one registry source is required".to_string());
+    }
+    if let Some(source) = settings
+        .registry_sources
+        .iter()
+        .find(|s| !s.starts_with("https://") && !s.starts_with("http://"))
+    {
+        return Err(format!(
+            "Registry source must be an http(s) URL: {}",
+            source
+        ));
+    }
+    if let Some(version) = &settings.node_version {
+        crate::node::normalize_version(version)?;
+    }
+    if let Some(mirror) = settings
+        .uv_mirror
+        .as_ref()
+        .filter(|m| !m.starts_with("https://") && !m.starts_with("http://"))
+    {
+        return Err(format!("uv mirror must be an http(s) URL: {}", mirror));
+    }
+    for client in settings.client_path_overrides.keys() {
+        crate::clients::ClientType::from_str(client)
+            .ok_or_else(|| format!("Invalid client in path overrides: {}", client))?;
+    }
+    Ok(())
+}
+
+/// Applies a partial update: top-level fields in `patch` replace the stored
+/// ones, everything else is kept. Unknown fields are an error.
+pub fn apply_settings_patch(patch: Value) -> Result<Settings, String> {
+    let patch = patch
+        .as_object()
+        .ok_or("Settings update must be a JSON object")?;
+
+    let (current, updated) = modify_settings(|settings| {
+        let current = settings.clone();
+        let mut merged = serde_json::to_value(&current)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        for (key, value) in patch {
+            if merged.get(key).is_none() {
+                return Err(format!("Unknown setting: {}", key));
+            }
+            if key != "schemaVersion" {
+                merged[key] = value.clone();
+            }
+        }
+
+        let updated: Settings =
+            serde_json::from_value(merged).map_err(|e| format!("Invalid settings: {}", e))?;
+        validate_settings(&updated)?;
+        *settings = updated.clone();
+        Ok((current, updated))
+    })?;
+
+    // Make the changes take effect without a restart
+    if updated.log_level != current.log_level {
+        log::set_max_level(effective_log_level(&updated));
+    }
+    if updated.client_path_overrides != current.client_path_overrides {
+        crate::clients::reload_client_path_configs();
+    }
+    if updated.default_client.is_some() && updated.default_client != current.default_client {
+        crate::onboarding::record_step(crate::onboarding::OnboardingStep::ClientChosen);
+    }
+    if updated.registry_sources != current.registry_sources {
+        *crate::app::APP_REGISTRY_CACHE.lock().unwrap() = None;
+    }
+
+    Ok(updated)
+}
+
+pub fn effective_log_level(settings: &Settings) -> LevelFilter {
+    match settings.log_level {
+        Some(level) => level.to_level_filter(),
+        None if cfg!(debug_assertions) => LevelFilter::Debug,
+        None => LevelFilter::Info,
+    }
+}
+
+#[tauri::command]
+pub fn get_settings() -> Result<Value, String> {
+    let settings = load_settings()?;
+    serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {}", e))
+}
+
+#[tauri::command]
+pub fn update_settings(patch: Value) -> Result<Value, String> {
+    info!("Updating settings");
+    let settings = apply_settings_patch(patch)?;
+    serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {}", e))
+}
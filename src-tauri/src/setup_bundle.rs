@@ -0,0 +1,573 @@
+use crate::app;
+use crate::clients::{self, ClientType};
+use crate::secrets::is_secret_env_name;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// A client's config location. Paths under the home directory are stored as
+/// `~/...` so they resolve on a machine with a different home.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathOverride {
+    pub base_dir: String,
+    pub config_filename: String,
+}
+
+/// A registry app installed in a client. Only the name and env are exported;
+/// the command is resolved again on the importing machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledApp {
+    pub mcp_key: String,
+    #[serde(default)]
+    pub env: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClientSetup {
+    #[serde(default)]
+    pub apps: BTreeMap<String, BundledApp>,
+    /// Entries that are not from the registry, copied as-is minus secrets
+    #[serde(default)]
+    pub custom_servers: Map<String, Value>,
+    #[serde(default)]
+    pub path_override: Option<PathOverride>,
+}
+
+/// Where a redacted secret came from. Secrets outside `env` are replaced by a
+/// placeholder in the entry, see `secret_placeholder`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretLocation {
+    #[default]
+    Env,
+    Header,
+    Arg,
+    Url,
+}
+
+impl SecretLocation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SecretLocation::Env => "env",
+            SecretLocation::Header => "header",
+            SecretLocation::Arg => "arg",
+            SecretLocation::Url => "url",
+        }
+    }
+}
+
+/// A secret that was left out of the bundle and must be supplied on import.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactedSecret {
+    pub id: String,
+    pub client: String,
+    pub mcp_key: String,
+    pub name: String,
+    #[serde(default)]
+    pub location: SecretLocation,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SetupBundle {
+    pub version: u32,
+    pub exported_at: String,
+    #[serde(default)]
+    pub clients: BTreeMap<String, ClientSetup>,
+    #[serde(default)]
+    pub redacted_secrets: Vec<RedactedSecret>,
+    #[serde(default)]
+    pub onboarding_completed: bool,
+}
+
+fn secret_id(client: &str, mcp_key: &str, name: &str) -> String {
+    format!("{}/{}/{}", client, mcp_key, name)
+}
+
+fn secret_placeholder(id: &str) -> String {
+    format!("{{{{staten-secret:{}}}}}", id)
+}
+
+fn redact_env(
+    env: &Map<String, Value>,
+    client: &str,
+    mcp_key: &str,
+    redacted: &mut Vec<RedactedSecret>,
+) -> Map<String, Value> {
+    let mut kept = Map::new();
+    for (name, value) in env {
+        if is_secret_env_name(name) {
+            redacted.push(RedactedSecret {
+                id: secret_id(client, mcp_key, name),
+                client: client.to_string(),
+                mcp_key: mcp_key.to_string(),
+                name: name.clone(),
+                location: SecretLocation::Env,
+            });
+        } else {
+            kept.insert(name.clone(), value.clone());
+        }
+    }
+    kept
+}
+
+// Flags, headers and query parameters are matched like env names, so
+// `--api-key` and `X-Api-Key` count as secrets
+fn is_secret_name(name: &str) -> bool {
+    let name = name.trim_start_matches('-').replace('-', "_");
+    name.eq_ignore_ascii_case("cookie") || is_secret_env_name(&name)
+}
+
+/// Redacts secrets outside `env` in a custom entry, replacing each with a
+/// placeholder that `fill_placeholders` swaps back on import.
+struct EntryRedactor<'a> {
+    client: &'a str,
+    mcp_key: &'a str,
+    redacted: &'a mut Vec<RedactedSecret>,
+}
+
+impl EntryRedactor<'_> {
+    fn redact(&mut self, location: SecretLocation, name: &str) -> String {
+        let base = secret_id(
+            self.client,
+            self.mcp_key,
+            &format!("{}:{}", location.as_str(), name),
+        );
+        let mut id = base.clone();
+        let mut n = 1;
+        while self.redacted.iter().any(|s| s.id == id) {
+            n += 1;
+            id = format!("{}#{}", base, n);
+        }
+        self.redacted.push(RedactedSecret {
+            id: id.clone(),
+            client: self.client.to_string(),
+            mcp_key: self.mcp_key.to_string(),
+            name: name.to_string(),
+            location,
+        });
+        secret_placeholder(&id)
+    }
+
+    // Replaces the password in the userinfo and secret-looking query values
+    fn redact_url(&mut self, url: &str) -> String {
+        let Some(scheme_end) = url.find("://") else {
+            return url.to_string();
+        };
+        let rest_start = scheme_end + 3;
+        let authority_end = url[rest_start..]
+            .find(['/', '?', '#'])
+            .map_or(url.len(), |i| rest_start + i);
+        let mut out = url[..rest_start].to_string();
+        let authority = &url[rest_start..authority_end];
+        match authority.rsplit_once('@') {
+            Some((userinfo, host)) => {
+                match userinfo.split_once(':') {
+                    Some((user, _)) => {
+                        let placeholder = self.redact(SecretLocation::Url, "password");
+                        out.push_str(&format!("{}:{}", user, placeholder));
+                    }
+                    None => out.push_str(userinfo),
+                }
+                out.push('@');
+                out.push_str(host);
+            }
+            None => out.push_str(authority),
+        }
+
+        let rest = &url[authority_end..];
+        let (before_fragment, fragment) = match rest.find('#') {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+        match before_fragment.split_once('?') {
+            Some((path, query)) => {
+                let params: Vec<String> = query
+                    .split('&')
+                    .map(|param| match param.split_once('=') {
+                        Some((name, _)) if is_secret_name(name) => {
+                            format!("{}={}", name, self.redact(SecretLocation::Url, name))
+                        }
+                        _ => param.to_string(),
+                    })
+                    .collect();
+                out.push_str(path);
+                out.push('?');
+                out.push_str(&params.join("&"));
+            }
+            None => out.push_str(before_fragment),
+        }
+        out.push_str(fragment);
+        out
+    }
+
+    fn redact_args(&mut self, args: &[Value]) -> Vec<Value> {
+        let mut redacted = Vec::new();
+        let mut secret_flag: Option<String> = None;
+        for arg in args {
+            let Some(arg) = arg.as_str() else {
+                redacted.push(arg.clone());
+                continue;
+            };
+            let value = match secret_flag.take() {
+                // `--token value`: the value is the next argument
+                Some(flag) if !arg.starts_with('-') => self.redact(SecretLocation::Arg, &flag),
+                _ if arg.starts_with('-') => match arg.split_once('=') {
+                    Some((flag, _)) if is_secret_name(flag) => {
+                        format!("{}={}", flag, self.redact(SecretLocation::Arg, flag))
+                    }
+                    Some((flag, value)) => format!("{}={}", flag, self.redact_url(value)),
+                    None => {
+                        if is_secret_name(arg) {
+                            secret_flag = Some(arg.to_string());
+                        }
+                        arg.to_string()
+                    }
+                },
+                _ => self.redact_url(arg),
+            };
+            redacted.push(Value::String(value));
+        }
+        redacted
+    }
+
+    fn redact_entry(&mut self, entry: &mut Value) {
+        if let Some(args) = entry.get("args").and_then(|a| a.as_array()).cloned() {
+            entry["args"] = Value::Array(self.redact_args(&args));
+        }
+        if let Some(url) = entry.get("url").and_then(|u| u.as_str()).map(String::from) {
+            entry["url"] = Value::String(self.redact_url(&url));
+        }
+        if let Some(headers) = entry.get_mut("headers").and_then(|h| h.as_object_mut()) {
+            for (name, value) in headers.iter_mut() {
+                if is_secret_name(name) {
+                    *value = Value::String(self.redact(SecretLocation::Header, name));
+                }
+            }
+        }
+    }
+}
+
+// Inverse of `to_portable_path`
+fn expand_portable_path(path: &str) -> PathBuf {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+        _ => return PathBuf::from(path),
+    };
+    let mut expanded = dirs::home_dir().unwrap_or_default();
+    for component in rest.split('/').filter(|c| !c.is_empty()) {
+        expanded.push(component);
+    }
+    expanded
+}
+
+fn to_portable_path(path: &Path) -> String {
+    let relative = dirs::home_dir()
+        .and_then(|home| path.strip_prefix(&home).ok().map(|rest| rest.to_path_buf()));
+    match relative {
+        Some(rest) => {
+            let components: Vec<String> = rest
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            if components.is_empty() {
+                "~".to_string()
+            } else {
+                format!("~/{}", components.join("/"))
+            }
+        }
+        None => path.to_string_lossy().to_string(),
+    }
+}
+
+fn exported_at() -> String {
+    time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_default()
+}
+
+pub fn build_setup_bundle() -> Result<SetupBundle, String> {
+    let registry = app::get_app_configs()?;
+    let mut bundle = SetupBundle {
+        version: BUNDLE_VERSION,
+        exported_at: exported_at(),
+        onboarding_completed: app::check_onboarding_completed()?,
+        ..Default::default()
+    };
+
+    for client in ClientType::all() {
+        let client_name = client.as_str().to_string();
+        let mut setup = ClientSetup::default();
+
+        let current_path = clients::get_client_path_config(&client)?;
+        if clients::get_default_client_path_config(&client).ok() != Some(current_path.clone()) {
+            setup.path_override = Some(PathOverride {
+                base_dir: to_portable_path(&current_path.base_dir),
+                config_filename: current_path.config_filename.clone(),
+            });
+        }
+
+        // A client that was never configured has nothing to export
+        let config_path = current_path.base_dir.join(&current_path.config_filename);
+        let servers = if config_path.exists() || crate::environment::is_test_mode() {
+            app::get_config(&client)?
+                .get("mcpServers")
+                .and_then(|v| v.as_object())
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            Map::new()
+        };
+
+        for (mcp_key, entry) in &servers {
+            match registry
+                .iter()
+                .find(|(_, config)| &config.mcp_key == mcp_key)
+            {
+                Some((app_name, config)) => {
+                    // Remote apps keep their values in the url and headers
+                    let env = app::installed_env(config, Some(entry))
+                        .as_object()
+                        .cloned()
+                        .unwrap_or_default();
+                    let env = redact_env(&env, &client_name, mcp_key, &mut bundle.redacted_secrets);
+                    setup.apps.insert(
+                        app_name.clone(),
+                        BundledApp {
+                            mcp_key: mcp_key.clone(),
+                            env,
+                        },
+                    );
+                }
+                None => {
+                    let mut entry = entry.clone();
+                    if let Some(env) = entry.get("env").and_then(|e| e.as_object()).cloned() {
+                        entry["env"] = Value::Object(redact_env(
+                            &env,
+                            &client_name,
+                            mcp_key,
+                            &mut bundle.redacted_secrets,
+                        ));
+                    }
+                    EntryRedactor {
+                        client: &client_name,
+                        mcp_key,
+                        redacted: &mut bundle.redacted_secrets,
+                    }
+                    .redact_entry(&mut entry);
+                    setup.custom_servers.insert(mcp_key.clone(), entry);
+                }
+            }
+        }
+
+        if !setup.apps.is_empty()
+            || !setup.custom_servers.is_empty()
+            || setup.path_override.is_some()
+        {
+            bundle.clients.insert(client_name, setup);
+        }
+    }
+
+    Ok(bundle)
+}
+
+fn fill_secrets(
+    env: &mut Map<String, Value>,
+    client: &str,
+    mcp_key: &str,
+    bundle: &SetupBundle,
+    secrets: &Map<String, Value>,
+) {
+    for secret in bundle
+        .redacted_secrets
+        .iter()
+        .filter(|s| s.client == client && s.mcp_key == mcp_key && s.location == SecretLocation::Env)
+    {
+        if let Some(value) = secrets.get(&secret.id) {
+            env.insert(secret.name.clone(), value.clone());
+        }
+    }
+}
+
+// Puts the values of secrets outside `env` back in place of their placeholders
+fn fill_placeholders(
+    value: &mut Value,
+    client: &str,
+    mcp_key: &str,
+    bundle: &SetupBundle,
+    secrets: &Map<String, Value>,
+) {
+    match value {
+        Value::String(text) => {
+            for secret in bundle.redacted_secrets.iter().filter(|s| {
+                s.client == client && s.mcp_key == mcp_key && s.location != SecretLocation::Env
+            }) {
+                let secret_value = match secrets.get(&secret.id) {
+                    Some(Value::String(v)) => v.clone(),
+                    Some(v) => v.to_string(),
+                    None => continue,
+                };
+                *text = text.replace(&secret_placeholder(&secret.id), &secret_value);
+            }
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| fill_placeholders(item, client, mcp_key, bundle, secrets)),
+        Value::Object(map) => map
+            .iter_mut()
+            .filter(|(key, _)| key.as_str() != "env")
+            .for_each(|(_, item)| fill_placeholders(item, client, mcp_key, bundle, secrets)),
+        _ => {}
+    }
+}
+
+/// Restores a bundle onto this machine. Every redacted secret must be present
+/// in `secrets`, keyed by its `id`.
+pub fn restore_setup_bundle(
+    bundle: &SetupBundle,
+    secrets: &Map<String, Value>,
+) -> Result<Value, String> {
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Setup bundle version {} is newer than this Staten supports",
+            bundle.version
+        ));
+    }
+
+    let missing: Vec<&str> = bundle
+        .redacted_secrets
+        .iter()
+        .filter(|s| !secrets.contains_key(&s.id))
+        .map(|s| s.id.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Missing values for secrets: {}",
+            missing.join(", ")
+        ));
+    }
+
+    let registry = app::get_app_configs()?;
+    let mut installed = Vec::new();
+    let mut skipped = Vec::new();
+    let mut failed = Vec::new();
+
+    for (client_name, setup) in &bundle.clients {
+        let client = ClientType::from_str(client_name)
+            .ok_or_else(|| format!("Invalid client in bundle: {}", client_name))?;
+
+        if let Some(path_override) = &setup.path_override {
+            // The directory may not exist on this machine; keep the default path then
+            let base_dir = expand_portable_path(&path_override.base_dir);
+            if let Err(e) = app::set_client_config_path(
+                client_name.clone(),
+                &base_dir.to_string_lossy(),
+                &path_override.config_filename,
+            ) {
+                warn!("Skipping path override for {}: {}", client_name, e);
+                failed.push(json!({ "client": client_name, "pathOverride": path_override.base_dir, "error": e }));
+            }
+        }
+
+        for (app_name, bundled) in &setup.apps {
+            // The registry on this machine may not have the app
+            if !registry.iter().any(|(name, _)| name == app_name) {
+                warn!(
+                    "Skipping {} for {}: not in the registry",
+                    app_name, client_name
+                );
+                skipped.push(format!("{}/{}", client_name, app_name));
+                continue;
+            }
+            let mut env = bundled.env.clone();
+            fill_secrets(&mut env, client_name, &bundled.mcp_key, bundle, secrets);
+
+            match app::install_app(app_name, Some(Value::Object(env)), client_name, None) {
+                Ok(_) => installed.push(format!("{}/{}", client_name, app_name)),
+                Err(e) => {
+                    warn!("Failed to import {} for {}: {}", app_name, client_name, e);
+                    failed.push(json!({ "client": client_name, "app": app_name, "error": e }));
+                }
+            }
+        }
+
+        if !setup.custom_servers.is_empty() {
+            let mut config_json = app::get_config(&client)?;
+            let servers = config_json
+                .get_mut("mcpServers")
+                .and_then(|v| v.as_object_mut())
+                .ok_or("Failed to find mcpServers in config")?;
+
+            for (mcp_key, entry) in &setup.custom_servers {
+                let mut entry = entry.clone();
+                if let Some(env) = entry.get_mut("env").and_then(|e| e.as_object_mut()) {
+                    fill_secrets(env, client_name, mcp_key, bundle, secrets);
+                }
+                fill_placeholders(&mut entry, client_name, mcp_key, bundle, secrets);
+                servers.insert(mcp_key.clone(), entry);
+                installed.push(format!("{}/{}", client_name, mcp_key));
+            }
+            app::save_config(&config_json, &client)?;
+        }
+    }
+
+    if bundle.onboarding_completed {
+        app::mark_onboarding_completed()?;
+    }
+
+    info!(
+        "Imported setup: {} entries, {} skipped, {} failures",
+        installed.len(),
+        skipped.len(),
+        failed.len()
+    );
+    Ok(json!({ "installed": installed, "skipped": skipped, "failed": failed }))
+}
+
+fn read_bundle(path: &str) -> Result<SetupBundle, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read setup bundle: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse setup bundle: {}", e))
+}
+
+#[tauri::command]
+pub fn export_setup(path: Option<String>) -> Result<Value, String> {
+    info!("Exporting setup");
+    let bundle = build_setup_bundle()?;
+    let bundle_json =
+        serde_json::to_value(&bundle).map_err(|e| format!("Failed to serialize setup: {}", e))?;
+
+    if let Some(path) = path {
+        let content = serde_json::to_string_pretty(&bundle_json)
+            .map_err(|e| format!("Failed to serialize setup: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write setup bundle: {}", e))?;
+        info!("Setup exported to {}", path);
+    }
+
+    Ok(bundle_json)
+}
+
+/// Lists the secrets the UI has to prompt for before calling `import_setup`.
+#[tauri::command]
+pub fn get_setup_import_secrets(path: &str) -> Result<Value, String> {
+    let bundle = read_bundle(path)?;
+    serde_json::to_value(&bundle.redacted_secrets)
+        .map_err(|e| format!("Failed to serialize secrets: {}", e))
+}
+
+#[tauri::command]
+pub async fn import_setup(path: String, secrets: Option<Value>) -> Result<Value, String> {
+    info!("Importing setup from {}", path);
+    let bundle = read_bundle(&path)?;
+    let secrets = secrets
+        .and_then(|s| s.as_object().cloned())
+        .unwrap_or_default();
+    // Installing the apps may download binaries and runtimes
+    tauri::async_runtime::spawn_blocking(move || restore_setup_bundle(&bundle, &secrets))
+        .await
+        .map_err(|e| format!("Failed to import setup: {}", e))?
+}
@@ -0,0 +1,218 @@
+use crate::download::{self, ArchiveKind};
+use log::{debug, info};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Staten's own uv. A pinned release is downloaded for the platform, checked
+// against the SHA-256 in the manifest bundled with the build, and uv and uvx
+// are unpacked into Staten's bin dir. Unlike uv's install script this never
+// edits shell profiles, so the user's PATH and any uv they have are untouched.
+//
+// uv-manifest.json is generated by scripts/update-uv-manifest.sh from the
+// checksums published with the release.
+
+const UV_MANIFEST: &str = include_str!("../uv-manifest.json");
+
+const DEFAULT_UV_MIRROR: &str = "https://github.com/astral-sh/uv/releases/download";
+
+/// Overrides where uv releases are downloaded from, ahead of the `uvMirror`
+/// setting. Takes the same `<version>/<archive>` layout as GitHub releases.
+pub const UV_MIRROR_ENV: &str = "STATEN_UV_MIRROR";
+
+// Records which release is in the bin dir, so a new pin replaces it
+const VERSION_FILE: &str = ".uv-version";
+
+static TEST_UV_DIR: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+static TEST_UV_MANIFEST: Lazy<Mutex<Option<UvManifest>>> = Lazy::new(|| Mutex::new(None));
+
+/// The pinned uv release and the SHA-256 of its archive per target triple
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UvManifest {
+    pub version: String,
+    pub sha256: BTreeMap<String, String>,
+}
+
+#[cfg(feature = "test-utils")]
+pub fn set_test_uv_dir(dir: Option<PathBuf>) {
+    *TEST_UV_DIR.lock().unwrap() = dir;
+}
+
+#[cfg(feature = "test-utils")]
+pub fn set_test_uv_manifest(manifest: Option<UvManifest>) {
+    *TEST_UV_MANIFEST.lock().unwrap() = manifest;
+}
+
+/// Staten's bin dir, which also holds the npx and uvx shims.
+pub fn uv_bin_dir() -> PathBuf {
+    if let Some(dir) = TEST_UV_DIR.lock().unwrap().clone() {
+        return dir;
+    }
+    if crate::environment::is_test_mode() {
+        return PathBuf::from("/test/.local/share/staten/bin");
+    }
+
+    #[cfg(target_os = "windows")]
+    let dir = dirs::data_local_dir()
+        .unwrap_or_default()
+        .join("staten")
+        .join("bin");
+
+    #[cfg(not(target_os = "windows"))]
+    let dir = dirs::home_dir()
+        .unwrap_or_default()
+        .join(".local/share/staten/bin");
+
+    dir
+}
+
+/// Paths of Staten's uv and uvx, whether or not they are installed.
+pub fn uv_paths() -> (PathBuf, PathBuf) {
+    let dir = uv_bin_dir();
+    let suffix = std::env::consts::EXE_SUFFIX;
+    (
+        dir.join(format!("uv{}", suffix)),
+        dir.join(format!("uvx{}", suffix)),
+    )
+}
+
+/// Reads a manifest and checks that every checksum is a SHA-256.
+pub fn parse_manifest(manifest: &str) -> Result<UvManifest, String> {
+    let manifest: UvManifest =
+        serde_json::from_str(manifest).map_err(|e| format!("Invalid uv manifest: {}", e))?;
+    if manifest.version.is_empty() || manifest.version.contains(['/', '\\']) {
+        return Err(format!("Invalid uv version: {}", manifest.version));
+    }
+    if let Some((target, _)) = manifest.sha256.iter().find(|(_, checksum)| {
+        checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit())
+    }) {
+        return Err(format!("Invalid uv checksum for {}", target));
+    }
+    Ok(manifest)
+}
+
+pub fn uv_manifest() -> Result<UvManifest, String> {
+    if let Some(manifest) = TEST_UV_MANIFEST.lock().unwrap().clone() {
+        return Ok(manifest);
+    }
+    parse_manifest(UV_MANIFEST)
+}
+
+/// uv's target triple for an OS and architecture as `std::env::consts`
+/// spells them, such as `aarch64-apple-darwin`.
+pub fn target_triple(os: &str, arch: &str) -> Option<String> {
+    let arch = match arch {
+        "x86_64" | "aarch64" => arch,
+        _ => return None,
+    };
+    let vendor_os = match os {
+        "macos" => "apple-darwin",
+        "linux" => "unknown-linux-gnu",
+        "windows" => "pc-windows-msvc",
+        _ => return None,
+    };
+    Some(format!("{}-{}", arch, vendor_os))
+}
+
+fn archive_kind() -> ArchiveKind {
+    if cfg!(target_os = "windows") {
+        ArchiveKind::Zip
+    } else {
+        ArchiveKind::TarGz
+    }
+}
+
+fn uv_mirror() -> String {
+    let setting = crate::settings::load_settings()
+        .ok()
+        .and_then(|settings| settings.uv_mirror);
+    std::env::var(UV_MIRROR_ENV)
+        .ok()
+        .or(setting)
+        .filter(|mirror| !mirror.trim().is_empty())
+        .map(|mirror| mirror.trim().trim_end_matches('/').to_string())
+        .unwrap_or_else(|| DEFAULT_UV_MIRROR.to_string())
+}
+
+/// The version of Staten's uv, if it is installed.
+pub fn installed_uv_version() -> Option<String> {
+    let (uv, uvx) = uv_paths();
+    if !uv.is_file() || !uvx.is_file() {
+        return None;
+    }
+    fs::read_to_string(uv_bin_dir().join(VERSION_FILE))
+        .ok()
+        .map(|version| version.trim().to_string())
+}
+
+/// Downloads, verifies and unpacks the pinned uv release unless it is
+/// already there, and returns the paths of uv and uvx.
+pub fn install_uv() -> Result<(PathBuf, PathBuf), String> {
+    let manifest = uv_manifest()?;
+    let (uv, uvx) = uv_paths();
+    if installed_uv_version().as_deref() == Some(manifest.version.as_str()) {
+        debug!("uv {} is already installed", manifest.version);
+        return Ok((uv, uvx));
+    }
+
+    let target = target_triple(std::env::consts::OS, std::env::consts::ARCH)
+        .ok_or_else(|| format!("uv has no build for {}", download::platform_key()))?;
+    let expected = manifest.sha256.get(&target).ok_or_else(|| {
+        format!(
+            "No checksum for uv {} on {} in this build",
+            manifest.version, target
+        )
+    })?;
+    let archive = match archive_kind() {
+        ArchiveKind::Zip => format!("uv-{}.zip", target),
+        _ => format!("uv-{}.tar.gz", target),
+    };
+
+    let url = format!("{}/{}/{}", uv_mirror(), manifest.version, archive);
+    info!("Downloading uv {} from {}", manifest.version, url);
+    let bytes = download::fetch(&url)?;
+    let checksum = download::sha256_hex(&bytes);
+    if checksum != expected.to_lowercase() {
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            archive,
+            expected.to_lowercase(),
+            checksum
+        ));
+    }
+
+    let dir = uv_bin_dir();
+    let partial = dir.join(format!(".uv-{}.partial", manifest.version));
+    if partial.exists() {
+        fs::remove_dir_all(&partial).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(&partial)
+        .map_err(|e| format!("Failed to create {}: {}", partial.display(), e))?;
+
+    // The tar.gz builds keep the binaries in a uv-<target> directory, the
+    // zip builds at the top
+    let moved = download::unpack_archive(&bytes, archive_kind(), &partial).and_then(|_| {
+        for dest in [&uv, &uvx] {
+            let name = dest.file_name().unwrap_or_default();
+            let unpacked = [
+                partial.join(format!("uv-{}", target)).join(name),
+                partial.join(name),
+            ]
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or_else(|| format!("{} is missing from {}", name.to_string_lossy(), archive))?;
+            download::mark_executable(&unpacked)?;
+            fs::rename(&unpacked, dest)
+                .map_err(|e| format!("Failed to move {} in place: {}", dest.display(), e))?;
+        }
+        fs::write(dir.join(VERSION_FILE), &manifest.version).map_err(|e| e.to_string())
+    });
+    let _ = fs::remove_dir_all(&partial);
+    moved?;
+
+    info!("Installed uv {} in {}", manifest.version, dir.display());
+    Ok((uv, uvx))
+}
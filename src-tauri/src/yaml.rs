@@ -0,0 +1,85 @@
+use serde_json::Value;
+
+// YAML config files of agents. Edits replace a single top-level key's block
+// and keep every other line, comments included, as it was. Unlike `jsonc`,
+// the block is rewritten whole rather than entry by entry.
+
+/// Parses YAML into a value. An empty document is an empty mapping.
+pub fn parse(text: &str) -> Result<Value, String> {
+    if text.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    let value: Value = serde_yaml::from_str(text).map_err(|e| e.to_string())?;
+    Ok(match value {
+        Value::Null => Value::Object(Default::default()),
+        value => value,
+    })
+}
+
+/// Renders a whole document.
+pub fn render(value: &Value) -> Result<String, String> {
+    serde_yaml::to_string(value).map_err(|e| e.to_string())
+}
+
+fn is_key_line(line: &str, key: &str) -> bool {
+    [
+        key.to_string(),
+        format!("\"{}\"", key),
+        format!("'{}'", key),
+    ]
+    .iter()
+    .any(|name| {
+        line.strip_prefix(name.as_str())
+            .is_some_and(|rest| rest.trim_start().starts_with(':'))
+    })
+}
+
+// Lines that continue the block of the top-level key above them
+fn continues_block(line: &str) -> bool {
+    line.trim().is_empty()
+        || line.starts_with([' ', '\t', '#'])
+        || (line.starts_with('-') && !line.starts_with("---"))
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// Sets top-level key `key` of the YAML document `text` to `value`, leaving
+/// every line outside that key's block untouched. The block itself is
+/// rendered anew, so comments inside it are dropped.
+pub fn set_member(text: &str, key: &str, value: &Value) -> Result<String, String> {
+    let mut member = serde_json::Map::new();
+    member.insert(key.to_string(), value.clone());
+    let rendered = render(&Value::Object(member))?;
+
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let Some(start) = lines.iter().position(|line| is_key_line(line, key)) else {
+        let mut text = text.to_string();
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&rendered);
+        return Ok(text);
+    };
+
+    let mut end = start + 1;
+    while end < lines.len() && continues_block(lines[end]) {
+        end += 1;
+    }
+    // Blank lines and unindented comments at the end describe what follows
+    while end > start + 1
+        && is_blank_or_comment(lines[end - 1])
+        && !lines[end - 1].starts_with([' ', '\t'])
+    {
+        end -= 1;
+    }
+
+    Ok(format!(
+        "{}{}{}",
+        lines[..start].concat(),
+        rendered,
+        lines[end..].concat()
+    ))
+}
@@ -85,7 +85,7 @@ fn test_install_and_uninstall() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Test installation
-    let install_result = app::install("Browser", None, ClientType::Claude.as_str());
+    let install_result = app::install_app("Browser", None, ClientType::Claude.as_str(), None);
     assert!(
         install_result.is_ok(),
         "Install failed: {:?}",
@@ -97,7 +97,7 @@ fn test_install_and_uninstall() {
     assert!(is_installed, "Browser should be installed");
 
     // Test uninstallation
-    let uninstall_result = app::uninstall("Browser", ClientType::Claude.as_str());
+    let uninstall_result = app::uninstall("Browser", ClientType::Claude.as_str(), None);
     assert!(
         uninstall_result.is_ok(),
         "Uninstall failed: {:?}",
@@ -137,7 +137,7 @@ fn test_app_env_operations() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Install app first
-    app::install("Browser", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Browser", None, ClientType::Claude.as_str(), None).unwrap();
 
     // Test saving env values
     let env_values = json!({
@@ -188,16 +188,16 @@ fn test_app_statuses() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Get initial statuses
-    let initial_statuses = app::get_app_statuses(ClientType::Claude.as_str()).unwrap();
+    let initial_statuses = app::app_statuses(ClientType::Claude.as_str(), None).unwrap();
     assert!(initial_statuses["installed"].is_object());
     assert!(initial_statuses["configured"].is_object());
 
     // Install an app
-    app::install("Browser", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Browser", None, ClientType::Claude.as_str(), None).unwrap();
     thread::sleep(Duration::from_millis(100));
 
     // Check updated statuses
-    let updated_statuses = app::get_app_statuses(ClientType::Claude.as_str()).unwrap();
+    let updated_statuses = app::app_statuses(ClientType::Claude.as_str(), None).unwrap();
     assert!(
         updated_statuses["installed"]["Browser"].as_bool().unwrap(),
         "Browser should be marked as installed"
@@ -272,10 +272,11 @@ fn test_install_with_env_vars() {
         "TEST_ENV": "test_value",
         "DEBUG": "true"
     });
-    let install_result = app::install(
+    let install_result = app::install_app(
         "Browser",
         Some(env_vars.clone()),
         ClientType::Claude.as_str(),
+        None,
     );
     assert!(
         install_result.is_ok(),
@@ -320,8 +321,8 @@ fn test_multiple_apps() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Install multiple apps
-    app::install("Browser", None, ClientType::Claude.as_str()).unwrap();
-    app::install("Time", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("Browser", None, ClientType::Claude.as_str(), None).unwrap();
+    app::install_app("Time", None, ClientType::Claude.as_str(), None).unwrap();
 
     // Verify both are installed
     assert!(
@@ -334,12 +335,12 @@ fn test_multiple_apps() {
     );
 
     // Check app statuses
-    let statuses = app::get_app_statuses(ClientType::Claude.as_str()).unwrap();
+    let statuses = app::app_statuses(ClientType::Claude.as_str(), None).unwrap();
     assert!(statuses["installed"]["Browser"].as_bool().unwrap());
     assert!(statuses["installed"]["Time"].as_bool().unwrap());
 
     // Uninstall one app
-    app::uninstall("Browser", ClientType::Claude.as_str()).unwrap();
+    app::uninstall("Browser", ClientType::Claude.as_str(), None).unwrap();
     assert!(
         !app::is_installed("Browser", ClientType::Claude.as_str()).unwrap(),
         "Browser should be uninstalled"
@@ -409,7 +410,7 @@ fn test_env_var_replacement_during_install() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Install app
-    app::install("EnvTest", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("EnvTest", None, ClientType::Claude.as_str(), None).unwrap();
 
     // Get the config directly to verify args
     let config = app::get_config(&ClientType::Claude).unwrap();
@@ -517,7 +518,7 @@ fn test_complex_env_var_replacements() {
     app::set_test_config_path(Some(config_path.clone()));
 
     // Install app
-    app::install("ComplexEnvTest", None, ClientType::Claude.as_str()).unwrap();
+    app::install_app("ComplexEnvTest", None, ClientType::Claude.as_str(), None).unwrap();
 
     // Get the config directly to verify args
     let config = app::get_config(&ClientType::Claude).unwrap();
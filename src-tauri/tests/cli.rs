@@ -0,0 +1,101 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    cli,
+    clients::ClientType,
+    environment,
+};
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_unknown_command_starts_gui() {
+    assert_eq!(cli::run_cli(&[]), None);
+    assert_eq!(cli::run_cli(&args(&["-psn_0_12345"])), None);
+}
+
+#[test]
+#[serial]
+fn test_cli_install_env_and_uninstall() {
+    environment::set_test_mode(true);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([{
+            "name": "Kolada",
+            "description": "Municipal key figures",
+            "config": {
+                "mcpKey": "kolada",
+                "runtime": "uvx",
+                "args": ["kolada-mcp"]
+            }
+        }]));
+    }
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("mcp.json");
+    std::fs::write(&config_path, r#"{"mcpServers": {}}"#).unwrap();
+    app::set_test_config_path(Some(config_path));
+
+    let code = cli::run_cli(&args(&[
+        "install",
+        "Kolada",
+        "--client",
+        "Cursor",
+        "--env",
+        "TOKEN=abc",
+    ]));
+    assert_eq!(code, Some(0));
+    assert!(app::is_installed("Kolada", ClientType::Cursor.as_str()).unwrap());
+    assert_eq!(
+        app::get_app_env("Kolada", "Cursor").unwrap(),
+        json!({ "TOKEN": "abc" })
+    );
+
+    let code = cli::run_cli(&args(&[
+        "env",
+        "set",
+        "Kolada",
+        "TOKEN=def",
+        "-c",
+        "Cursor",
+    ]));
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        app::get_app_env("Kolada", "Cursor").unwrap()["TOKEN"],
+        "def"
+    );
+
+    assert_eq!(cli::run_cli(&args(&["status", "--json"])), Some(0));
+    assert_eq!(
+        cli::run_cli(&args(&["list", "--client", "Cursor"])),
+        Some(0)
+    );
+
+    // Bad input is reported through the exit code
+    assert_eq!(
+        cli::run_cli(&args(&["install", "Kolada", "--client", "Nope"])),
+        Some(1)
+    );
+    assert_eq!(
+        cli::run_cli(&args(&["env", "set", "Kolada", "NOVALUE"])),
+        Some(1)
+    );
+
+    assert_eq!(
+        cli::run_cli(&args(&["uninstall", "Kolada", "--client", "Cursor"])),
+        Some(0)
+    );
+    assert!(!app::is_installed("Kolada", ClientType::Cursor.as_str()).unwrap());
+
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
@@ -0,0 +1,600 @@
+mod common;
+
+use serde_json::{json, Value};
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    client_format,
+    clients::{ClientType, ConfigScope},
+    environment, settings,
+};
+
+fn set_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = Some(json!([{
+        "name": "Kolada",
+        "description": "Municipal key figures",
+        "config": {
+            "mcpKey": "kolada",
+            "runtime": "uvx",
+            "args": ["kolada-mcp"]
+        }
+    }]));
+}
+
+fn clear_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = None;
+}
+
+#[test]
+fn test_claude_code_entries_round_trip() {
+    let native = json!({
+        "numStartups": 4,
+        "mcpServers": {
+            "kolada": { "type": "stdio", "command": "uvx", "args": ["kolada-mcp"] },
+            "remote": { "type": "http", "url": "https://example.com/mcp" }
+        }
+    });
+
+    let staten = client_format::from_client_format(&ClientType::ClaudeCode, native.clone());
+    assert!(staten["mcpServers"]["kolada"].get("type").is_none());
+    assert_eq!(staten["mcpServers"]["remote"]["type"], "http");
+    assert_eq!(staten["numStartups"], 4);
+
+    assert_eq!(
+        client_format::to_client_format(&ClientType::ClaudeCode, &staten),
+        native
+    );
+}
+
+#[test]
+fn test_other_clients_are_left_as_is() {
+    let config = json!({
+        "mcpServers": { "kolada": { "command": "uvx", "args": ["kolada-mcp"] } }
+    });
+    assert_eq!(
+        client_format::to_client_format(&ClientType::Cursor, &config),
+        config
+    );
+    assert_eq!(
+        client_format::from_client_format(&ClientType::Cursor, config.clone()),
+        config
+    );
+}
+
+#[test]
+fn test_config_scope_parse() {
+    assert_eq!(ConfigScope::parse(None).unwrap(), ConfigScope::User);
+    assert_eq!(ConfigScope::parse(Some("user")).unwrap(), ConfigScope::User);
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dir = temp_dir.path().to_string_lossy().to_string();
+    assert_eq!(
+        ConfigScope::parse(Some(&dir)).unwrap(),
+        ConfigScope::Project(temp_dir.path().to_path_buf())
+    );
+    assert!(ConfigScope::parse(Some("relative/dir")).is_err());
+    assert!(ConfigScope::parse(Some(&format!("{}/missing", dir))).is_err());
+}
+
+#[test]
+#[serial]
+fn test_claude_code_project_scope() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let project_dir = tempfile::tempdir().unwrap();
+    let scope = Some(project_dir.path().to_string_lossy().to_string());
+
+    app::install_app("Kolada", None, "ClaudeCode", scope.clone()).unwrap();
+
+    let written: Value = serde_json::from_str(
+        &std::fs::read_to_string(project_dir.path().join(".mcp.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(written["mcpServers"]["kolada"]["type"], "stdio");
+    assert!(written["mcpServers"]["kolada"]["command"].is_string());
+
+    let statuses = app::app_statuses("ClaudeCode", scope.clone()).unwrap();
+    assert_eq!(statuses["installed"]["Kolada"], true);
+
+    app::uninstall("Kolada", "ClaudeCode", scope.clone()).unwrap();
+    let statuses = app::app_statuses("ClaudeCode", scope).unwrap();
+    assert_eq!(statuses["installed"]["Kolada"], false);
+
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_project_configs_are_portable() {
+    environment::set_test_mode(true);
+    set_test_registry();
+    let settings_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(settings_dir.path().join("settings.json")));
+
+    let project_dir = tempfile::tempdir().unwrap();
+    let scope = Some(project_dir.path().to_string_lossy().to_string());
+    let env = json!({ "KOLADA_REGION": "Stockholm", "KOLADA_API_KEY": "very-secret" });
+
+    for (client, path, reference) in [
+        ("ClaudeCode", ".mcp.json", "${KOLADA_API_KEY}"),
+        ("Cursor", ".cursor/mcp.json", "${env:KOLADA_API_KEY}"),
+    ] {
+        let message = app::install_app("Kolada", Some(env.clone()), client, scope.clone()).unwrap();
+        assert!(
+            message.contains("Set KOLADA_API_KEY in the environment"),
+            "{}",
+            message
+        );
+
+        let text = std::fs::read_to_string(project_dir.path().join(path)).unwrap();
+        assert!(!text.contains("very-secret"), "{}", client);
+        let written: Value = serde_json::from_str(&text).unwrap();
+        let entry = &written["mcpServers"]["kolada"];
+        assert_eq!(entry["command"], "uvx", "{}", client);
+        assert_eq!(entry["args"], json!(["kolada-mcp"]), "{}", client);
+        assert_eq!(entry["env"]["KOLADA_API_KEY"], reference, "{}", client);
+        assert_eq!(entry["env"]["KOLADA_REGION"], "Stockholm", "{}", client);
+    }
+
+    settings::set_test_settings_path(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_claude_code_keeps_its_own_changes() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join(".claude.json");
+    std::fs::write(
+        &config_path,
+        "{\n  \"numStartups\": 1,\n  \"mcpServers\": {}\n}",
+    )
+    .unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::install_app("Kolada", None, "ClaudeCode", None).unwrap();
+
+    // Claude Code updates the file between Staten's edits
+    std::fs::write(
+        &config_path,
+        std::fs::read_to_string(&config_path).unwrap().replace(
+            "\"numStartups\": 1",
+            "\"zebra\": true,\n  \"numStartups\": 2",
+        ),
+    )
+    .unwrap();
+
+    app::uninstall("Kolada", "ClaudeCode", None).unwrap();
+
+    let written = std::fs::read_to_string(&config_path).unwrap();
+    assert!(written.starts_with("{\n  \"zebra\": true,\n  \"numStartups\": 2,"));
+    let parsed: Value = serde_json::from_str(&written).unwrap();
+    assert_eq!(parsed["mcpServers"], json!({}));
+
+    app::set_test_config_path(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_project_scope_needs_a_supporting_client() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let project_dir = tempfile::tempdir().unwrap();
+    let scope = Some(project_dir.path().to_string_lossy().to_string());
+    assert!(app::install_app("Kolada", None, "Claude", scope).is_err());
+
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+fn test_claude_code_needs_no_restart() {
+    assert!(!app::client_needs_restart("ClaudeCode").unwrap());
+    assert!(app::client_needs_restart("Claude").unwrap());
+    assert_eq!(
+        app::restart_client("ClaudeCode").unwrap(),
+        "Restart not needed for ClaudeCode"
+    );
+}
+
+#[test]
+fn test_vscode_servers_key_round_trip() {
+    let native = json!({
+        "inputs": [],
+        "servers": {
+            "kolada": { "type": "stdio", "command": "uvx", "args": ["kolada-mcp"] }
+        }
+    });
+
+    let staten = client_format::from_client_format(&ClientType::VSCode, native.clone());
+    assert!(staten.get("servers").is_none());
+    assert_eq!(
+        staten["mcpServers"]["kolada"],
+        json!({ "command": "uvx", "args": ["kolada-mcp"] })
+    );
+    assert_eq!(
+        client_format::to_client_format(&ClientType::VSCode, &staten),
+        native
+    );
+
+    assert_eq!(
+        client_format::initial_config(&ClientType::VSCode),
+        json!({ "servers": {} })
+    );
+}
+
+#[test]
+fn test_vscode_settings_json_servers() {
+    let native = json!({
+        "editor.fontSize": 14,
+        "mcp": { "servers": { "kolada": { "command": "uvx" } } }
+    });
+
+    let staten = client_format::from_client_format(&ClientType::VSCode, native);
+    assert_eq!(staten["mcpServers"]["kolada"]["command"], "uvx");
+
+    let written = client_format::to_client_format(&ClientType::VSCode, &staten);
+    assert_eq!(written["mcp"]["servers"]["kolada"]["type"], "stdio");
+    assert!(written.get("servers").is_none());
+    assert_eq!(written["editor.fontSize"], 14);
+}
+
+#[test]
+fn test_prompt_for_secrets() {
+    let mut config = json!({
+        "mcpServers": {
+            "kolada": {
+                "command": "uvx",
+                "env": { "API_TOKEN": "abc", "REGION": "se" }
+            }
+        }
+    });
+
+    client_format::prompt_for_secrets(&mut config, "kolada");
+    client_format::prompt_for_secrets(&mut config, "kolada");
+
+    assert_eq!(
+        config["mcpServers"]["kolada"]["env"],
+        json!({ "API_TOKEN": "${input:kolada-API_TOKEN}", "REGION": "se" })
+    );
+    assert_eq!(
+        config["inputs"],
+        json!([{
+            "type": "promptString",
+            "id": "kolada-API_TOKEN",
+            "description": "API_TOKEN for kolada",
+            "password": true
+        }])
+    );
+
+    config["inputs"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({ "type": "promptString", "id": "own-input" }));
+    client_format::remove_secret_inputs(&mut config, "kolada");
+    assert_eq!(
+        config["inputs"],
+        json!([{ "type": "promptString", "id": "own-input" }])
+    );
+}
+
+#[test]
+fn test_prompt_for_secrets_in_settings_json() {
+    let native = json!({
+        "editor.fontSize": 14,
+        "mcp": {
+            "servers": {
+                "kolada": { "command": "uvx", "env": { "API_TOKEN": "abc" } }
+            }
+        }
+    });
+
+    let mut config = client_format::from_client_format(&ClientType::VSCode, native);
+    client_format::prompt_for_secrets(&mut config, "kolada");
+    let written = client_format::to_client_format(&ClientType::VSCode, &config);
+
+    assert!(written.get("inputs").is_none());
+    assert_eq!(written["mcp"]["inputs"][0]["id"], "kolada-API_TOKEN");
+    assert_eq!(
+        written["mcp"]["servers"]["kolada"]["env"]["API_TOKEN"],
+        "${input:kolada-API_TOKEN}"
+    );
+
+    client_format::remove_secret_inputs(&mut config, "kolada");
+    assert_eq!(config["mcp"]["inputs"], json!([]));
+}
+
+#[test]
+#[serial]
+fn test_vscode_workspace_install_prompts_for_secrets() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let project_dir = tempfile::tempdir().unwrap();
+    let scope = Some(project_dir.path().to_string_lossy().to_string());
+
+    app::install_app(
+        "Kolada",
+        Some(json!({ "KOLADA_API_KEY": "secret" })),
+        "VSCode",
+        scope.clone(),
+    )
+    .unwrap();
+
+    let config_path = project_dir.path().join(".vscode").join("mcp.json");
+    let written = std::fs::read_to_string(&config_path).unwrap();
+    assert!(!written.contains("secret\""));
+    let written: Value = serde_json::from_str(&written).unwrap();
+    assert_eq!(
+        written["servers"]["kolada"]["env"]["KOLADA_API_KEY"],
+        "${input:kolada-KOLADA_API_KEY}"
+    );
+    assert_eq!(written["inputs"][0]["id"], "kolada-KOLADA_API_KEY");
+    assert!(written.get("mcpServers").is_none());
+
+    app::uninstall("Kolada", "VSCode", scope).unwrap();
+    let written: Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(written["servers"], json!({}));
+    assert_eq!(written["inputs"], json!([]));
+
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_vscode_install_prompts_for_secrets_in_args() {
+    environment::set_test_mode(true);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([{
+            "name": "Riksdagen",
+            "config": {
+                "mcpKey": "riksdagen",
+                "runtime": "uvx",
+                "args": ["riksdagen-mcp", "--api-key=${RIKSDAGEN_API_KEY}", "--region", "${REGION}"]
+            }
+        }]));
+    }
+
+    let project_dir = tempfile::tempdir().unwrap();
+    let scope = Some(project_dir.path().to_string_lossy().to_string());
+    app::install_app(
+        "Riksdagen",
+        Some(json!({ "RIKSDAGEN_API_KEY": "secret", "REGION": "se" })),
+        "VSCode",
+        scope.clone(),
+    )
+    .unwrap();
+
+    let config_path = project_dir.path().join(".vscode").join("mcp.json");
+    let written = std::fs::read_to_string(&config_path).unwrap();
+    assert!(!written.contains("secret"));
+    let written: Value = serde_json::from_str(&written).unwrap();
+    assert_eq!(
+        written["servers"]["riksdagen"]["args"],
+        json!([
+            "riksdagen-mcp",
+            "--api-key=${input:riksdagen-RIKSDAGEN_API_KEY}",
+            "--region",
+            "se"
+        ])
+    );
+    assert_eq!(
+        written["inputs"],
+        json!([{
+            "type": "promptString",
+            "id": "riksdagen-RIKSDAGEN_API_KEY",
+            "description": "RIKSDAGEN_API_KEY for riksdagen",
+            "password": true
+        }])
+    );
+
+    app::uninstall("Riksdagen", "VSCode", scope).unwrap();
+    let written: Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(written["inputs"], json!([]));
+
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+fn test_zed_entries_round_trip() {
+    let native = json!({
+        "context_servers": {
+            "kolada": {
+                "command": { "path": "uvx", "args": ["kolada-mcp"], "env": {} },
+                "settings": {}
+            },
+            "from-extension": { "settings": { "token": "x" } }
+        }
+    });
+
+    let staten = client_format::from_client_format(&ClientType::Zed, native.clone());
+    assert_eq!(
+        staten["mcpServers"]["kolada"],
+        json!({ "command": "uvx", "args": ["kolada-mcp"], "env": {}, "settings": {} })
+    );
+    assert!(staten.get("context_servers").is_none());
+    assert_eq!(
+        client_format::to_client_format(&ClientType::Zed, &staten),
+        native
+    );
+}
+
+#[test]
+fn test_zed_flat_command_is_kept() {
+    let native = json!({
+        "context_servers": {
+            "kolada": { "command": "uvx", "args": ["kolada-mcp"] }
+        }
+    });
+
+    let staten = client_format::from_client_format(&ClientType::Zed, native);
+    assert_eq!(
+        staten["mcpServers"]["kolada"],
+        json!({ "command": "uvx", "args": ["kolada-mcp"] })
+    );
+    assert_eq!(
+        client_format::to_client_format(&ClientType::Zed, &staten)["context_servers"]["kolada"],
+        json!({ "command": { "path": "uvx", "args": ["kolada-mcp"] } })
+    );
+}
+
+#[test]
+#[serial]
+fn test_zed_install_keeps_comments() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("settings.json");
+    std::fs::write(
+        &config_path,
+        "// Zed settings\n{\n  \"theme\": \"One Dark\", // keep me\n  \"vim_mode\": true,\n}\n",
+    )
+    .unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::install_app("Kolada", None, "Zed", None).unwrap();
+
+    let written = std::fs::read_to_string(&config_path).unwrap();
+    assert!(written.starts_with("// Zed settings\n{\n  \"theme\": \"One Dark\", // keep me\n"));
+    let value = staten_lib::jsonc::parse(&written).unwrap();
+    assert_eq!(value["vim_mode"], true);
+    assert!(value["context_servers"]["kolada"]["command"]["path"].is_string());
+    assert_eq!(
+        value["context_servers"]["kolada"]["command"]["args"],
+        json!(["kolada-mcp"])
+    );
+    assert!(app::is_installed("Kolada", "Zed").unwrap());
+
+    app::set_test_config_path(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+fn test_goose_extensions_round_trip() {
+    let native = json!({
+        "extensions": {
+            "developer": { "enabled": true, "name": "developer", "type": "builtin" },
+            "kolada": {
+                "cmd": "uvx",
+                "args": ["kolada-mcp"],
+                "envs": { "REGION": "se" },
+                "enabled": false,
+                "name": "Kolada",
+                "timeout": 60,
+                "type": "stdio"
+            }
+        }
+    });
+
+    let staten = client_format::from_client_format(&ClientType::Goose, native.clone());
+    let kolada = &staten["mcpServers"]["kolada"];
+    assert_eq!(kolada["command"], "uvx");
+    assert_eq!(kolada["env"], json!({ "REGION": "se" }));
+    assert!(kolada.get("type").is_none());
+    assert_eq!(
+        client_format::to_client_format(&ClientType::Goose, &staten),
+        native
+    );
+
+    // New entries get the fields Goose requires
+    let written = client_format::to_client_format(
+        &ClientType::Goose,
+        &json!({ "mcpServers": { "time": { "command": "uvx", "args": [] } } }),
+    );
+    assert_eq!(
+        written["extensions"]["time"],
+        json!({
+            "cmd": "uvx",
+            "args": [],
+            "envs": {},
+            "type": "stdio",
+            "name": "time",
+            "enabled": true,
+            "timeout": 300
+        })
+    );
+}
+
+#[test]
+fn test_continue_servers_list_by_name() {
+    let native = json!({
+        "name": "Local Assistant",
+        "mcpServers": [
+            { "name": "time", "command": "uvx", "args": ["mcp-server-time"] },
+            { "uses": "anthropic/memory-mcp" },
+            { "name": "kolada", "command": "uvx", "args": ["kolada-mcp"] }
+        ]
+    });
+
+    let staten = client_format::from_client_format(&ClientType::Continue, native.clone());
+    assert_eq!(
+        staten["mcpServers"]["kolada"],
+        json!({ "command": "uvx", "args": ["kolada-mcp"] })
+    );
+    // Order and entries without a name survive the round trip
+    assert_eq!(
+        client_format::to_client_format(&ClientType::Continue, &staten),
+        native
+    );
+
+    let initial = client_format::initial_config(&ClientType::Continue);
+    assert_eq!(initial["mcpServers"], json!([]));
+    assert_eq!(initial["schema"], "v1");
+}
+
+#[test]
+#[serial]
+fn test_continue_install_keeps_other_blocks() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.yaml");
+    std::fs::write(
+        &config_path,
+        "name: Local Assistant # mine\nversion: 1.0.0\nmcpServers:\n  - name: time\n    command: uvx\n  - uses: owner/block\n",
+    )
+    .unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::install_app("Kolada", None, "Continue", None).unwrap();
+    let written = std::fs::read_to_string(&config_path).unwrap();
+    assert!(written.starts_with("name: Local Assistant # mine\nversion: 1.0.0\n"));
+    let value = staten_lib::yaml::parse(&written).unwrap();
+    let names: Vec<&str> = value["mcpServers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["name"].as_str().unwrap_or("-"))
+        .collect();
+    assert_eq!(names, vec!["time", "-", "kolada"]);
+
+    app::uninstall("Kolada", "Continue", None).unwrap();
+    let value = staten_lib::yaml::parse(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(
+        value["mcpServers"],
+        json!([{ "name": "time", "command": "uvx" }, { "uses": "owner/block" }])
+    );
+
+    app::set_test_config_path(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use serde_json::{json, Value};
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    config_format::{self, ConfigFormat, Json, Jsonc, Toml},
+    environment,
+};
+
+const CODEX_CONFIG: &str = r#"# Codex config
+model = "o3" # the model
+
+[mcp_servers.old]
+command = "old"
+
+[mcp_servers.kolada]
+command = "uvx" # runs kolada
+args = ["kolada-mcp"]
+
+[profiles.fast]
+model = "o4-mini"
+"#;
+
+fn codex() -> Toml {
+    Toml {
+        managed_keys: &["mcp_servers"],
+    }
+}
+
+#[test]
+fn test_toml_parse() {
+    let config = codex().parse(CODEX_CONFIG).unwrap();
+    assert_eq!(config["model"], "o3");
+    assert_eq!(
+        config["mcp_servers"]["kolada"]["args"],
+        json!(["kolada-mcp"])
+    );
+    assert_eq!(config["profiles"]["fast"]["model"], "o4-mini");
+
+    assert_eq!(codex().parse("").unwrap(), json!({}));
+    assert!(codex().parse("model = ").is_err());
+}
+
+#[test]
+fn test_toml_render_edits_in_place() {
+    let mut config = codex().parse(CODEX_CONFIG).unwrap();
+    config["model"] = json!("ignored, not managed");
+    config["mcp_servers"] = json!({
+        "kolada": {
+            "command": "uvx",
+            "args": ["kolada-mcp", "--verbose"],
+            "env": { "KOLADA_API_KEY": "abc" }
+        },
+        "time": { "command": "uvx", "args": ["mcp-server-time"] }
+    });
+
+    let rendered = codex().render(&config, Some(CODEX_CONFIG)).unwrap();
+    assert!(rendered.starts_with("# Codex config\nmodel = \"o3\" # the model\n"));
+    assert!(rendered.contains("command = \"uvx\" # runs kolada\n"));
+    assert!(rendered.contains("env = { KOLADA_API_KEY = \"abc\" }"));
+    assert!(rendered.contains("[mcp_servers.time]\n"));
+    assert!(rendered.contains("[profiles.fast]\nmodel = \"o4-mini\"\n"));
+    assert!(!rendered.contains("[mcp_servers.old]"));
+
+    let parsed = codex().parse(&rendered).unwrap();
+    assert_eq!(parsed["mcp_servers"], config["mcp_servers"]);
+    assert_eq!(parsed["model"], "o3");
+}
+
+#[test]
+fn test_toml_render_new_file() {
+    let config = json!({
+        "mcp_servers": { "time": { "command": "uvx", "args": ["mcp-server-time"] } }
+    });
+    let rendered = codex().render(&config, None).unwrap();
+    assert!(rendered.starts_with("[mcp_servers.time]\n"));
+    assert!(rendered.contains("command = \"uvx\"\n"));
+    assert!(rendered.contains("args = [\"mcp-server-time\"]\n"));
+}
+
+#[test]
+fn test_json_formats() {
+    let config = json!({ "mcpServers": { "time": { "command": "uvx" } } });
+    let rendered = Json.render(&config, Some("{}")).unwrap();
+    assert_eq!(Json.parse(&rendered).unwrap(), config);
+
+    let jsonc = Jsonc {
+        managed_keys: &["context_servers"],
+    };
+    let previous = "{\n  // theme\n  \"theme\": \"One Dark\",\n  \"context_servers\": {}\n}";
+    let rendered = jsonc
+        .render(
+            &json!({ "theme": "changed", "context_servers": { "a": {} } }),
+            Some(previous),
+        )
+        .unwrap();
+    assert!(rendered.contains("// theme\n  \"theme\": \"One Dark\""));
+    assert_eq!(
+        jsonc.parse(&rendered).unwrap()["context_servers"]["a"],
+        json!({})
+    );
+    assert_eq!(Json.name(), "JSON");
+    assert_eq!(jsonc.name(), "JSONC");
+}
+
+#[test]
+#[serial]
+fn test_codex_install_and_uninstall() {
+    environment::set_test_mode(true);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([{
+            "name": "Kolada",
+            "description": "Municipal key figures",
+            "config": {
+                "mcpKey": "kolada",
+                "runtime": "uvx",
+                "args": ["kolada-mcp"]
+            }
+        }]));
+    }
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "# my settings\nmodel = \"o3\"\n").unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::install_app(
+        "Kolada",
+        Some(json!({ "REGION": "se" })),
+        ClientType::Codex.as_str(),
+        None,
+    )
+    .unwrap();
+    assert!(app::is_installed("Kolada", ClientType::Codex.as_str()).unwrap());
+
+    let written = std::fs::read_to_string(&config_path).unwrap();
+    assert!(written.starts_with("# my settings\nmodel = \"o3\"\n"));
+    assert!(written.contains("[mcp_servers.kolada]"));
+    let parsed: Value = config_format::for_client(&ClientType::Codex)
+        .parse(&written)
+        .unwrap();
+    assert_eq!(parsed["mcp_servers"]["kolada"]["env"]["REGION"], "se");
+
+    app::uninstall("Kolada", ClientType::Codex.as_str(), None).unwrap();
+    let written = std::fs::read_to_string(&config_path).unwrap();
+    assert!(!written.contains("kolada"));
+    assert!(written.contains("model = \"o3\""));
+
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_parse_error_names_format() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(&config_path, "[mcp_servers\n").unwrap();
+    app::set_test_config_path(Some(config_path));
+
+    let error = app::get_config(&ClientType::Codex).unwrap_err();
+    assert!(
+        error.starts_with("Failed to parse config TOML"),
+        "{}",
+        error
+    );
+
+    app::set_test_config_path(None);
+    environment::set_test_mode(false);
+}
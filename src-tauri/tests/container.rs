@@ -0,0 +1,196 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    container::{ContainerImage, ContainerMount},
+    environment,
+};
+
+const DIGEST: &str = "sha256:4c5a5e3f1d2b7a9c8e6f0a1b2c3d4e5f60718293a4b5c6d7e8f9a0b1c2d3e4f5";
+
+fn set_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = Some(json!([{
+        "name": "Arkivet",
+        "description": "Search a local document archive",
+        "setup": [
+            { "key": "ARCHIVE_DIR", "type": "input", "label": "Archive folder", "mount": "/archive", "readOnly": true },
+            { "key": "ARCHIVE_TOKEN", "type": "input", "label": "Token" }
+        ],
+        "config": {
+            "mcpKey": "arkivet",
+            "runtime": "docker",
+            "image": "ghcr.io/example/arkivet-mcp:1.4",
+            "digest": DIGEST,
+            "args": ["--root", "/archive"]
+        }
+    }]));
+}
+
+fn clear_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = None;
+}
+
+fn sample_image() -> ContainerImage {
+    ContainerImage {
+        image: "ghcr.io/example/arkivet-mcp:1.4".to_string(),
+        digest: None,
+        mounts: vec![ContainerMount {
+            key: "ARCHIVE_DIR".to_string(),
+            target: "/archive".to_string(),
+            read_only: true,
+        }],
+    }
+}
+
+#[test]
+fn test_run_args() {
+    let image = sample_image();
+    let env = json!({ "ARCHIVE_DIR": "/Users/anna/arkiv", "ARCHIVE_TOKEN": "secret" });
+
+    assert_eq!(
+        image.run_args(&env, &["--verbose".to_string()]),
+        vec![
+            "run",
+            "-i",
+            "--rm",
+            "-e",
+            "ARCHIVE_TOKEN",
+            "-v",
+            "/Users/anna/arkiv:/archive:ro",
+            "ghcr.io/example/arkivet-mcp:1.4",
+            "--verbose"
+        ]
+    );
+
+    // Secrets are passed by name and never end up in the args
+    let args = image.run_args(&env, &[]);
+    assert!(!args.iter().any(|arg| arg.contains("secret")));
+
+    // A mount without a folder picked is left out
+    let args = image.run_args(&json!({ "ARCHIVE_DIR": "" }), &[]);
+    assert!(!args.contains(&"-v".to_string()));
+}
+
+#[test]
+fn test_pin_image() {
+    let mut image = sample_image();
+    image.pin("1.5");
+    assert_eq!(image.reference(), "ghcr.io/example/arkivet-mcp:1.5");
+
+    image.pin(DIGEST);
+    assert_eq!(
+        image.reference(),
+        format!("ghcr.io/example/arkivet-mcp@{}", DIGEST)
+    );
+
+    // A registry port is not a tag
+    let mut image = ContainerImage {
+        image: "localhost:5000/arkivet".to_string(),
+        digest: None,
+        mounts: Vec::new(),
+    };
+    image.pin("2.0");
+    assert_eq!(image.reference(), "localhost:5000/arkivet:2.0");
+}
+
+#[test]
+fn test_from_registry() {
+    let app = json!({
+        "setup": [
+            { "key": "ARCHIVE_DIR", "mount": "/archive" },
+            { "key": "ARCHIVE_TOKEN" }
+        ]
+    });
+    let config = json!({ "image": "arkivet", "digest": DIGEST });
+    let image = ContainerImage::from_registry(&app, config.as_object().unwrap()).unwrap();
+    assert_eq!(image.digest.as_deref(), Some(DIGEST));
+    assert_eq!(image.mounts.len(), 1);
+    assert!(!image.mounts[0].read_only);
+
+    let config = json!({ "image": "arkivet", "digest": "latest" });
+    assert!(ContainerImage::from_registry(&app, config.as_object().unwrap()).is_err());
+    assert!(ContainerImage::from_registry(&app, &serde_json::Map::new()).is_err());
+}
+
+#[test]
+#[serial]
+fn test_install_container_app() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("claude_desktop_config.json");
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::install_app(
+        "Arkivet",
+        Some(json!({ "ARCHIVE_DIR": "/Users/anna/arkiv", "ARCHIVE_TOKEN": "secret" })),
+        "Claude",
+        None,
+    )
+    .unwrap();
+
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let entry = &written["mcpServers"]["arkivet"];
+    assert_eq!(entry["command"], "/test/bin/docker");
+    assert_eq!(
+        entry["args"],
+        json!([
+            "run",
+            "-i",
+            "--rm",
+            "-e",
+            "ARCHIVE_TOKEN",
+            "-v",
+            "/Users/anna/arkiv:/archive:ro",
+            format!("ghcr.io/example/arkivet-mcp@{}", DIGEST),
+            "--root",
+            "/archive"
+        ])
+    );
+    assert_eq!(entry["env"]["ARCHIVE_TOKEN"], "secret");
+
+    // New variables are passed to the container too
+    app::save_app_env("Arkivet", json!({ "LOG_LEVEL": "debug" }), "Claude").unwrap();
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let args = written["mcpServers"]["arkivet"]["args"].as_array().unwrap();
+    assert!(args.contains(&json!("LOG_LEVEL")));
+    assert!(args.contains(&json!("ARCHIVE_TOKEN")));
+
+    app::set_test_config_path(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_container_app_without_engine() {
+    environment::set_test_mode(true);
+    environment::set_test_container_engine(None);
+    set_test_registry();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    app::set_test_config_path(Some(temp_dir.path().join("claude_desktop_config.json")));
+
+    let status = environment::get_container_engine_status();
+    assert_eq!(status["available"], false);
+
+    let statuses = app::app_statuses("Claude", None).unwrap();
+    assert_eq!(statuses["configured"]["Arkivet"], false);
+    let error = app::install_app("Arkivet", None, "Claude", None).unwrap_err();
+    assert!(error.contains("Docker"));
+
+    app::set_test_config_path(None);
+    clear_test_registry();
+    environment::set_test_container_engine(Some(environment::ContainerEngine {
+        name: "docker".to_string(),
+        path: "/test/bin/docker".to_string(),
+    }));
+    environment::set_test_mode(false);
+}
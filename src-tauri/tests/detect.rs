@@ -0,0 +1,95 @@
+mod common;
+
+use staten_lib::detect::{self, DetectionSource};
+use std::path::PathBuf;
+
+const REG_OUTPUT: &str = r#"
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\Uninstall\{1234}
+    DisplayName    REG_SZ    Something Else
+    DisplayVersion    REG_SZ    1.0
+
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\Uninstall\{DADADADA}_is1
+    DisplayName    REG_SZ    Cursor (User)
+    DisplayVersion    REG_SZ    0.48.7
+    InstallLocation    REG_SZ    C:\Users\me\AppData\Local\Programs\cursor\
+    EstimatedSize    REG_DWORD    0x1c2
+
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\Uninstall\AnthropicClaude
+    DisplayName    REG_SZ    Claude
+    DisplayIcon    REG_SZ    "C:\Users\me\AppData\Local\AnthropicClaude\claude.exe",0
+    DisplayVersion    REG_SZ    0.9.2
+"#;
+
+#[test]
+fn test_parse_registry_entries() {
+    let entries = detect::parse_registry_entries(REG_OUTPUT);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[1].display_name.as_deref(), Some("Cursor (User)"));
+
+    let cursor = detect::find_registry_installation(&entries, "Cursor").unwrap();
+    assert_eq!(
+        cursor.path,
+        PathBuf::from(r"C:\Users\me\AppData\Local\Programs\cursor\")
+    );
+    assert_eq!(cursor.version.as_deref(), Some("0.48.7"));
+    assert_eq!(cursor.source, DetectionSource::Registry);
+
+    // Without an InstallLocation the path comes from the icon
+    let claude = detect::find_registry_installation(&entries, "Claude").unwrap();
+    assert_eq!(
+        claude.path,
+        PathBuf::from(r"C:\Users\me\AppData\Local\AnthropicClaude\claude.exe")
+    );
+
+    assert!(detect::find_registry_installation(&entries, "Windsurf").is_none());
+}
+
+#[test]
+fn test_parse_desktop_entry() {
+    let content = "[Desktop Entry]\nName=Cursor\nExec=/opt/cursor/cursor.AppImage --no-sandbox %U\n\n[Desktop Action new-window]\nName=New Window\nExec=/opt/cursor/cursor.AppImage --new-window\n";
+    assert_eq!(
+        detect::parse_desktop_entry(content),
+        Some((
+            "Cursor".to_string(),
+            "/opt/cursor/cursor.AppImage --no-sandbox".to_string()
+        ))
+    );
+    assert_eq!(
+        detect::parse_desktop_entry("[Desktop Entry]\nName=X\n"),
+        None
+    );
+}
+
+#[test]
+fn test_find_in_path() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir_all(&bin_dir).unwrap();
+    let file_name = if cfg!(target_os = "windows") {
+        "windsurf.cmd"
+    } else {
+        "windsurf"
+    };
+    std::fs::write(bin_dir.join(file_name), "").unwrap();
+
+    let path_var =
+        std::env::join_paths([temp_dir.path().join("missing"), bin_dir.clone()]).unwrap();
+    assert_eq!(
+        detect::find_in_path("windsurf", &path_var),
+        Some(bin_dir.join(file_name))
+    );
+    assert_eq!(detect::find_in_path("cursor", &path_var), None);
+}
+
+#[test]
+fn test_detection_source_names() {
+    assert_eq!(
+        serde_json::to_value(DetectionSource::AppBundle).unwrap(),
+        "appBundle"
+    );
+    assert_eq!(
+        serde_json::to_value(DetectionSource::DesktopEntry).unwrap(),
+        "desktopEntry"
+    );
+    assert_eq!(serde_json::to_value(DetectionSource::Path).unwrap(), "path");
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use serial_test::serial;
+use staten_lib::{
+    app,
+    doctor::{self, Severity},
+    environment, node, settings,
+};
+
+#[test]
+fn test_parse_df() {
+    let output = "\
+Filesystem     1024-blocks      Used Available Capacity Mounted on
+/dev/disk3s5     482797652 301234567 158745124      66% /System/Volumes/Data
+";
+    assert_eq!(doctor::parse_df(output), Some(158745124 * 1024));
+    assert_eq!(
+        doctor::parse_df("df: /nope: No such file or directory\n"),
+        None
+    );
+}
+
+#[test]
+fn test_disk_space_severity() {
+    assert_eq!(
+        doctor::disk_space_severity(100 * 1024 * 1024),
+        Severity::Error
+    );
+    assert_eq!(
+        doctor::disk_space_severity(doctor::MIN_FREE_BYTES),
+        Severity::Warning
+    );
+    assert_eq!(
+        doctor::disk_space_severity(doctor::LOW_FREE_BYTES),
+        Severity::Ok
+    );
+}
+
+#[test]
+fn test_path_issues() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let existing = temp_dir.path().to_path_buf();
+    let missing = temp_dir.path().join("missing");
+
+    let path_var =
+        std::env::join_paths([existing.clone(), missing.clone(), existing.clone()]).unwrap();
+    let issues = doctor::path_issues(&path_var);
+    assert_eq!(issues.len(), 2);
+    assert!(issues[0].contains("does not exist"));
+    assert!(issues[1].contains("more than once"));
+
+    let path_var = std::env::join_paths([existing]).unwrap();
+    assert!(doctor::path_issues(&path_var).is_empty());
+    assert_eq!(
+        doctor::path_issues(std::ffi::OsStr::new("")),
+        vec!["PATH is empty"]
+    );
+}
+
+#[test]
+#[serial]
+fn test_run_checks() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+    let registry = common::serve(vec![("/".to_string(), b"[]".to_vec())]);
+    settings::update_settings(serde_json::json!({
+        "registrySources": [registry, "http://127.0.0.1:1/registry.json"]
+    }))
+    .unwrap();
+
+    // The default Node.js version is installed
+    let node_dir = temp_dir.path().join("node");
+    node::set_test_node_dir(Some(node_dir.clone()));
+    let (node_path, npx_path) = node::node_paths(node::NODE_VERSION);
+    std::fs::create_dir_all(node_path.parent().unwrap()).unwrap();
+    std::fs::write(&node_path, "").unwrap();
+    std::fs::write(&npx_path, "").unwrap();
+
+    // Every client reads the same broken config
+    let config_path = temp_dir.path().join("claude_desktop_config.json");
+    std::fs::write(&config_path, r#"{ "mcpServers": "#).unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let report = doctor::run_checks();
+    let check = |id: &str| {
+        report
+            .checks
+            .iter()
+            .find(|check| check.id == id)
+            .unwrap_or_else(|| panic!("no {} check", id))
+    };
+
+    assert_eq!(check("uv").severity, Severity::Ok);
+    assert_eq!(check("node").severity, Severity::Ok);
+    assert_eq!(check("node").details["version"], node::NODE_VERSION);
+
+    let claude = check("client.claude");
+    assert_eq!(claude.severity, Severity::Error);
+    assert!(claude
+        .fix
+        .as_ref()
+        .unwrap()
+        .contains(&*config_path.to_string_lossy()));
+
+    let registry = check("registry");
+    assert_eq!(registry.severity, Severity::Warning);
+    assert_eq!(registry.details[0]["reachable"], true);
+    assert_eq!(registry.details[1]["reachable"], false);
+
+    // Checks that aren't ok say what to do
+    for check in &report.checks {
+        if check.severity >= Severity::Warning {
+            assert!(check.fix.is_some(), "{} has no fix", check.id);
+        }
+    }
+    assert!(!report.healthy);
+    assert_eq!(report.severity, Severity::Error);
+
+    // A valid config clears the error
+    std::fs::write(&config_path, r#"{ "mcpServers": {} }"#).unwrap();
+    let report = doctor::run_checks();
+    let claude = report
+        .checks
+        .iter()
+        .find(|check| check.id == "client.claude")
+        .unwrap();
+    assert_eq!(claude.severity, Severity::Ok);
+    assert_eq!(claude.fix, None);
+
+    app::set_test_config_path(None);
+    node::set_test_node_dir(None);
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
@@ -0,0 +1,272 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    download::{self, ArchiveKind, BinaryApp, BinaryAsset},
+    environment, gateway, settings,
+};
+
+const SERVER: &[u8] = b"#!/bin/sh\necho kartan\n";
+
+fn set_test_registry(version: &str, url: &str, sha256: &str) {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = Some(json!([{
+        "name": "Kartan",
+        "description": "Maps and places",
+        "config": {
+            "mcpKey": "kartan",
+            "runtime": "binary",
+            "version": version,
+            "platforms": {
+                download::platform_key(): {
+                    "url": url,
+                    "sha256": sha256,
+                    "bin": "kartan-mcp/bin/kartan"
+                }
+            },
+            "args": ["--stdio"]
+        }
+    }]));
+}
+
+fn clear_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = None;
+}
+
+#[test]
+fn test_sha256_hex() {
+    assert_eq!(
+        download::sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn test_binary_app_from_registry() {
+    let sha256 = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+    let config = json!({
+        "version": "0.3.1",
+        "platforms": {
+            "macos-aarch64": { "url": "https://example.se/kartan-macos.tar.gz?raw=1", "sha256": sha256, "bin": "kartan" },
+            "linux-x86_64": { "url": "https://example.se/kartan-linux", "sha256": sha256 }
+        }
+    });
+    let config = config.as_object().unwrap();
+
+    let mac = BinaryApp::for_platform("kartan", config, "macos-aarch64").unwrap();
+    let asset = mac.asset.unwrap();
+    assert_eq!(asset.archive, ArchiveKind::TarGz);
+    assert_eq!(asset.sha256, sha256.to_lowercase());
+
+    // A plain executable is named after the app
+    let linux = BinaryApp::for_platform("kartan", config, "linux-x86_64").unwrap();
+    let asset = linux.asset.unwrap();
+    assert_eq!(asset.archive, ArchiveKind::Raw);
+    assert!(asset.bin.starts_with("kartan"));
+
+    let windows = BinaryApp::for_platform("kartan", config, "windows-x86_64").unwrap();
+    assert_eq!(windows.asset, None);
+    assert_eq!(windows.executable(), None);
+
+    let invalid = json!({
+        "version": "0.3.1",
+        "platforms": { "linux-x86_64": { "url": "https://example.se/kartan", "sha256": "abc" } }
+    });
+    assert!(
+        BinaryApp::for_platform("kartan", invalid.as_object().unwrap(), "linux-x86_64").is_err()
+    );
+
+    let escaping = json!({ "version": "../0.3.1", "platforms": {} });
+    assert!(
+        BinaryApp::for_platform("kartan", escaping.as_object().unwrap(), "linux-x86_64").is_err()
+    );
+
+    // The mcpKey and bin must not lead out of the apps directory
+    for name in ["", ".", "..", "../kartan", "/tmp/kartan"] {
+        assert!(
+            BinaryApp::for_platform(name, config, "linux-x86_64").is_err(),
+            "{}",
+            name
+        );
+    }
+    for bin in ["/usr/local/bin/kartan", "../kartan", "bin/../../kartan", ""] {
+        let escaping = json!({
+            "version": "0.3.1",
+            "platforms": { "linux-x86_64": { "url": "https://example.se/k", "sha256": sha256, "bin": bin } }
+        });
+        assert!(
+            BinaryApp::for_platform("kartan", escaping.as_object().unwrap(), "linux-x86_64")
+                .is_err(),
+            "{}",
+            bin
+        );
+    }
+    assert!(download::remove_binary("..").is_err());
+    assert!(download::remove_binary("").is_err());
+}
+
+#[test]
+fn test_unpack_zip() {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    zip.start_file("bin/kartan.exe", options).unwrap();
+    zip.write_all(SERVER).unwrap();
+    zip.start_file("../outside", options).unwrap();
+    zip.write_all(b"nope").unwrap();
+    let bytes = zip.finish().unwrap().into_inner();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let dest = temp_dir.path().join("unpacked");
+    std::fs::create_dir(&dest).unwrap();
+    let asset = BinaryAsset {
+        url: "https://example.se/kartan.zip".to_string(),
+        sha256: download::sha256_hex(&bytes),
+        archive: ArchiveKind::Zip,
+        bin: "bin/kartan.exe".to_string(),
+    };
+    download::unpack(&bytes, &asset, &dest).unwrap();
+
+    assert_eq!(std::fs::read(dest.join("bin/kartan.exe")).unwrap(), SERVER);
+    assert!(!temp_dir.path().join("outside").exists());
+}
+
+#[test]
+#[serial]
+fn test_install_binary_app() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let apps_dir = temp_dir.path().join("apps");
+    download::set_test_apps_dir(Some(apps_dir.clone()));
+    let config_path = temp_dir.path().join("claude_desktop_config.json");
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let archive = common::tar_gz(&[
+        ("kartan-mcp/bin/kartan", SERVER),
+        ("kartan-mcp/LICENSE", b"MIT"),
+    ]);
+    let url = format!(
+        "{}/kartan.tar.gz",
+        common::serve(vec![("/kartan.tar.gz".to_string(), archive.clone())])
+    );
+
+    // A download that does not match its checksum is never installed
+    set_test_registry("0.3.1", &url, &download::sha256_hex(b"something else"));
+    let error = app::install_app("Kartan", None, "Claude", None).unwrap_err();
+    assert!(error.contains("Checksum mismatch"));
+    assert!(!apps_dir.join("kartan/0.3.1").exists());
+    assert!(!app::is_installed("Kartan", "Claude").unwrap());
+
+    set_test_registry("0.3.1", &url, &download::sha256_hex(&archive));
+    app::install_app("Kartan", None, "Claude", None).unwrap();
+
+    let executable = apps_dir.join("kartan/0.3.1/kartan-mcp/bin/kartan");
+    assert_eq!(std::fs::read(&executable).unwrap(), SERVER);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&executable).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(
+        written["mcpServers"]["kartan"]["command"],
+        json!(executable.to_string_lossy())
+    );
+    assert_eq!(written["mcpServers"]["kartan"]["args"], json!(["--stdio"]));
+
+    // Updating replaces the old version
+    set_test_registry("0.4.0", &url, &download::sha256_hex(&archive));
+    app::install_app("Kartan", None, "Claude", None).unwrap();
+    assert!(apps_dir.join("kartan/0.4.0/kartan-mcp/bin/kartan").exists());
+    assert!(!apps_dir.join("kartan/0.3.1").exists());
+
+    app::uninstall("Kartan", "Claude", None).unwrap();
+    assert!(!apps_dir.join("kartan").exists());
+
+    app::set_test_config_path(None);
+    download::set_test_apps_dir(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_versions_in_use_are_kept() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let apps_dir = temp_dir.path().join("apps");
+    download::set_test_apps_dir(Some(apps_dir.clone()));
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+    app::set_test_config_path(Some(temp_dir.path().join("claude_desktop_config.json")));
+    let project_dir = tempfile::tempdir().unwrap();
+    let project = Some(project_dir.path().to_string_lossy().to_string());
+
+    let archive = common::tar_gz(&[("kartan-mcp/bin/kartan", SERVER)]);
+    let url = format!(
+        "{}/kartan.tar.gz",
+        common::serve(vec![("/kartan.tar.gz".to_string(), archive.clone())])
+    );
+    let sha256 = download::sha256_hex(&archive);
+
+    set_test_registry("0.3.1", &url, &sha256);
+    app::install_app("Kartan", None, "ClaudeCode", project.clone()).unwrap();
+
+    // Claude moves on, the project's Claude Code config still starts 0.3.1
+    set_test_registry("0.4.0", &url, &sha256);
+    app::install_app("Kartan", None, "Claude", None).unwrap();
+    assert!(apps_dir.join("kartan/0.3.1/kartan-mcp/bin/kartan").exists());
+    assert!(apps_dir.join("kartan/0.4.0/kartan-mcp/bin/kartan").exists());
+
+    app::install_app("Kartan", None, "ClaudeCode", project.clone()).unwrap();
+    assert!(!apps_dir.join("kartan/0.3.1").exists());
+    assert!(apps_dir.join("kartan/0.4.0").exists());
+
+    app::uninstall("Kartan", "ClaudeCode", project).unwrap();
+    assert!(apps_dir.join("kartan/0.4.0").exists());
+    app::uninstall("Kartan", "Claude", None).unwrap();
+    assert!(!apps_dir.join("kartan").exists());
+
+    app::set_test_config_path(None);
+    settings::set_test_settings_path(None);
+    download::set_test_apps_dir(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_gateway_keeps_binary() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let apps_dir = temp_dir.path().join("apps");
+    download::set_test_apps_dir(Some(apps_dir.clone()));
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+    gateway::set_test_gateway_config_path(Some(temp_dir.path().join("gateway.json")));
+    app::set_test_config_path(Some(temp_dir.path().join("claude_desktop_config.json")));
+
+    let archive = common::tar_gz(&[("kartan-mcp/bin/kartan", SERVER)]);
+    let url = format!(
+        "{}/kartan.tar.gz",
+        common::serve(vec![("/kartan.tar.gz".to_string(), archive.clone())])
+    );
+    set_test_registry("0.3.1", &url, &download::sha256_hex(&archive));
+
+    app::install_app("Kartan", None, "Claude", None).unwrap();
+    gateway::set_gateway_app("Kartan", true, None).unwrap();
+
+    // The gateway still starts it after the last client entry is gone
+    app::uninstall("Kartan", "Claude", None).unwrap();
+    assert!(apps_dir.join("kartan/0.3.1/kartan-mcp/bin/kartan").exists());
+
+    app::set_test_config_path(None);
+    gateway::set_test_gateway_config_path(None);
+    settings::set_test_settings_path(None);
+    download::set_test_apps_dir(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
@@ -1,7 +1,8 @@
 #[cfg(test)]
 mod common;
 
-use staten_lib::environment;
+use serial_test::serial;
+use staten_lib::{environment, logs, uv};
 
 #[test]
 fn test_environment_setup() {
@@ -19,3 +20,50 @@ fn test_node_environment() {
     assert!(result.unwrap().contains("npx-staten"));
     environment::set_test_mode(false);
 }
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+#[serial]
+fn test_logging_shim_exits_with_server_status() {
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+    use std::process::{Command, Stdio};
+
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    uv::set_test_uv_dir(Some(temp_dir.path().join("bin")));
+    logs::set_test_logs_dir(Some(temp_dir.path().join("logs")));
+
+    // A server that answers one line and fails
+    let server = temp_dir.path().join("server");
+    std::fs::write(&server, "#!/bin/sh\nread line\necho \"$line\"\nexit 3\n").unwrap();
+    std::fs::set_permissions(&server, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let shim_path = environment::ensure_uvx_python_shim().unwrap();
+    let shim = std::fs::read_to_string(&shim_path).unwrap().replace(
+        &environment::get_uvx_path().unwrap(),
+        &server.to_string_lossy(),
+    );
+    std::fs::write(&shim_path, shim).unwrap();
+
+    let mut child = Command::new("sh")
+        .arg(&shim_path)
+        .env(logs::LOG_APP_ENV, "kolada")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"{}\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "{}\n");
+    let protocol =
+        std::fs::read_to_string(temp_dir.path().join("logs/kolada").join(logs::PROTOCOL_LOG))
+            .unwrap();
+    assert_eq!(protocol, "--> {}\n<-- {}\n");
+
+    logs::set_test_logs_dir(None);
+    uv::set_test_uv_dir(None);
+    environment::set_test_mode(false);
+}
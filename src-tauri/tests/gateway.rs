@@ -0,0 +1,106 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    environment,
+    gateway::{self, GATEWAY_MCP_KEY},
+};
+
+#[test]
+fn test_tool_namespacing() {
+    let keys = ["kolada".to_string(), "server.time".to_string()];
+    let prefixes = gateway::tool_prefixes(keys.iter());
+    assert_eq!(prefixes["kolada"], "kolada__");
+    assert_eq!(prefixes["server.time"], "server_time__");
+
+    // Keys that sanitize to the same name still get prefixes of their own
+    let keys = [
+        "server.time".to_string(),
+        "server_time".to_string(),
+        "server time".to_string(),
+        "server_time2".to_string(),
+    ];
+    let prefixes = gateway::tool_prefixes(keys.iter());
+    assert_eq!(prefixes["server_time"], "server_time__");
+    assert_eq!(prefixes["server_time2"], "server_time2__");
+    assert_eq!(prefixes["server time"], "server_time3__");
+    assert_eq!(prefixes["server.time"], "server_time4__");
+    assert_eq!(
+        gateway::resolve_tool("server_time4__now", keys.iter()),
+        Some(("server.time".to_string(), "now".to_string()))
+    );
+
+    let keys = ["kolada".to_string(), "kolada__v2".to_string()];
+    assert_eq!(
+        gateway::resolve_tool("kolada__search", keys.iter()),
+        Some(("kolada".to_string(), "search".to_string()))
+    );
+    // The longest prefix wins when app keys overlap
+    assert_eq!(
+        gateway::resolve_tool("kolada__v2__search", keys.iter()),
+        Some(("kolada__v2".to_string(), "search".to_string()))
+    );
+    assert_eq!(gateway::resolve_tool("swemo__rates", keys.iter()), None);
+    assert_eq!(gateway::resolve_tool("kolada__", keys.iter()), None);
+}
+
+#[test]
+#[serial]
+fn test_gateway_app_config() {
+    environment::set_test_mode(true);
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    gateway::set_test_gateway_config_path(Some(temp_dir.path().join("gateway.json")));
+
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([{
+            "name": "Kolada",
+            "config": {
+                "mcpKey": "kolada",
+                "runtime": "uvx",
+                "args": ["kolada-mcp", "--token", "${KOLADA_TOKEN}"]
+            }
+        }]));
+    }
+
+    gateway::set_gateway_app("Kolada", true, Some(json!({ "KOLADA_TOKEN": "abc" }))).unwrap();
+    let apps = gateway::get_gateway_apps().unwrap();
+    assert_eq!(apps["kolada"]["enabled"], true);
+    assert_eq!(
+        apps["kolada"]["args"],
+        json!(["kolada-mcp", "--token", "abc"])
+    );
+
+    // Disabling keeps the previously stored env
+    gateway::set_gateway_app("Kolada", false, None).unwrap();
+    let config = gateway::load_gateway_config().unwrap();
+    assert!(!config.apps["kolada"].enabled);
+    assert_eq!(config.apps["kolada"].env["KOLADA_TOKEN"], "abc");
+
+    // The client config gets a single gateway entry
+    let config_path = temp_dir.path().join("claude_desktop_config.json");
+    std::fs::write(&config_path, r#"{"mcpServers": {}}"#).unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    gateway::install_staten_gateway("Claude").unwrap();
+    let client_config = app::get_config(&staten_lib::clients::ClientType::Claude).unwrap();
+    assert_eq!(
+        client_config["mcpServers"][GATEWAY_MCP_KEY]["args"],
+        json!(["gateway"])
+    );
+
+    gateway::uninstall_staten_gateway("Claude").unwrap();
+    let client_config = app::get_config(&staten_lib::clients::ClientType::Claude).unwrap();
+    assert!(client_config["mcpServers"].get(GATEWAY_MCP_KEY).is_none());
+
+    app::set_test_config_path(None);
+    gateway::set_test_gateway_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use serde_json::json;
+use staten_lib::jsonc;
+
+const SETTINGS: &str = r#"// Zed settings
+{
+  "theme": "One Dark", // keep me
+  /* fonts */
+  "buffer_font_size": 15,
+  "context_servers": {
+    "old": { "command": { "path": "old" } },
+  },
+  "url": "http://example.com/*not a comment*/",
+}
+"#;
+
+#[test]
+fn test_parse_ignores_comments_and_trailing_commas() {
+    let value = jsonc::parse(SETTINGS).unwrap();
+    assert_eq!(value["theme"], "One Dark");
+    assert_eq!(value["buffer_font_size"], 15);
+    assert_eq!(value["context_servers"]["old"]["command"]["path"], "old");
+    assert_eq!(value["url"], "http://example.com/*not a comment*/");
+
+    assert_eq!(jsonc::parse("  ").unwrap(), json!({}));
+    assert!(jsonc::parse("{ \"a\": }").is_err());
+}
+
+#[test]
+fn test_set_member_replaces_only_that_member() {
+    let updated = jsonc::set_member(
+        SETTINGS,
+        "context_servers",
+        &json!({ "new": { "command": { "path": "new" } } }),
+    )
+    .unwrap();
+
+    assert!(updated
+        .starts_with("// Zed settings\n{\n  \"theme\": \"One Dark\", // keep me\n  /* fonts */\n"));
+    assert!(updated.contains("  \"context_servers\": {\n    \"new\": {\n"));
+    assert!(updated.ends_with("  },\n  \"url\": \"http://example.com/*not a comment*/\",\n}\n"));
+
+    let value = jsonc::parse(&updated).unwrap();
+    assert!(value["context_servers"].get("old").is_none());
+    assert_eq!(value["context_servers"]["new"]["command"]["path"], "new");
+}
+
+#[test]
+fn test_set_member_adds_missing_member() {
+    let text = "{\n  \"theme\": \"One Dark\" // keep me\n}\n";
+    let updated = jsonc::set_member(text, "context_servers", &json!({})).unwrap();
+    assert_eq!(
+        updated,
+        "{\n  \"theme\": \"One Dark\", // keep me\n  \"context_servers\": {}\n}\n"
+    );
+
+    // A trailing comma is already a separator
+    let text = "{\n  \"theme\": \"One Dark\",\n}";
+    let updated = jsonc::set_member(text, "context_servers", &json!({})).unwrap();
+    assert_eq!(
+        updated,
+        "{\n  \"theme\": \"One Dark\",\n  \"context_servers\": {}\n}"
+    );
+
+    assert_eq!(
+        jsonc::set_member("{}", "a", &json!(1)).unwrap(),
+        "{\n  \"a\": 1\n}"
+    );
+    assert_eq!(
+        jsonc::set_member("", "a", &json!(1)).unwrap(),
+        "{\n  \"a\": 1\n}"
+    );
+}
+
+#[test]
+fn test_set_member_keeps_comments_on_unchanged_entries() {
+    let text = r#"{
+  "context_servers": {
+    // Pinned to the old release
+    "time": { "command": { "path": "uvx" } }, // keep me
+    "old": { "command": { "path": "old" } }
+  }
+}
+"#;
+    let updated = jsonc::set_member(
+        text,
+        "context_servers",
+        &json!({
+            "time": { "command": { "path": "uvx" } },
+            "new": { "command": { "path": "new" } }
+        }),
+    )
+    .unwrap();
+    assert_eq!(
+        updated,
+        r#"{
+  "context_servers": {
+    // Pinned to the old release
+    "time": { "command": { "path": "uvx" } }, // keep me
+    "new": {
+      "command": {
+        "path": "new"
+      }
+    }
+  }
+}
+"#
+    );
+
+    // Removing the last entry takes the comma before it too
+    let updated = jsonc::set_member(
+        &updated,
+        "context_servers",
+        &json!({ "time": { "command": { "path": "uvx" } } }),
+    )
+    .unwrap();
+    assert!(updated.contains("\"time\": { \"command\": { \"path\": \"uvx\" } } // keep me\n  }"));
+    assert_eq!(
+        jsonc::parse(&updated).unwrap()["context_servers"],
+        json!({ "time": { "command": { "path": "uvx" } } })
+    );
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use serial_test::serial;
+use staten_lib::logs;
+use std::fs;
+
+fn write_log(dir: &std::path::Path, app: &str, file: &str, content: &str) {
+    let app_dir = dir.join(app);
+    fs::create_dir_all(&app_dir).unwrap();
+    fs::write(app_dir.join(file), content).unwrap();
+}
+
+#[test]
+#[serial]
+fn test_list_and_tail_app_logs() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    logs::set_test_logs_dir(Some(temp_dir.path().to_path_buf()));
+
+    write_log(
+        temp_dir.path(),
+        "kolada",
+        logs::STDERR_LOG,
+        "one\ntwo\nthree\n",
+    );
+    write_log(temp_dir.path(), "kolada", logs::PROTOCOL_LOG, "--> {}\n");
+
+    let listed = logs::list_app_logs().unwrap();
+    let apps = listed.as_array().unwrap();
+    assert_eq!(apps.len(), 1);
+    assert_eq!(apps[0]["app"], "kolada");
+    assert_eq!(apps[0]["files"].as_array().unwrap().len(), 2);
+
+    let tail = logs::tail_app_log("kolada", None, Some(2)).unwrap();
+    assert_eq!(tail, vec!["two", "three"]);
+
+    // Missing logs are not an error
+    let missing = logs::tail_app_log("riksbanken", None, None).unwrap();
+    assert!(missing.is_empty());
+
+    // Paths outside the logs directory are rejected
+    assert!(logs::tail_app_log("..", None, None).is_err());
+    assert!(logs::tail_app_log("kolada", Some("../x".to_string()), None).is_err());
+
+    logs::set_test_logs_dir(None);
+}
+
+#[test]
+#[serial]
+fn test_search_app_logs() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    logs::set_test_logs_dir(Some(temp_dir.path().to_path_buf()));
+
+    write_log(
+        temp_dir.path(),
+        "kolada",
+        logs::STDERR_LOG,
+        "ok\nERROR: timeout\n",
+    );
+    write_log(temp_dir.path(), "kolada", "stderr.log.1", "error: old\n");
+    write_log(
+        temp_dir.path(),
+        "swemo",
+        logs::STDERR_LOG,
+        "Error: bad key\n",
+    );
+
+    let all = logs::search_app_logs("error", None, None).unwrap();
+    assert_eq!(all.as_array().unwrap().len(), 3);
+    assert_eq!(all[0]["file"], "stderr.log");
+    assert_eq!(all[0]["line"], 2);
+
+    let single = logs::search_app_logs("error", Some("swemo".to_string()), None).unwrap();
+    assert_eq!(single.as_array().unwrap().len(), 1);
+    assert_eq!(single[0]["app"], "swemo");
+
+    let limited = logs::search_app_logs("error", None, Some(1)).unwrap();
+    assert_eq!(limited.as_array().unwrap().len(), 1);
+
+    assert!(logs::search_app_logs("", None, None).is_err());
+
+    logs::set_test_logs_dir(None);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::ClientType,
+    environment,
+    manifest::{self, ChangeAction, EnvSource},
+};
+use std::path::Path;
+
+const MANIFEST: &str = r#"
+clients = ["Claude"]
+
+[[apps]]
+name = "Kolada"
+version = "0.4.0"
+
+[apps.env]
+KOLADA_REGION = "Stockholm"
+KOLADA_TOKEN = { env = "STATEN_TEST_KOLADA_TOKEN" }
+"#;
+
+fn setup_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = Some(json!([{
+        "name": "Kolada",
+        "config": {
+            "mcpKey": "kolada",
+            "runtime": "uvx",
+            "args": ["kolada-mcp", "--region", "${KOLADA_REGION}"]
+        }
+    }, {
+        "name": "Time",
+        "config": {
+            "mcpKey": "time",
+            "runtime": "npx",
+            "args": ["-y", "mcp-server-time"]
+        }
+    }]));
+}
+
+#[test]
+fn test_parse_manifest_env_sources() {
+    let parsed = manifest::parse_manifest(MANIFEST, Path::new("staten.toml")).unwrap();
+    assert_eq!(parsed.clients, vec!["Claude"]);
+    assert_eq!(parsed.apps[0].version.as_deref(), Some("0.4.0"));
+    assert_eq!(
+        parsed.apps[0].env["KOLADA_REGION"],
+        EnvSource::Literal("Stockholm".to_string())
+    );
+    assert_eq!(
+        parsed.apps[0].env["KOLADA_TOKEN"],
+        EnvSource::Env {
+            env: "STATEN_TEST_KOLADA_TOKEN".to_string()
+        }
+    );
+
+    let parsed = manifest::parse_manifest(
+        r#"{"apps": [{"name": "Time", "clients": ["Cursor"], "env": {"T": {"keychain": "staten", "account": "time"}}}]}"#,
+        Path::new("staten.json"),
+    )
+    .unwrap();
+    assert_eq!(
+        parsed.apps[0].env["T"],
+        EnvSource::Keychain {
+            keychain: "staten".to_string(),
+            account: Some("time".to_string())
+        }
+    );
+}
+
+#[test]
+fn test_pin_version() {
+    let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+        manifest::pin_version("npx", &args(&["-y", "@scope/server@1.0.0"]), "2.0.0"),
+        args(&["-y", "@scope/server@2.0.0"])
+    );
+    assert_eq!(
+        manifest::pin_version("uvx", &args(&["--from", "kolada-mcp", "kolada"]), "0.4.0"),
+        args(&["--from", "kolada-mcp@0.4.0", "kolada"])
+    );
+    assert_eq!(
+        manifest::pin_version("/usr/bin/server", &args(&["serve"]), "1.0"),
+        args(&["serve"])
+    );
+}
+
+#[test]
+#[serial]
+fn test_plan_and_apply_manifest() {
+    environment::set_test_mode(true);
+    setup_registry();
+    std::env::set_var("STATEN_TEST_KOLADA_TOKEN", "secret-token");
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("claude_desktop_config.json");
+    std::fs::write(
+        &config_path,
+        r#"{"mcpServers": {"time": {"command": "npx", "args": []}, "custom": {"command": "x"}}}"#,
+    )
+    .unwrap();
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let mut parsed = manifest::parse_manifest(MANIFEST, Path::new("staten.toml")).unwrap();
+
+    // A dry run reports the change without touching the config
+    let plan = manifest::reconcile(&parsed, true).unwrap();
+    assert!(!plan.applied);
+    assert_eq!(plan.changes.len(), 1);
+    assert_eq!(plan.changes[0].action, ChangeAction::Add);
+    assert!(plan.changes[0]
+        .diff
+        .iter()
+        .any(|line| line == "env.KOLADA_TOKEN: (unset) -> ********"));
+    assert!(!app::is_installed("Kolada", ClientType::Claude.as_str()).unwrap());
+
+    let plan = manifest::reconcile(&parsed, false).unwrap();
+    assert!(plan.applied);
+    let config = app::get_config(&ClientType::Claude).unwrap();
+    assert_eq!(
+        config["mcpServers"]["kolada"]["args"],
+        json!(["kolada-mcp@0.4.0", "--region", "Stockholm"])
+    );
+    assert_eq!(
+        config["mcpServers"]["kolada"]["env"]["KOLADA_TOKEN"],
+        "secret-token"
+    );
+
+    // Applying again is a no-op
+    let plan = manifest::reconcile(&parsed, true).unwrap();
+    assert_eq!(plan.changes[0].action, ChangeAction::Unchanged);
+
+    // Pruning removes registry apps that are not listed, but leaves custom entries
+    parsed.prune = true;
+    let plan = manifest::reconcile(&parsed, false).unwrap();
+    assert!(plan
+        .changes
+        .iter()
+        .any(|c| c.mcp_key == "time" && c.action == ChangeAction::Remove));
+    let config = app::get_config(&ClientType::Claude).unwrap();
+    assert!(config["mcpServers"].get("time").is_none());
+    assert!(config["mcpServers"].get("custom").is_some());
+
+    std::env::remove_var("STATEN_TEST_KOLADA_TOKEN");
+    app::set_test_config_path(None);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    environment::set_test_mode(false);
+}
@@ -0,0 +1,259 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    environment, node,
+};
+
+#[test]
+fn test_dist_name() {
+    assert_eq!(
+        node::dist_name("v20.9.0", "macos", "aarch64").as_deref(),
+        Some("node-v20.9.0-darwin-arm64")
+    );
+    assert_eq!(
+        node::dist_name("v20.9.0", "windows", "x86_64").as_deref(),
+        Some("node-v20.9.0-win-x64")
+    );
+    assert_eq!(
+        node::dist_name("v20.9.0", "linux", "x86_64").as_deref(),
+        Some("node-v20.9.0-linux-x64")
+    );
+    assert_eq!(node::dist_name("v20.9.0", "freebsd", "x86_64"), None);
+    assert_eq!(node::dist_name("v20.9.0", "linux", "riscv64"), None);
+}
+
+#[test]
+fn test_parse_shasums() {
+    let shasums = "\
+        5f3d3f2a1e0b8a6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a3b2c  node-v20.9.0-darwin-arm64.tar.gz\n\
+        A1B2C3D4E5F60718293A4B5C6D7E8F90A1B2C3D4E5F60718293A4B5C6D7E8F90  node-v20.9.0-darwin-x64.tar.gz\n";
+
+    assert_eq!(
+        node::parse_shasums(shasums, "node-v20.9.0-darwin-x64.tar.gz").as_deref(),
+        Some("a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f90")
+    );
+    assert_eq!(
+        node::parse_shasums(shasums, "node-v20.9.0-darwin-x64"),
+        None
+    );
+    assert_eq!(
+        node::parse_shasums(shasums, "node-v20.9.0-linux-x64.tar.gz"),
+        None
+    );
+}
+
+#[test]
+fn test_parse_node_range() {
+    let cases = [
+        (">=18", "v18.0.0", true),
+        (">=18", "v16.20.2", false),
+        (">= 18 <21", "v20.18.1", true),
+        (">= 18 <21", "v21.0.0", false),
+        ("^20.1 || 22.x", "v20.0.0", false),
+        ("^20.1 || 22.x", "v22.12.0", true),
+        ("18 - 20", "v20.18.1", true),
+        ("18 - 20", "v21.7.3", false),
+        ("v18", "v18.20.5", true),
+        ("*", "v23.4.0", true),
+    ];
+    for (range, version, expected) in cases {
+        let parsed = node::parse_node_range(range).unwrap();
+        assert_eq!(
+            node::satisfies(version, &parsed),
+            expected,
+            "{} {}",
+            range,
+            version
+        );
+    }
+    assert!(node::parse_node_range("lts").is_err());
+}
+
+#[test]
+fn test_pick_version() {
+    let index = vec![
+        node::NodeRelease {
+            version: "v23.4.0".to_string(),
+            lts: false,
+        },
+        node::NodeRelease {
+            version: "v22.12.0".to_string(),
+            lts: true,
+        },
+        node::NodeRelease {
+            version: "v18.20.5".to_string(),
+            lts: true,
+        },
+        node::NodeRelease {
+            version: "v18.21.0-rc".to_string(),
+            lts: false,
+        },
+    ];
+    let installed = vec!["v18.19.0".to_string()];
+    let pick = |range| node::pick_version(range, "v22.12.0", &installed, &index).unwrap();
+
+    assert_eq!(pick(">=20").as_deref(), Some("v22.12.0"));
+    // An installed version is used before downloading a newer one
+    assert_eq!(pick("^18").as_deref(), Some("v18.19.0"));
+    assert_eq!(pick(">=23").as_deref(), Some("v23.4.0"));
+    assert_eq!(pick(">=24"), None);
+
+    assert_eq!(
+        node::pick_version("^18", "v22.12.0", &[], &index)
+            .unwrap()
+            .as_deref(),
+        Some("v18.20.5")
+    );
+    assert_eq!(node::normalize_version("22.12.0").unwrap(), "v22.12.0");
+    assert!(node::normalize_version("22").is_err());
+}
+
+#[test]
+#[serial]
+fn test_app_node_version() {
+    environment::set_test_mode(true);
+    let index = json!([
+        { "version": "v23.4.0", "lts": false },
+        { "version": "v22.12.0", "lts": "Jod" },
+        { "version": "v18.20.5", "lts": "Hydrogen" }
+    ]);
+    let base = common::serve(vec![(
+        "/index.json".to_string(),
+        index.to_string().into_bytes(),
+    )]);
+    std::env::set_var(node::NODE_MIRROR_ENV, &base);
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([
+            {
+                "name": "Legacy",
+                "config": { "mcpKey": "legacy", "runtime": "npx", "args": ["-y", "legacy-mcp"], "engines": { "node": "^18" } }
+            },
+            {
+                "name": "Current",
+                "config": { "mcpKey": "current", "runtime": "npx", "args": ["-y", "current-mcp"], "engines": { "node": ">=20" } }
+            },
+            {
+                "name": "Future",
+                "config": { "mcpKey": "future", "runtime": "npx", "args": ["-y", "future-mcp"], "engines": { "node": ">=99" } }
+            }
+        ]));
+    }
+
+    let configs = app::get_app_configs().unwrap();
+    let config = |name: &str| {
+        configs
+            .iter()
+            .find(|(app_name, _)| app_name == name)
+            .map(|(_, config)| config.clone())
+            .unwrap()
+    };
+    assert_eq!(config("Legacy").node_version.as_deref(), Some("v18.20.5"));
+    assert_eq!(
+        config("Legacy").command,
+        "/test/.local/share/staten/bin/npx-staten-v18.20.5"
+    );
+    assert_eq!(config("Current").node_version, None);
+    assert_eq!(
+        config("Current").command,
+        "/test/.local/share/staten/bin/npx-staten"
+    );
+    assert_eq!(config("Future").command, "");
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    app::set_test_config_path(Some(temp_dir.path().join("claude_desktop_config.json")));
+    let error = app::install_app("Future", None, "Claude", None).unwrap_err();
+    assert!(error.contains("Node.js"));
+    app::install_app("Legacy", None, "Claude", None).unwrap();
+
+    app::set_test_config_path(None);
+    *APP_REGISTRY_CACHE.lock().unwrap() = None;
+    std::env::remove_var(node::NODE_MIRROR_ENV);
+    environment::set_test_mode(false);
+}
+
+#[cfg(not(target_os = "windows"))]
+mod install {
+    use super::*;
+    use staten_lib::download;
+
+    fn node_archive(dist: &str) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, mode) in [
+            ("bin/node", 0o755),
+            ("lib/node_modules/npm/bin/npx-cli.js", 0o755),
+        ] {
+            let contents = b"#!/bin/sh\n";
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(mode);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("{}/{}", dist, path), &contents[..])
+                .unwrap();
+        }
+        // npx is a relative symlink in the official builds
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder
+            .append_link(
+                &mut header,
+                format!("{}/bin/npx", dist),
+                "../lib/node_modules/npm/bin/npx-cli.js",
+            )
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn test_install_node() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        node::set_test_node_dir(Some(temp_dir.path().to_path_buf()));
+
+        let dist =
+            node::dist_name("v20.9.0", std::env::consts::OS, std::env::consts::ARCH).unwrap();
+        let archive_name = format!("{}.tar.gz", dist);
+        let archive = node_archive(&dist);
+
+        // A download that does not match SHASUMS256.txt is never installed
+        let wrong = format!("{}  {}\n", download::sha256_hex(b"tampered"), archive_name);
+        let base = common::serve(vec![
+            ("/v20.9.0/SHASUMS256.txt".to_string(), wrong.into_bytes()),
+            (format!("/v20.9.0/{}", archive_name), archive.clone()),
+        ]);
+        std::env::set_var(node::NODE_MIRROR_ENV, &base);
+        let error = node::install_node("v20.9.0").unwrap_err();
+        assert!(error.contains("Checksum mismatch"));
+        assert!(!node::is_node_installed("v20.9.0"));
+
+        let shasums = format!("{}  {}\n", download::sha256_hex(&archive), archive_name);
+        let base = common::serve(vec![
+            ("/v20.9.0/SHASUMS256.txt".to_string(), shasums.into_bytes()),
+            (format!("/v20.9.0/{}", archive_name), archive),
+        ]);
+        std::env::set_var(node::NODE_MIRROR_ENV, format!("{}/", base));
+        let (node_path, npx_path) = node::install_node("v20.9.0").unwrap();
+        assert_eq!(
+            std::path::PathBuf::from(&node_path),
+            temp_dir.path().join("v20.9.0/bin/node")
+        );
+        assert!(std::path::Path::new(&npx_path).exists());
+        assert!(node::is_node_installed("v20.9.0"));
+
+        // Nothing is left over next to the installed version
+        let entries: Vec<_> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec!["v20.9.0"]);
+
+        std::env::remove_var(node::NODE_MIRROR_ENV);
+        node::set_test_node_dir(None);
+    }
+}
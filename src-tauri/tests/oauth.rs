@@ -0,0 +1,258 @@
+mod common;
+
+use serde_json::{json, Value};
+use serial_test::serial;
+use staten_lib::{oauth, secrets};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+// A local authorization server that accepts every sign-in and checks PKCE
+struct MockAuthServer {
+    base: String,
+    challenge: Arc<Mutex<Option<String>>>,
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    reqwest::Url::parse(&format!("http://localhost/?{}", query))
+        .unwrap()
+        .query_pairs()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn read_request(stream: &mut TcpStream) -> (String, String, String) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    (method, target, String::from_utf8(body).unwrap())
+}
+
+fn reply(stream: &mut TcpStream, status: &str, headers: &str, body: &str) {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        headers,
+        body.len(),
+        body
+    )
+    .unwrap();
+}
+
+fn reply_json(stream: &mut TcpStream, body: Value) {
+    reply(
+        stream,
+        "200 OK",
+        "Content-Type: application/json\r\n",
+        &body.to_string(),
+    );
+}
+
+impl MockAuthServer {
+    fn start() -> MockAuthServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let challenge = Arc::new(Mutex::new(None));
+
+        let server_base = base.clone();
+        let server_challenge = Arc::clone(&challenge);
+        std::thread::spawn(move || {
+            let mut refreshed = false;
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let (method, target, body) = read_request(&mut stream);
+                let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+
+                match (method.as_str(), path) {
+                    ("GET", "/.well-known/oauth-protected-resource/mcp") => reply_json(
+                        &mut stream,
+                        json!({
+                            "resource": format!("{}/mcp", server_base),
+                            "authorization_servers": [server_base]
+                        }),
+                    ),
+                    ("GET", "/.well-known/oauth-authorization-server") => reply_json(
+                        &mut stream,
+                        json!({
+                            "issuer": server_base,
+                            "authorization_endpoint": format!("{}/authorize", server_base),
+                            "token_endpoint": format!("{}/token", server_base),
+                            "registration_endpoint": format!("{}/register", server_base)
+                        }),
+                    ),
+                    ("POST", "/register") => {
+                        let request: Value = serde_json::from_str(&body).unwrap();
+                        assert_eq!(request["token_endpoint_auth_method"], "none");
+                        reply_json(&mut stream, json!({ "client_id": "test-client" }));
+                    }
+                    ("GET", "/authorize") => {
+                        let params = parse_query(query);
+                        assert_eq!(params["code_challenge_method"], "S256");
+                        assert_eq!(params["client_id"], "test-client");
+                        *server_challenge.lock().unwrap() = Some(params["code_challenge"].clone());
+                        let location = format!(
+                            "{}?code=test-code&state={}",
+                            params["redirect_uri"], params["state"]
+                        );
+                        reply(
+                            &mut stream,
+                            "302 Found",
+                            &format!("Location: {}\r\n", location),
+                            "",
+                        );
+                    }
+                    ("POST", "/token") => {
+                        let form = parse_query(&body);
+                        match form["grant_type"].as_str() {
+                            "authorization_code" => {
+                                assert_eq!(form["code"], "test-code");
+                                let expected = server_challenge.lock().unwrap().clone();
+                                if Some(oauth::code_challenge(&form["code_verifier"])) != expected {
+                                    reply(&mut stream, "400 Bad Request", "", "invalid_grant");
+                                    continue;
+                                }
+                                reply_json(
+                                    &mut stream,
+                                    json!({
+                                        "access_token": "access-1",
+                                        "refresh_token": "refresh-1",
+                                        "token_type": "Bearer",
+                                        "expires_in": 0
+                                    }),
+                                );
+                            }
+                            "refresh_token" => {
+                                assert_eq!(form["refresh_token"], "refresh-1");
+                                // Each refresh token is good for one refresh
+                                if refreshed {
+                                    reply(&mut stream, "400 Bad Request", "", "invalid_grant");
+                                    continue;
+                                }
+                                refreshed = true;
+                                reply_json(
+                                    &mut stream,
+                                    json!({
+                                        "access_token": "access-2",
+                                        "token_type": "Bearer",
+                                        "expires_in": 3600
+                                    }),
+                                );
+                            }
+                            other => panic!("Unexpected grant type {}", other),
+                        }
+                    }
+                    _ => reply(&mut stream, "404 Not Found", "", ""),
+                }
+            }
+        });
+
+        MockAuthServer { base, challenge }
+    }
+}
+
+#[test]
+fn test_code_challenge() {
+    // The example from RFC 7636, appendix B
+    assert_eq!(
+        oauth::code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+
+    let verifier = oauth::code_verifier();
+    assert_eq!(verifier.len(), 64);
+    assert_ne!(verifier, oauth::code_verifier());
+}
+
+#[test]
+fn test_discover() {
+    let server = MockAuthServer::start();
+    let metadata = oauth::discover(&format!("{}/mcp", server.base)).unwrap();
+    assert_eq!(metadata.token_endpoint, format!("{}/token", server.base));
+    assert_eq!(
+        metadata.registration_endpoint,
+        Some(format!("{}/register", server.base))
+    );
+}
+
+#[test]
+#[serial]
+fn test_authorize_refresh_and_sign_out() {
+    secrets::set_test_secret_store(true);
+    let server = MockAuthServer::start();
+    let server_url = format!("{}/mcp", server.base);
+
+    // The browser follows the redirect back to Staten's loopback listener
+    let tokens = oauth::authorize_with(&server_url, |url| {
+        std::thread::spawn(move || {
+            let _ = reqwest::blocking::get(url);
+        });
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(tokens.access_token, "access-1");
+    assert_eq!(tokens.client_id, "test-client");
+    assert!(server.challenge.lock().unwrap().is_some());
+    assert_eq!(oauth::load_tokens(&server_url), Some(tokens.clone()));
+
+    // The access token is issued expired, so it is refreshed and stored again
+    let refreshed = oauth::refresh_if_expired(&server_url, tokens.clone()).unwrap();
+    assert_eq!(refreshed.access_token, "access-2");
+    assert_eq!(refreshed.refresh_token.as_deref(), Some("refresh-1"));
+    assert_eq!(oauth::access_token(&server_url).unwrap(), "access-2");
+
+    // A proxy still holding the old tokens picks up the stored ones instead
+    // of spending the refresh token again
+    let stale = oauth::refresh_if_expired(&server_url, tokens).unwrap();
+    assert_eq!(stale, refreshed);
+
+    oauth::sign_out(&server_url).unwrap();
+    assert_eq!(oauth::load_tokens(&server_url), None);
+    assert!(oauth::access_token(&server_url).is_err());
+
+    secrets::set_test_secret_store(false);
+}
+
+#[test]
+#[serial]
+fn test_stray_redirect_does_not_end_sign_in() {
+    secrets::set_test_secret_store(true);
+    let server = MockAuthServer::start();
+    let server_url = format!("{}/mcp", server.base);
+
+    let tokens = oauth::authorize_with(&server_url, |url| {
+        let params = parse_query(url.split_once('?').unwrap().1);
+        let stray = format!("{}?code=other&state=not-ours", params["redirect_uri"]);
+        std::thread::spawn(move || {
+            let response = reqwest::blocking::get(stray).unwrap();
+            assert_eq!(response.status(), 400);
+            let _ = reqwest::blocking::get(url);
+        });
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(tokens.access_token, "access-1");
+
+    oauth::sign_out(&server_url).unwrap();
+    secrets::set_test_secret_store(false);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app, environment,
+    onboarding::{self, OnboardingStep},
+    settings,
+};
+
+#[test]
+fn test_step_names_round_trip() {
+    for step in OnboardingStep::all() {
+        assert_eq!(OnboardingStep::parse(step.as_str()), Some(step));
+    }
+    assert_eq!(OnboardingStep::parse("unknown"), None);
+}
+
+#[test]
+#[serial]
+fn test_onboarding_resumes_from_first_open_step() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+
+    let state = onboarding::get_onboarding_state().unwrap();
+    assert_eq!(state["currentStep"], "environment_ready");
+    assert_eq!(state["completed"], false);
+
+    onboarding::advance_onboarding("environment_ready").unwrap();
+    let first =
+        settings::load_settings().unwrap().onboarding[&OnboardingStep::EnvironmentReady].clone();
+
+    // Advancing twice keeps the original timestamp
+    let state = onboarding::advance_onboarding("environment_ready").unwrap();
+    assert_eq!(state["steps"][0]["completedAt"], json!(first));
+    assert_eq!(state["currentStep"], "client_chosen");
+
+    // A fresh load (as after a crash) sees the same progress
+    settings::update_settings(json!({"defaultClient": "Cursor"})).unwrap();
+    let state = onboarding::get_onboarding_state().unwrap();
+    assert_eq!(state["currentStep"], "staten_mcp_installed");
+    assert!(!app::check_onboarding_completed().unwrap());
+
+    assert!(onboarding::advance_onboarding("nope").is_err());
+
+    onboarding::advance_onboarding("staten_mcp_installed").unwrap();
+    let state = onboarding::advance_onboarding("first_app_installed").unwrap();
+    assert_eq!(state["completed"], true);
+    assert_eq!(state["currentStep"], serde_json::Value::Null);
+    assert!(app::check_onboarding_completed().unwrap());
+
+    app::reset_onboarding_completed().unwrap();
+    let state = onboarding::get_onboarding_state().unwrap();
+    assert_eq!(state["currentStep"], "environment_ready");
+
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+fn test_migrate_completed_flag_to_steps() {
+    let v2 = json!({"schemaVersion": 2, "onboardingCompleted": true});
+    let migrated = settings::migrate(v2, false).unwrap();
+    let parsed: settings::Settings = serde_json::from_value(migrated).unwrap();
+    assert_eq!(onboarding::current_step(&parsed.onboarding), None);
+
+    let v2 = json!({"schemaVersion": 2, "onboardingCompleted": false});
+    let parsed: settings::Settings =
+        serde_json::from_value(settings::migrate(v2, false).unwrap()).unwrap();
+    assert!(parsed.onboarding.is_empty());
+}
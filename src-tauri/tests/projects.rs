@@ -0,0 +1,115 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    clients::{self, ClientType},
+    environment, projects, settings,
+};
+
+#[test]
+fn test_project_config_paths() {
+    let dir = std::path::Path::new("/work/repo");
+    assert_eq!(
+        clients::project_config_path(&ClientType::Cursor, dir).unwrap(),
+        dir.join(".cursor").join("mcp.json")
+    );
+    assert_eq!(
+        clients::project_config_path(&ClientType::VSCode, dir).unwrap(),
+        dir.join(".vscode").join("mcp.json")
+    );
+    assert_eq!(
+        clients::project_config_path(&ClientType::ClaudeCode, dir).unwrap(),
+        dir.join(".mcp.json")
+    );
+    assert!(clients::project_config_path(&ClientType::Claude, dir).is_err());
+}
+
+#[test]
+#[serial]
+fn test_project_install_is_listed() {
+    environment::set_test_mode(true);
+    let settings_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(settings_dir.path().join("settings.json")));
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = Some(json!([{
+            "name": "Kolada",
+            "description": "Municipal key figures",
+            "config": {
+                "mcpKey": "kolada",
+                "runtime": "uvx",
+                "args": ["kolada-mcp"]
+            }
+        }]));
+    }
+
+    let project = tempfile::tempdir().unwrap();
+    let project_path = project.path().to_string_lossy().to_string();
+    app::install_app(
+        "Kolada",
+        None,
+        ClientType::Cursor.as_str(),
+        Some(project_path.clone()),
+    )
+    .unwrap();
+    assert!(project.path().join(".cursor").join("mcp.json").exists());
+
+    let statuses = app::app_statuses("Cursor", Some(project_path.clone())).unwrap();
+    assert_eq!(statuses["installed"]["Kolada"], true);
+
+    let listed = projects::list_projects().unwrap();
+    assert_eq!(
+        listed,
+        json!([{
+            "path": project_path,
+            "exists": true,
+            "configs": [{
+                "client": "Cursor",
+                "path": project.path().join(".cursor").join("mcp.json").to_string_lossy(),
+                "servers": ["kolada"]
+            }]
+        }])
+    );
+
+    // Installing again does not list the project twice
+    app::install_app("Kolada", None, "Cursor", Some(project_path.clone())).unwrap();
+    assert_eq!(
+        projects::list_projects().unwrap().as_array().unwrap().len(),
+        1
+    );
+
+    assert_eq!(
+        projects::remove_project(project_path.clone()).unwrap(),
+        json!([])
+    );
+    assert!(projects::remove_project(project_path).is_err());
+
+    {
+        let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+        *cache = None;
+    }
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_add_project_needs_an_existing_directory() {
+    environment::set_test_mode(true);
+    let settings_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(settings_dir.path().join("settings.json")));
+
+    assert!(projects::add_project("relative/path".to_string()).is_err());
+    assert!(projects::add_project("/definitely/not/here".to_string()).is_err());
+
+    let project = tempfile::tempdir().unwrap();
+    std::fs::write(project.path().join(".mcp.json"), r#"{"mcpServers": {}}"#).unwrap();
+    let listed = projects::add_project(project.path().to_string_lossy().to_string()).unwrap();
+    assert_eq!(listed[0]["configs"][0]["client"], "ClaudeCode");
+    assert_eq!(listed[0]["configs"][0]["servers"], json!([]));
+
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    environment, python, uv,
+};
+
+fn set_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = Some(json!([
+        {
+            "name": "Statistik",
+            "config": {
+                "mcpKey": "statistik",
+                "runtime": "uvx",
+                "args": ["scb-mcp"],
+                "requires-python": ">= 3.11, <3.13"
+            }
+        },
+        {
+            "name": "Time",
+            "config": { "mcpKey": "time", "runtime": "uvx", "args": ["mcp-server-time"] }
+        },
+        {
+            "name": "Broken",
+            "config": {
+                "mcpKey": "broken",
+                "runtime": "uvx",
+                "args": ["broken-mcp"],
+                "requires-python": "newest"
+            }
+        }
+    ]));
+}
+
+fn clear_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = None;
+}
+
+#[test]
+fn test_parse_requirement() {
+    assert_eq!(python::parse_requirement("3.12").unwrap(), "3.12");
+    assert_eq!(
+        python::parse_requirement(">= 3.10, < 3.13").unwrap(),
+        ">=3.10,<3.13"
+    );
+    assert_eq!(python::parse_requirement("~=3.11").unwrap(), "~=3.11");
+    assert_eq!(python::parse_requirement("==3.12.*").unwrap(), "==3.12.*");
+
+    assert!(python::parse_requirement("").is_err());
+    assert!(python::parse_requirement("newest").is_err());
+    assert!(python::parse_requirement(">=3.10,").is_err());
+    assert!(python::parse_requirement("3.12; rm -rf /").is_err());
+}
+
+#[test]
+#[serial]
+fn test_install_app_with_python() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("claude_desktop_config.json");
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::install_app("Statistik", None, "Claude", None).unwrap();
+    app::install_app("Time", None, "Claude", None).unwrap();
+
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(
+        written["mcpServers"]["statistik"]["args"],
+        json!(["--python", ">=3.11,<3.13", "scb-mcp"])
+    );
+    assert_eq!(
+        written["mcpServers"]["time"]["args"],
+        json!(["mcp-server-time"])
+    );
+    // Only apps with a requirement are pointed at Staten's interpreters
+    assert_eq!(
+        written["mcpServers"]["statistik"]["command"],
+        json!(environment::get_uvx_python_shim_path().to_string_lossy())
+    );
+    assert_eq!(
+        written["mcpServers"]["time"]["command"],
+        json!(environment::get_uvx_shim_path().to_string_lossy())
+    );
+
+    // An unreadable requirement is not silently dropped
+    let statuses = app::app_statuses("Claude", None).unwrap();
+    assert_eq!(statuses["configured"]["Broken"], false);
+    assert!(app::install_app("Broken", None, "Claude", None)
+        .unwrap_err()
+        .contains("requires-python"));
+
+    let report = python::python_report().unwrap();
+    let apps = report["apps"].as_array().unwrap();
+    assert_eq!(apps.len(), 3);
+    let statistik = apps.iter().find(|a| a["app"] == "Statistik").unwrap();
+    assert_eq!(statistik["requiresPython"], ">=3.11,<3.13");
+    assert!(statistik["python"]
+        .as_str()
+        .unwrap()
+        .starts_with(&*python::python_root().to_string_lossy()));
+    let time = apps.iter().find(|a| a["app"] == "Time").unwrap();
+    assert_eq!(time["requiresPython"], serde_json::Value::Null);
+
+    app::set_test_config_path(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_install_writes_python_shim_before_checking_it() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    // A real bin dir, so the command path is checked like on a user's machine
+    let temp_dir = tempfile::tempdir().unwrap();
+    uv::set_test_uv_dir(Some(temp_dir.path().join("bin")));
+    let config_path = temp_dir.path().join("claude_desktop_config.json");
+    app::set_test_config_path(Some(config_path.clone()));
+
+    let shim_path = environment::get_uvx_python_shim_path();
+    assert!(!shim_path.exists());
+    app::install_app("Statistik", None, "Claude", None).unwrap();
+    assert!(shim_path.is_file());
+
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(
+        written["mcpServers"]["statistik"]["command"],
+        json!(shim_path.to_string_lossy())
+    );
+
+    app::set_test_config_path(None);
+    uv::set_test_uv_dir(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
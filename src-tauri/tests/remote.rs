@@ -0,0 +1,313 @@
+mod common;
+
+use serde_json::json;
+use serial_test::serial;
+use staten_lib::{
+    app::{self, APP_REGISTRY_CACHE},
+    client_format,
+    clients::ClientType,
+    environment,
+    remote::{self, RemoteServer, RemoteTransport, SseEvent},
+};
+use std::collections::BTreeMap;
+
+fn set_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = Some(json!([{
+        "name": "Riksdagen",
+        "description": "Documents of the Swedish parliament",
+        "config": {
+            "mcpKey": "riksdagen",
+            "transport": "http",
+            "url": "https://mcp.example.se/riksdagen",
+            "headers": { "Authorization": "Bearer ${RIKSDAGEN_TOKEN}" }
+        }
+    }]));
+}
+
+fn clear_test_registry() {
+    let mut cache = APP_REGISTRY_CACHE.lock().unwrap();
+    *cache = None;
+}
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn sample_server() -> RemoteServer {
+    RemoteServer {
+        transport: RemoteTransport::Sse,
+        url: "https://mcp.example.se/sse".to_string(),
+        headers: BTreeMap::from([("Authorization".to_string(), "Bearer abc".to_string())]),
+        oauth: false,
+    }
+}
+
+#[test]
+fn test_proxy_args_round_trip() {
+    let server = sample_server();
+    let args = remote::proxy_args(&server);
+    // Headers stay out of the process list
+    assert_eq!(
+        args,
+        strings(&["proxy", "--transport", "sse", "https://mcp.example.se/sse"])
+    );
+    let env = remote::proxy_env(&server);
+    assert_eq!(
+        env[remote::PROXY_HEADERS_ENV],
+        json!("Authorization: Bearer abc")
+    );
+
+    assert_eq!(
+        remote::parse_proxy_entry(&remote::proxy_command(), &args, Some(&env)),
+        Some(server.clone())
+    );
+    // The app may have moved since the entry was written
+    assert_eq!(
+        remote::parse_proxy_entry(
+            "/Applications/Staten.app/Contents/MacOS/staten",
+            &args,
+            Some(&env)
+        ),
+        Some(server.clone())
+    );
+    assert_eq!(remote::parse_proxy_entry("uvx", &args, Some(&env)), None);
+
+    // Entries from before headers moved to the environment
+    let legacy = strings(&[
+        "proxy",
+        "--transport",
+        "sse",
+        "--header",
+        "Authorization: Bearer abc",
+        "https://mcp.example.se/sse",
+    ]);
+    assert_eq!(remote::parse_proxy_args(&legacy[1..]).unwrap(), server);
+    assert_eq!(
+        remote::parse_proxy_entry("staten", &legacy, None),
+        Some(server)
+    );
+}
+
+#[test]
+fn test_parse_proxy_headers() {
+    assert_eq!(
+        remote::parse_proxy_headers("Authorization: Bearer abc\nX-Team:  staten \n").unwrap(),
+        BTreeMap::from([
+            ("Authorization".to_string(), "Bearer abc".to_string()),
+            ("X-Team".to_string(), "staten".to_string()),
+        ])
+    );
+    assert!(remote::parse_proxy_headers("no-colon").is_err());
+    assert!(remote::proxy_env(&RemoteServer {
+        headers: BTreeMap::new(),
+        ..sample_server()
+    })
+    .is_empty());
+}
+
+#[test]
+fn test_parse_proxy_args_errors() {
+    let parsed = remote::parse_proxy_args(&strings(&["https://mcp.example.se/mcp"])).unwrap();
+    assert_eq!(parsed.transport, RemoteTransport::Http);
+    assert!(parsed.headers.is_empty());
+
+    assert!(remote::parse_proxy_args(&[]).is_err());
+    assert!(remote::parse_proxy_args(&strings(&["--transport", "ws", "https://x"])).is_err());
+    assert!(remote::parse_proxy_args(&strings(&["--header", "no-colon", "https://x"])).is_err());
+    assert!(remote::parse_proxy_args(&strings(&["https://x", "https://y"])).is_err());
+}
+
+#[test]
+fn test_read_events() {
+    let stream = ": keep-alive\n\
+        event: endpoint\n\
+        data: /messages?session=1\n\
+        \n\
+        data: {\"id\":1}\r\n\
+        \r\n\
+        data: first\n\
+        data: second\n\
+        \n\
+        data: never dispatched";
+
+    let mut events = Vec::new();
+    remote::read_events(stream.as_bytes(), |event| {
+        events.push(event);
+        true
+    })
+    .unwrap();
+
+    assert_eq!(
+        events,
+        vec![
+            SseEvent {
+                event: "endpoint".to_string(),
+                data: "/messages?session=1".to_string()
+            },
+            SseEvent {
+                event: "message".to_string(),
+                data: "{\"id\":1}".to_string()
+            },
+            SseEvent {
+                event: "message".to_string(),
+                data: "first\nsecond".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_native_remote_entries() {
+    let staten = json!({ "mcpServers": { "riksdagen": sample_server().to_entry() } });
+
+    let cases = [
+        (
+            ClientType::ClaudeCode,
+            json!({ "type": "sse", "url": "https://mcp.example.se/sse", "headers": { "Authorization": "Bearer abc" } }),
+        ),
+        (
+            ClientType::Cursor,
+            json!({ "url": "https://mcp.example.se/sse", "headers": { "Authorization": "Bearer abc" } }),
+        ),
+        (
+            ClientType::Windsurf,
+            json!({ "serverUrl": "https://mcp.example.se/sse", "headers": { "Authorization": "Bearer abc" } }),
+        ),
+    ];
+    for (client, native) in cases {
+        let written = client_format::to_client_format(&client, &staten);
+        assert_eq!(written["mcpServers"]["riksdagen"], native, "{:?}", client);
+        assert_eq!(
+            client_format::from_client_format(&client, written),
+            staten,
+            "{:?}",
+            client
+        );
+    }
+
+    let goose = client_format::to_client_format(&ClientType::Goose, &staten);
+    let extension = &goose["extensions"]["riksdagen"];
+    assert_eq!(extension["type"], "sse");
+    assert_eq!(extension["uri"], "https://mcp.example.se/sse");
+    assert_eq!(extension["enabled"], true);
+    let read = client_format::from_client_format(&ClientType::Goose, goose);
+    assert_eq!(
+        RemoteServer::from_entry(&read["mcpServers"]["riksdagen"]),
+        Some(sample_server())
+    );
+}
+
+#[test]
+fn test_proxied_remote_entries() {
+    let staten = json!({ "mcpServers": { "riksdagen": sample_server().to_entry() } });
+
+    for client in [ClientType::Claude, ClientType::Codex, ClientType::Continue] {
+        let written = client_format::to_client_format(&client, &staten);
+        assert_eq!(
+            client_format::from_client_format(&client, written)["mcpServers"],
+            staten["mcpServers"],
+            "{:?}",
+            client
+        );
+    }
+
+    let claude = client_format::to_client_format(&ClientType::Claude, &staten);
+    assert_eq!(
+        claude["mcpServers"]["riksdagen"],
+        json!({
+            "command": remote::proxy_command(),
+            "args": remote::proxy_args(&sample_server()),
+            "env": remote::proxy_env(&sample_server())
+        })
+    );
+
+    let zed = client_format::to_client_format(&ClientType::Zed, &staten);
+    assert_eq!(
+        zed["context_servers"]["riksdagen"]["command"]["path"],
+        json!(remote::proxy_command())
+    );
+    assert_eq!(
+        client_format::from_client_format(&ClientType::Zed, zed),
+        staten
+    );
+}
+
+#[test]
+fn test_oauth_servers_are_always_proxied() {
+    let server = RemoteServer {
+        oauth: true,
+        ..sample_server()
+    };
+    let staten = json!({ "mcpServers": { "riksdagen": server.to_entry() } });
+    assert_eq!(staten["mcpServers"]["riksdagen"]["auth"], "oauth");
+
+    let written = client_format::to_client_format(&ClientType::ClaudeCode, &staten);
+    let args = written["mcpServers"]["riksdagen"]["args"]
+        .as_array()
+        .unwrap();
+    assert!(args.contains(&json!("--oauth")));
+    assert_eq!(
+        client_format::from_client_format(&ClientType::ClaudeCode, written),
+        staten
+    );
+}
+
+#[test]
+#[serial]
+fn test_install_remote_app() {
+    environment::set_test_mode(true);
+    set_test_registry();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config_path = temp_dir.path().join("claude_desktop_config.json");
+    app::set_test_config_path(Some(config_path.clone()));
+
+    app::install_app(
+        "Riksdagen",
+        Some(json!({ "RIKSDAGEN_TOKEN": "secret" })),
+        "Claude",
+        None,
+    )
+    .unwrap();
+
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(
+        written["mcpServers"]["riksdagen"]["args"],
+        json!([
+            "proxy",
+            "--transport",
+            "http",
+            "https://mcp.example.se/riksdagen"
+        ])
+    );
+    assert_eq!(
+        written["mcpServers"]["riksdagen"]["env"],
+        json!({ remote::PROXY_HEADERS_ENV: "Authorization: Bearer secret" })
+    );
+
+    // Values are read back out of the headers
+    assert_eq!(
+        app::get_app_env("Riksdagen", "Claude").unwrap(),
+        json!({ "RIKSDAGEN_TOKEN": "secret" })
+    );
+    app::save_app_env(
+        "Riksdagen",
+        json!({ "RIKSDAGEN_TOKEN": "rotated" }),
+        "Claude",
+    )
+    .unwrap();
+    assert_eq!(
+        app::get_app_env("Riksdagen", "Claude").unwrap(),
+        json!({ "RIKSDAGEN_TOKEN": "rotated" })
+    );
+
+    let statuses = app::app_statuses("Claude", None).unwrap();
+    assert_eq!(statuses["installed"]["Riksdagen"], true);
+    assert_eq!(statuses["configured"]["Riksdagen"], true);
+
+    app::set_test_config_path(None);
+    clear_test_registry();
+    environment::set_test_mode(false);
+}
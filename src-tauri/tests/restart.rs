@@ -0,0 +1,44 @@
+mod common;
+
+use staten_lib::{clients::ClientType, restart};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_tasklist_contains() {
+    let output = "\"Cursor.exe\",\"1234\",\"Console\",\"1\",\"210,000 K\"\r\n";
+    assert!(restart::tasklist_contains(output, "cursor.exe"));
+    assert!(!restart::tasklist_contains(output, "claude.exe"));
+    assert!(!restart::tasklist_contains(
+        "INFO: No tasks are running which match the specified criteria.",
+        "claude.exe"
+    ));
+}
+
+#[test]
+fn test_wait_for() {
+    let mut calls = 0;
+    assert!(restart::wait_for(
+        || {
+            calls += 1;
+            calls == 3
+        },
+        Duration::from_secs(5),
+        Duration::from_millis(1)
+    ));
+    assert_eq!(calls, 3);
+
+    let started = Instant::now();
+    assert!(!restart::wait_for(
+        || false,
+        Duration::from_millis(50),
+        Duration::from_millis(10)
+    ));
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test_every_client_has_a_process_name() {
+    for client in ClientType::all() {
+        assert!(!restart::process_name(&client).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+mod common;
+
+use serial_test::serial;
+use staten_lib::{
+    app, clients,
+    clients::ClientType,
+    environment, node,
+    settings::{self, ClientPathOverride},
+};
+
+#[test]
+#[serial]
+fn test_client_path_override_is_persisted() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let settings_path = temp_dir.path().join("settings.json");
+    settings::set_test_settings_path(Some(settings_path.clone()));
+
+    let claude_dir = temp_dir.path().join("claude");
+    std::fs::create_dir_all(&claude_dir).unwrap();
+    std::fs::write(claude_dir.join("custom.json"), r#"{"mcpServers": {}}"#).unwrap();
+
+    app::set_client_config_path(
+        "Claude".to_string(),
+        claude_dir.to_str().unwrap(),
+        "custom.json",
+    )
+    .unwrap();
+
+    let saved = settings::load_settings().unwrap();
+    assert_eq!(
+        saved.client_path_overrides["Claude"],
+        ClientPathOverride {
+            base_dir: claude_dir.to_string_lossy().to_string(),
+            config_filename: "custom.json".to_string(),
+        }
+    );
+
+    // A restart re-reads the override from the settings file
+    clients::reload_client_path_configs();
+    let config = clients::get_client_path_config(&ClientType::Claude).unwrap();
+    assert_eq!(config.base_dir, claude_dir);
+    assert_eq!(config.config_filename, "custom.json");
+
+    app::reset_client_config_path("Claude".to_string()).unwrap();
+    assert!(settings::load_settings()
+        .unwrap()
+        .client_path_overrides
+        .is_empty());
+    assert_eq!(
+        clients::get_client_path_config(&ClientType::Claude).unwrap(),
+        clients::get_default_client_path_config(&ClientType::Claude).unwrap()
+    );
+
+    settings::set_test_settings_path(None);
+    clients::reload_client_path_configs();
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_client_path_override_is_validated() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+
+    let missing = temp_dir.path().join("missing");
+    assert!(app::set_client_config_path(
+        "Cursor".to_string(),
+        missing.to_str().unwrap(),
+        "mcp.json"
+    )
+    .unwrap_err()
+    .contains("does not exist"));
+
+    std::fs::write(temp_dir.path().join("broken.json"), "{ not json").unwrap();
+    assert!(app::set_client_config_path(
+        "Cursor".to_string(),
+        temp_dir.path().to_str().unwrap(),
+        "broken.json"
+    )
+    .unwrap_err()
+    .contains("not valid JSON"));
+
+    std::fs::write(temp_dir.path().join("list.json"), r#"{"mcpServers": []}"#).unwrap();
+    assert!(app::set_client_config_path(
+        "Cursor".to_string(),
+        temp_dir.path().to_str().unwrap(),
+        "list.json"
+    )
+    .is_err());
+
+    assert!(settings::load_settings()
+        .unwrap()
+        .client_path_overrides
+        .is_empty());
+
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+fn test_migrate_v1_settings() {
+    let v1 = serde_json::json!({
+        "clientPathOverrides": {"Cursor": {"baseDir": "/tmp/cursor", "configFilename": "mcp.json"}}
+    });
+
+    let migrated = settings::migrate(v1, true).unwrap();
+    assert_eq!(migrated["schemaVersion"], settings::SETTINGS_SCHEMA_VERSION);
+    assert!(migrated.get("onboardingCompleted").is_none());
+    assert_eq!(migrated["onboarding"].as_object().unwrap().len(), 4);
+
+    let parsed: settings::Settings = serde_json::from_value(migrated).unwrap();
+    assert_eq!(
+        parsed.client_path_overrides["Cursor"].base_dir,
+        "/tmp/cursor"
+    );
+    assert_eq!(
+        parsed.registry_sources,
+        vec![settings::DEFAULT_REGISTRY_URL.to_string()]
+    );
+    assert_eq!(parsed.auto_update, settings::AutoUpdatePolicy::Install);
+
+    assert!(settings::migrate(serde_json::json!([]), false).is_err());
+}
+
+#[test]
+#[serial]
+fn test_onboarding_marker_is_migrated() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let settings_path = temp_dir.path().join("settings.json");
+    let marker = temp_dir.path().join("onboarding_completed");
+    std::fs::write(&marker, "").unwrap();
+    settings::set_test_settings_path(Some(settings_path.clone()));
+
+    assert!(app::check_onboarding_completed().unwrap());
+    assert!(!marker.exists());
+    assert!(settings_path.exists());
+
+    app::reset_onboarding_completed().unwrap();
+    assert!(!app::check_onboarding_completed().unwrap());
+
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_update_settings() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+
+    let updated = settings::update_settings(serde_json::json!({
+        "defaultClient": "Cursor",
+        "autoUpdate": "notify",
+        "logLevel": "warn"
+    }))
+    .unwrap();
+    assert_eq!(updated["defaultClient"], "Cursor");
+    assert_eq!(clients::get_default_client(), ClientType::Cursor);
+
+    let stored = settings::load_settings().unwrap();
+    assert_eq!(stored.auto_update, settings::AutoUpdatePolicy::Notify);
+    assert_eq!(stored.log_level, Some(settings::LogLevel::Warn));
+
+    assert!(settings::update_settings(serde_json::json!({"defaultClient": "Nope"})).is_err());
+    assert!(settings::update_settings(serde_json::json!({"registrySources": []})).is_err());
+    assert!(settings::update_settings(serde_json::json!({"autoUpdate": "sometimes"})).is_err());
+    assert!(settings::update_settings(serde_json::json!({"nodeVersion": "latest"})).is_err());
+    assert!(settings::update_settings(serde_json::json!({"uvMirror": "mirror.local"})).is_err());
+    let error = settings::update_settings(serde_json::json!({"logLvl": "debug"})).unwrap_err();
+    assert!(error.contains("logLvl"), "{}", error);
+    assert_eq!(
+        settings::load_settings().unwrap().default_client.as_deref(),
+        Some("Cursor")
+    );
+
+    // The default Node.js version follows the setting
+    settings::update_settings(serde_json::json!({"nodeVersion": "20.18.1"})).unwrap();
+    assert_eq!(node::default_node_version(), "v20.18.1");
+
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
+
+#[test]
+#[serial]
+fn test_concurrent_updates_are_not_lost() {
+    environment::set_test_mode(true);
+    let temp_dir = tempfile::tempdir().unwrap();
+    settings::set_test_settings_path(Some(temp_dir.path().join("settings.json")));
+
+    let threads: Vec<_> = (0..8)
+        .map(|i| {
+            std::thread::spawn(move || {
+                settings::modify_settings(|current| {
+                    current.projects.push(format!("/work/project-{}", i));
+                    Ok(())
+                })
+                .unwrap();
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(settings::load_settings().unwrap().projects.len(), 8);
+
+    settings::set_test_settings_path(None);
+    environment::set_test_mode(false);
+}
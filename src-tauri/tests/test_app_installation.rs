@@ -50,7 +50,7 @@ fn test_full_app_lifecycle() {
     }
 
     // Test installation
-    let install_result = app::install("Browser", None, ClientType::Cursor.as_str());
+    let install_result = app::install_app("Browser", None, ClientType::Cursor.as_str(), None);
     assert!(
         install_result.is_ok(),
         "Install failed: {:?}",
@@ -59,7 +59,7 @@ fn test_full_app_lifecycle() {
     assert!(app::is_installed("Browser", ClientType::Cursor.as_str()).unwrap());
 
     // Test uninstallation
-    let uninstall_result = app::uninstall("Browser", ClientType::Cursor.as_str());
+    let uninstall_result = app::uninstall("Browser", ClientType::Cursor.as_str(), None);
     assert!(uninstall_result.is_ok());
     assert!(!app::is_installed("Browser", ClientType::Cursor.as_str()).unwrap());
 
@@ -0,0 +1,127 @@
+mod common;
+
+use staten_lib::uv;
+
+#[test]
+fn test_target_triple() {
+    assert_eq!(
+        uv::target_triple("macos", "aarch64").as_deref(),
+        Some("aarch64-apple-darwin")
+    );
+    assert_eq!(
+        uv::target_triple("windows", "x86_64").as_deref(),
+        Some("x86_64-pc-windows-msvc")
+    );
+    assert_eq!(
+        uv::target_triple("linux", "x86_64").as_deref(),
+        Some("x86_64-unknown-linux-gnu")
+    );
+    assert_eq!(uv::target_triple("freebsd", "x86_64"), None);
+    assert_eq!(uv::target_triple("linux", "riscv64"), None);
+}
+
+#[test]
+fn test_parse_manifest() {
+    let checksum = "A1B2C3D4E5F60718293A4B5C6D7E8F90A1B2C3D4E5F60718293A4B5C6D7E8F90";
+    let manifest = uv::parse_manifest(&format!(
+        r#"{{ "version": "0.5.11", "sha256": {{ "aarch64-apple-darwin": "{}" }} }}"#,
+        checksum
+    ))
+    .unwrap();
+    assert_eq!(manifest.version, "0.5.11");
+    assert_eq!(manifest.sha256["aarch64-apple-darwin"], checksum);
+
+    assert!(uv::parse_manifest(
+        r#"{ "version": "0.5.11", "sha256": { "aarch64-apple-darwin": "abc" } }"#
+    )
+    .is_err());
+    assert!(uv::parse_manifest(r#"{ "version": "../0.5.11", "sha256": {} }"#).is_err());
+    assert!(uv::parse_manifest(r#"{ "sha256": {} }"#).is_err());
+
+    // The manifest bundled with the build pins every platform
+    // scripts/update-uv-manifest.sh covers
+    let bundled = uv::uv_manifest().unwrap();
+    for os in ["macos", "linux", "windows"] {
+        for arch in ["aarch64", "x86_64"] {
+            let target = uv::target_triple(os, arch).unwrap();
+            assert!(
+                bundled.sha256.contains_key(&target),
+                "uv-manifest.json has no checksum for {}",
+                target
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod install {
+    use super::*;
+    use serial_test::serial;
+    use staten_lib::download;
+    use std::collections::BTreeMap;
+
+    fn set_manifest(version: &str, target: &str, checksum: Option<String>) {
+        uv::set_test_uv_manifest(Some(uv::UvManifest {
+            version: version.to_string(),
+            sha256: checksum
+                .map(|checksum| BTreeMap::from([(target.to_string(), checksum)]))
+                .unwrap_or_default(),
+        }));
+    }
+
+    #[test]
+    #[serial]
+    fn test_install_uv() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        uv::set_test_uv_dir(Some(temp_dir.path().to_path_buf()));
+
+        let target = uv::target_triple(std::env::consts::OS, std::env::consts::ARCH).unwrap();
+        let (uv_bin, uvx_bin) = (format!("uv-{}/uv", target), format!("uv-{}/uvx", target));
+        let archive = common::tar_gz(&[
+            (uv_bin.as_str(), &b"#!/bin/sh\n"[..]),
+            (uvx_bin.as_str(), &b"#!/bin/sh\n"[..]),
+        ]);
+        let base = common::serve(vec![(
+            format!("/0.5.11/uv-{}.tar.gz", target),
+            archive.clone(),
+        )]);
+        std::env::set_var(uv::UV_MIRROR_ENV, format!("{}/", base));
+
+        // Without a bundled checksum nothing is downloaded
+        set_manifest("0.5.11", &target, None);
+        assert!(uv::install_uv().unwrap_err().contains("No checksum"));
+
+        // A download that does not match the manifest is never installed
+        set_manifest("0.5.11", &target, Some(download::sha256_hex(b"tampered")));
+        assert!(uv::install_uv().unwrap_err().contains("Checksum mismatch"));
+        assert_eq!(uv::installed_uv_version(), None);
+
+        set_manifest("0.5.11", &target, Some(download::sha256_hex(&archive)));
+        let (uv_path, uvx_path) = uv::install_uv().unwrap();
+        assert_eq!(uv_path, temp_dir.path().join("uv"));
+        assert_eq!(uvx_path, temp_dir.path().join("uvx"));
+        assert_eq!(uv::installed_uv_version().as_deref(), Some("0.5.11"));
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&uvx_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+
+        // Nothing is left over next to the binaries
+        let mut entries: Vec<_> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, vec![".uv-version", "uv", "uvx"]);
+
+        // A new pin is downloaded from the mirror, which does not have it
+        set_manifest("0.6.0", &target, Some(download::sha256_hex(&archive)));
+        assert!(uv::install_uv().is_err());
+        assert_eq!(uv::installed_uv_version().as_deref(), Some("0.5.11"));
+
+        std::env::remove_var(uv::UV_MIRROR_ENV);
+        uv::set_test_uv_manifest(None);
+        uv::set_test_uv_dir(None);
+    }
+}
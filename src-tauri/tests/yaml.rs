@@ -0,0 +1,75 @@
+mod common;
+
+use serde_json::json;
+use staten_lib::yaml;
+
+const CONFIG: &str = "# Continue config
+name: Local Assistant
+version: 1.0.0
+models:
+  - name: gpt # keep me
+    provider: openai
+mcpServers:
+  - name: old
+    command: old
+
+# Rules for the agent
+rules:
+  - Be brief
+";
+
+#[test]
+fn test_parse() {
+    let value = yaml::parse(CONFIG).unwrap();
+    assert_eq!(value["name"], "Local Assistant");
+    assert_eq!(value["mcpServers"][0]["command"], "old");
+    assert_eq!(value["rules"], json!(["Be brief"]));
+
+    assert_eq!(yaml::parse("").unwrap(), json!({}));
+    assert_eq!(yaml::parse("# only a comment\n").unwrap(), json!({}));
+    assert!(yaml::parse("a: [").is_err());
+}
+
+#[test]
+fn test_set_member_replaces_only_that_block() {
+    let updated = yaml::set_member(
+        CONFIG,
+        "mcpServers",
+        &json!([{ "name": "time", "command": "uvx" }]),
+    )
+    .unwrap();
+
+    assert!(updated.starts_with(
+        "# Continue config\nname: Local Assistant\nversion: 1.0.0\nmodels:\n  - name: gpt # keep me\n"
+    ));
+    assert!(updated.ends_with("\n# Rules for the agent\nrules:\n  - Be brief\n"));
+    assert!(!updated.contains("old"));
+
+    let value = yaml::parse(&updated).unwrap();
+    assert_eq!(
+        value["mcpServers"],
+        json!([{ "name": "time", "command": "uvx" }])
+    );
+    assert_eq!(value["models"][0]["provider"], "openai");
+}
+
+#[test]
+fn test_set_member_adds_missing_key() {
+    let updated = yaml::set_member("GOOSE_MODEL: gpt-4o", "extensions", &json!({})).unwrap();
+    assert_eq!(updated, "GOOSE_MODEL: gpt-4o\nextensions: {}\n");
+
+    let updated = yaml::set_member("", "extensions", &json!({})).unwrap();
+    assert_eq!(updated, "extensions: {}\n");
+}
+
+#[test]
+fn test_set_member_drops_comments_inside_the_block() {
+    let text =
+        "extensions:\n  # Pinned\n  time:\n    cmd: uvx # dropped\nGOOSE_MODEL: gpt-4o # kept\n";
+    let updated =
+        yaml::set_member(text, "extensions", &json!({ "time": { "cmd": "uvx" } })).unwrap();
+    assert_eq!(
+        updated,
+        "extensions:\n  time:\n    cmd: uvx\nGOOSE_MODEL: gpt-4o # kept\n"
+    );
+}